@@ -1,17 +1,31 @@
-use engine::audio::init_audio;
+use engine::audio::synth::SynthParams;
+use engine::audio::{Mixer, SoundManager};
+use engine::error::Error;
+use engine::math::Vec2;
+use engine::res;
 
 use crate::logic::Logic;
 
 pub const CHANNEL_COUNT: i32 = 6;
+pub const EXPLOSION_SOUND_PATH: &str = res!("explosion.sfx");
 
-pub struct Audio {}
+/// The sounds of the game are generated, and heard on the side of the board they happen on.
+pub struct Audio {
+    sounds: SoundManager,
+}
 
 impl Audio {
-    pub fn new() -> Audio {
-        init_audio(CHANNEL_COUNT);
-
-        Audio {}
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+        sounds.add_synth("explosion", &SynthParams::load(EXPLOSION_SOUND_PATH)?, 0, 1.)?;
+        Ok(Audio { sounds })
     }
 
     pub fn update(&mut self, _logic: &Logic) {}
+
+    /// Play the explosion of an asteroid.
+    pub fn play_explosion(&self, position: Vec2) {
+        self.sounds.play_at("explosion", position);
+    }
 }
@@ -1,24 +1,82 @@
+use engine::collide::{overlap, Shape};
+use engine::ecs::{Entity, World};
+use engine::geometry::AsCircle;
+use engine::math::Vec2;
+use engine::physics::CircleSolid;
+use engine::spatial::Grid;
 
-use crate::logic::{Logic, Asteroid, Bullet};
-use engine::geometry::AsRect;
-use engine::collide::collide;
-
-fn collide_shell_and_asteroids(asteroids : &mut Vec<Asteroid>, bullets : &mut Vec<Bullet>){
-    for bullet in bullets{
-        let bullet_rect = bullet.as_rect();
-        for asteroid in &mut *asteroids {
-            let asteroid_rect = asteroid.as_rect();
-            match collide(&bullet_rect,&asteroid_rect) {
-                None => (),
-                Some(_rect) => {
-                    bullet.destroy();
-                    asteroid.destroy();
-                }
+use crate::audio::Audio;
+use crate::logic::{Asteroid, Bullet, Logic};
+
+/// The asteroids are indexed again by the grid, so each bullet is only tested against the asteroids around it.
+///
+/// An asteroid going over an edge of the board can also be hit on the other side.
+/// The asteroids hit explode with a sound, once even if several bullets hit them, and their positions are returned.
+fn collide_shell_and_asteroids(world: &World, grid: &mut Grid<Entity>, audio: &Audio) -> Vec<Vec2> {
+    let solids = world.storage::<CircleSolid>();
+    grid.clear();
+    for (asteroid, _) in world.storage::<Asteroid>().iter() {
+        for circle in solids.get(asteroid).unwrap().wrapped_circles() {
+            grid.insert_circle(asteroid, &circle);
+        }
+    }
+
+    let mut destroyed = Vec::new();
+    let mut explosions = Vec::new();
+    for (bullet, _) in world.storage::<Bullet>().iter() {
+        let bullet_solid = solids.get(bullet).unwrap();
+        let bullet_shape = Shape::from(bullet_solid.as_circle());
+        for asteroid in grid.query_circle(bullet_solid) {
+            if destroyed.contains(&asteroid) {
+                continue;
+            }
+            let asteroid_solid = solids.get(asteroid).unwrap();
+            let circles = asteroid_solid.wrapped_circles();
+            if circles.into_iter().any(|circle| overlap(&bullet_shape, &Shape::from(circle)).is_some()) {
+                world.despawn(bullet);
+                world.despawn(asteroid);
+                destroyed.push(asteroid);
+                audio.play_explosion(asteroid_solid.pos);
+                explosions.push(asteroid_solid.pos);
+                break;
             }
         }
     }
+    explosions
+}
+
+/// Check all the collisions, then remove what they destroyed from the world, returning where the asteroids exploded.
+pub fn check_collision(logic: &mut Logic, audio: &Audio) -> Vec<Vec2> {
+    let explosions = collide_shell_and_asteroids(&logic.world, &mut logic.asteroid_grid, audio);
+    logic.world.maintain();
+    explosions
 }
 
-pub fn check_collision(logic: &mut Logic) {
-    collide_shell_and_asteroids(&mut logic.asteroids.vec, &mut logic.spaceship.bullets);
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use engine::audio::NullMixer;
+    use engine::random::Rng;
+
+    use super::*;
+
+    #[test]
+    fn asteroids_hit_by_two_bullets_explode_once() {
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let audio = Audio::new(Box::new(mixer)).unwrap();
+        let mut logic = Logic::new(Rng::new(1));
+
+        let (asteroid, position) = {
+            let solids = logic.world.storage::<CircleSolid>();
+            let (asteroid, _) = logic.world.storage::<Asteroid>().iter().next().unwrap();
+            (asteroid, solids.get(asteroid).unwrap().pos)
+        };
+        Bullet::spawn(&mut logic.world, position.x, position.y, 0.);
+        Bullet::spawn(&mut logic.world, position.x, position.y, 1.);
+
+        assert_eq!(check_collision(&mut logic, &audio), vec![position]);
+        assert!(!logic.world.is_alive(asteroid));
+        assert_eq!(record.borrow().played.len(), 1);
+        assert_eq!(logic.world.storage::<Bullet>().iter().count(), 1);
+    }
+}
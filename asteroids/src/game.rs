@@ -0,0 +1,100 @@
+use sdl2::event::Event;
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::graphics::Window;
+use engine::random::Rng;
+
+use crate::audio::Audio;
+use crate::collide::check_collision;
+use crate::event::handle_event;
+use crate::graphics::Graphics;
+use crate::logic::Logic;
+
+/// The asteroids game, gathering the logic, the audio and the graphics for the game loop.
+pub struct Asteroids {
+    logic: Logic,
+    audio: Audio,
+    graphics: Graphics,
+}
+
+impl Asteroids {
+    /// Create a new game, the seed giving all its random events.
+    pub fn new(audio: Audio, canvas_width: u32, canvas_height: u32, ttf_context: &Sdl2TtfContext, seed: u64) -> Result<Asteroids, Error> {
+        let mut rng = Rng::new(seed);
+        let mut assets = Assets::new(ttf_context);
+        Ok(Asteroids {
+            logic: Logic::new(rng.fork()),
+            audio,
+            graphics: Graphics::new(rng.fork(), canvas_width, canvas_height, &mut assets)?,
+        })
+    }
+}
+
+impl Game for Asteroids {
+    fn handle_event(&mut self, event: Event) {
+        handle_event(event, &mut self.logic, &self.audio);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.graphics.save_positions(&self.logic);
+        self.logic.update(dt);
+        self.audio.update(&self.logic);
+        self.graphics.animate(dt);
+    }
+
+    fn collide(&mut self, _dt: f32) {
+        for position in check_collision(&mut self.logic, &self.audio) {
+            self.graphics.explode(position);
+        }
+    }
+
+    fn render(&mut self, window: &mut Window, alpha: f32) {
+        self.graphics.update(&self.logic, window, alpha);
+        self.graphics.draw(window);
+    }
+
+    fn is_over(&self) -> bool {
+        self.logic.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Keycode;
+
+    use engine::audio::NullMixer;
+    use engine::game_loop::simulate;
+    use engine::graphics::{DrawCall, NullRenderer};
+    use engine::backend::key_down;
+
+    use crate::logic::{Asteroid, Bullet, ASTEROID_STARTING_NUMBER};
+    use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+    use super::*;
+
+    #[test]
+    fn full_match_runs_headless() {
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let renderer = NullRenderer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+        let frames = renderer.record();
+        let mut window = Window { canvas: Box::new(renderer), event_pump: None };
+
+        let mut asteroids = Asteroids::new(Audio::new(Box::new(NullMixer::new())).unwrap(), WINDOW_WIDTH, WINDOW_HEIGHT, &ttf_context, 1).unwrap();
+
+        // The spaceship spins on itself while firing during half a minute.
+        asteroids.handle_event(key_down(Keycode::Left));
+        asteroids.handle_event(key_down(Keycode::F));
+        simulate(&mut asteroids, &mut window, 30 * 120);
+
+        assert!(!asteroids.is_over());
+        assert!(!asteroids.logic.world.storage::<Bullet>().is_empty());
+        assert!(asteroids.logic.world.storage::<Asteroid>().len() <= ASTEROID_STARTING_NUMBER as usize);
+
+        let frames = frames.borrow();
+        assert_eq!(frames.frame_count, 30 * 120);
+        assert!(frames.calls.iter().any(|call| matches!(call, DrawCall::Image { .. })));
+    }
+}
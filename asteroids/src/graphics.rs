@@ -1,19 +1,52 @@
+use std::f32::consts::{PI, TAU};
+
 use sdl2::pixels::Color;
-use sdl2::rect::{Point};
-use sdl2::render::WindowCanvas;
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::rect::{Point, Rect};
 
+use engine::animation::{Animation, Clip, Playback, SpriteSheet};
+use engine::assets::{Assets, Image};
+use engine::ecs::{Storage, World};
+use engine::error::Error;
+use engine::game_loop::interpolate;
+use engine::geometry;
 use engine::geometry::AsRect;
-use engine::graphics::{RectSprite, Sprite, Window};
+use engine::graphics::{Camera, RectSprite, Renderer, Sprite, Window};
+use engine::math::Vec2;
+use engine::particles::{Emitter, ParticleConfig, ParticleSystem};
+use engine::physics::CircleSolid;
 
 use crate::logic;
 use crate::logic::Logic;
-use engine::random::rand;
-use engine::geometry;
-
-pub const SPACESHIP_SPRITE_PATH: &str = "res/spaceship.bmp";
-pub const SPACESHIP_ACCELERATING_SPRITE_PATH: &str = "res/accelerating_spaceship.bmp";
-pub const ASTEROID_SPRITE_PATHS: [&str;4] = ["res/asteroid_0.bmp","res/asteroid_1.bmp","res/asteroid_2.bmp","res/asteroid_3.bmp"];
+use engine::random::Rng;
+use engine::res;
+
+pub const SPACESHIP_SPRITE_PATH: &str = res!("spaceship.bmp");
+pub const SPACESHIP_ACCELERATING_SPRITE_PATH: &str = res!("accelerating_spaceship.bmp");
+pub const THRUST_FLICKER_DURATION: f32 = 0.05;
+pub const ASTEROID_SPRITE_PATHS: [&str;4] = [res!("asteroid_0.bmp"),res!("asteroid_1.bmp"),res!("asteroid_2.bmp"),res!("asteroid_3.bmp")];
+
+pub const PARTICLE_CAPACITY: usize = 512;
+pub const DEBRIS_PARTICLE_COUNT: usize = 30;
+pub const DEBRIS: ParticleConfig = ParticleConfig {
+    lifetime: (0.4, 1.2),
+    speed: (0.05, 0.25),
+    direction: 0.,
+    spread: TAU,
+    gravity: Vec2::ZERO,
+    colors: (Color::RGB(200, 200, 200), Color::RGBA(100, 100, 100, 0)),
+    sizes: (0.008, 0.003),
+};
+/// Rate of the exhaust of the spaceship, in particles per second.
+pub const EXHAUST_RATE: f32 = 120.;
+pub const EXHAUST: ParticleConfig = ParticleConfig {
+    lifetime: (0.15, 0.35),
+    speed: (0.2, 0.4),
+    direction: 0.,
+    spread: 0.6,
+    gravity: Vec2::ZERO,
+    colors: (Color::RGB(255, 240, 120), Color::RGBA(255, 60, 0, 0)),
+    sizes: (0.008, 0.002),
+};
 
 pub struct Bullet {
     sprite: RectSprite
@@ -27,153 +60,169 @@ impl Bullet {
     }
 }
 
-pub struct Asteroid<'a> {
-    sprite: Sprite<'a>,
-    logic_id : u32
+/// Sprites of the asteroids, by entity of the logic world.
+pub struct Asteroids{
+    sprites: Storage<Sprite>,
+    images: Vec<Image>,
+    rng: Rng,
 }
 
-impl  Asteroid<'_> {
-    pub  fn new<'a> (logic_id : u32)-> Asteroid<'a> {
-        let sprite_index = rand(0, ASTEROID_SPRITE_PATHS.len() as i32);
-        Asteroid {
-            sprite: Sprite::from_bmp(ASTEROID_SPRITE_PATHS[sprite_index as usize]),
-            logic_id
-        }
+impl Asteroids{
+    /// The asteroid images are loaded once, and shared by all the asteroids.
+    pub  fn new (rng: Rng, assets: &mut Assets)-> Result<Asteroids, Error> {
+        let images = ASTEROID_SPRITE_PATHS.iter().map(|path| assets.image(path)).collect::<Result<Vec<Image>, Error>>()?;
+        Ok(Asteroids {
+            sprites: Storage::new(),
+            images,
+            rng,
+        })
     }
-}
 
-pub struct Asteroids<'a>{
-    vec: Vec<Asteroid<'a>>
-}
+    /// Drop the sprites of the destroyed asteroids, and give a random image to the new ones.
+    pub fn update(&mut self, world: &World, camera: &Camera){
+        self.sprites.retain(|entity, _| world.is_alive(entity));
 
-impl Asteroids<'_>{
-    pub  fn new<'a> ()-> Asteroids<'a> {
-         Asteroids {
-            vec: Vec::new()
-        }
-    }
-
-    pub fn is_existing(&self, logic_id : u32) -> bool {
-        for asteroid in &self.vec {
-            if logic_id == asteroid.logic_id{
-                return true
-            }
-        }
-        return false
-    }
-
-    pub fn create(&mut self, logic_id : u32) {
-        let asteroid = Asteroid::new(logic_id);
-        self.vec.push(asteroid);
-    }
-
-    pub fn update(&mut self, logic_id : u32, logic_rect : geometry::Rect, w : u32, h : u32){
-        for asteroid in &mut self.vec {
-            if logic_id == asteroid.logic_id{
-                asteroid.sprite.update(logic_rect, 0., w, h);
-                return
+        let solids = world.storage::<CircleSolid>();
+        for (entity, _) in world.storage::<logic::Asteroid>().iter() {
+            if !self.sprites.contains(entity) {
+                let sprite_index = self.rng.rand(0, self.images.len() as i32);
+                self.sprites.insert(entity, Sprite::from_image(self.images[sprite_index as usize].clone()));
             }
+            let rect = logic::Asteroid::rect(solids.get(entity).unwrap());
+            self.sprites.get_mut(entity).unwrap().update(rect, 0., camera);
         }
     }
 
-    pub fn draw(&self, canvas : &mut WindowCanvas){
-        for asteroid in &self.vec {
-            asteroid.sprite.draw(canvas);
+    pub fn draw(&self, canvas: &mut dyn Renderer, bounds: Rect){
+        for (_, sprite) in self.sprites.iter() {
+            sprite.draw_wrapped(canvas, bounds);
         }
     }
 }
 
-pub struct Spaceship<'a> {
-    sprite: Sprite<'a>,
-    accelerating_sprite: Sprite<'a>,
+/// Farthest a bullet is drawn from its previous position, it has gone over a side of the board when it moves more in a step.
+pub const BULLET_MAX_STEP: f32 = 0.1;
+
+/// The spaceship flickers between its two images and leaves an exhaust behind it while it accelerates.
+///
+/// The bullets are drawn between their positions before and after the last step.
+pub struct Spaceship {
+    sprite: Sprite,
+    sheet: SpriteSheet,
+    animation: Animation,
+    exhaust: Emitter,
+    exhaust_position: Vec2,
     speed_point: Point,
-    accelerating: bool,
     bullets: Vec<Bullet>,
+    previous_bullets: Storage<Vec2>,
 }
 
 
-impl Spaceship<'_> {
-    pub fn new() -> Spaceship<'static> {
-        Spaceship {
-            sprite: Sprite::from_bmp(SPACESHIP_SPRITE_PATH),
-            accelerating_sprite: Sprite::from_bmp(SPACESHIP_ACCELERATING_SPRITE_PATH),
+impl Spaceship {
+    pub fn new(assets: &mut Assets) -> Result<Spaceship, Error> {
+        let sheet = SpriteSheet::from_images(vec![
+            assets.image(SPACESHIP_SPRITE_PATH)?,
+            assets.image(SPACESHIP_ACCELERATING_SPRITE_PATH)?,
+        ]);
+        let mut animation = Animation::new();
+        animation.add("idle", Clip::uniform(&[0], 1., Playback::Loop));
+        animation.add("accelerating", Clip::uniform(&[1, 0], THRUST_FLICKER_DURATION, Playback::Loop));
+        animation.play("idle");
+
+        Ok(Spaceship {
+            sprite: Sprite::from_image(sheet.frame(0).0.clone()),
+            sheet,
+            animation,
+            exhaust: Emitter::new(EXHAUST, EXHAUST_RATE),
+            exhaust_position: Vec2::ZERO,
             speed_point: Point::new(0, 0),
-            accelerating: false,
             bullets: Vec::new(),
+            previous_bullets: Storage::new(),
+        })
+    }
+
+    /// Keep the positions of the bullets before a step of the logic.
+    pub fn save_positions(&mut self, world: &World) {
+        self.previous_bullets = Storage::new();
+        let solids = world.storage::<CircleSolid>();
+        for (entity, _) in world.storage::<logic::Bullet>().iter() {
+            self.previous_bullets.insert(entity, logic::Bullet::rect(solids.get(entity).unwrap()).min());
         }
     }
 
-    pub fn update(&mut self, logic_spaceship: &logic::Spaceship, w: u32, h: u32) {
-        self.sprite.update(logic_spaceship.as_rect(), logic_spaceship.orientation.to_degrees() as f64, w, h);
-        self.accelerating_sprite.update(logic_spaceship.as_rect(), logic_spaceship.orientation.to_degrees() as f64, w, h);
-        self.accelerating = logic_spaceship.accelerating;
+    pub fn update(&mut self, logic_spaceship: &logic::Spaceship, world: &World, camera: &Camera, alpha: f32) {
+        self.animation.play(if logic_spaceship.accelerating { "accelerating" } else { "idle" });
+        self.animation.apply(&self.sheet, &mut self.sprite);
+        self.sprite.update(logic_spaceship.as_rect(), logic_spaceship.orientation().to_degrees() as f64, camera);
+
+        if logic_spaceship.accelerating {
+            self.exhaust.start();
+        } else {
+            self.exhaust.stop();
+        }
+        let orientation = logic_spaceship.orientation();
+        self.exhaust.config.direction = orientation + PI;
+        self.exhaust_position = logic_spaceship.solid.pos - Vec2::polar(logic_spaceship.solid.r, orientation);
 
-        let xc = self.sprite.dest_rect.center().x();
-        let yc = self.sprite.dest_rect.center().y();
-        let speed_line_length = logic_spaceship.solid.vel.mag() * (w as f32);
-        let speed_line_angle = logic_spaceship.solid.vel.angle();
-        self.speed_point = Point::new(
-            xc + (speed_line_length as f32 * speed_line_angle.cos()) as i32,
-            yc + (speed_line_length as f32 * speed_line_angle.sin()) as i32,
-        );
+        let center = self.sprite.dest_rect.center();
+        let speed_line = camera.to_screen(logic_spaceship.solid.vel) - camera.to_screen(Vec2::ZERO);
+        self.speed_point = center + speed_line;
 
         self.bullets.clear();
-        for logic_bullet in logic_spaceship.bullets() {
+        let solids = world.storage::<CircleSolid>();
+        for (entity, _) in world.storage::<logic::Bullet>().iter() {
+            let rect = logic::Bullet::rect(solids.get(entity).unwrap());
+            let previous = self.previous_bullets.get(entity).copied().unwrap_or_else(|| rect.min());
+            let position = interpolate(previous, rect.min(), alpha, BULLET_MAX_STEP);
             let mut bullet = Bullet::new();
-            bullet.sprite.update(logic_bullet.as_rect(), w, h);
+            bullet.sprite.update(geometry::Rect::from_min_size(position, rect.size()), camera);
             self.bullets.push(bullet);
         }
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
-        match self.accelerating {
-            true => self.accelerating_sprite.draw(canvas),
-            false => self.sprite.draw(canvas)
-        }
+    pub fn draw(&self, canvas: &mut dyn Renderer, bounds: Rect) {
+        self.sprite.draw_wrapped(canvas, bounds);
 
         for bullet in &self.bullets {
             bullet.sprite.draw(canvas);
         }
 
         let start = self.sprite.dest_rect.center();
-        canvas.set_draw_color(Color::BLUE);
-        canvas.draw_line(start, self.speed_point).unwrap();
+        canvas.draw_line(start, self.speed_point, Color::BLUE);
     }
 }
 
-pub struct Graphics<'a> {
-    spaceship: Spaceship<'a>,
-    asteroids: Asteroids<'a>,
+/// The square space is shown by a camera, the sprites going over its sides being drawn on the other sides too.
+pub struct Graphics {
+    camera: Camera,
+    spaceship: Spaceship,
+    asteroids: Asteroids,
+    particles: ParticleSystem,
 }
 
-impl Graphics<'_> {
-    /// Init the dynamic elements required to draw the game
-    pub fn new(_canvas_width: u32, _canvas_height: u32, _ttf_context: &Sdl2TtfContext) -> Graphics {
-        Graphics {
-            spaceship: Spaceship::new(),
-            asteroids: Asteroids::new(),
-        }
+impl Graphics {
+    /// Init the dynamic elements required to draw the game.
+    ///
+    /// The random generator picks the sprite of each asteroid, and throws the particles.
+    pub fn new(mut rng: Rng, canvas_width: u32, canvas_height: u32, assets: &mut Assets) -> Result<Graphics, Error> {
+        Ok(Graphics {
+            camera: Camera::new(geometry::Rect::new(0., 0., 1., 1.), canvas_width, canvas_height),
+            spaceship: Spaceship::new(assets)?,
+            particles: ParticleSystem::new(PARTICLE_CAPACITY, rng.fork()),
+            asteroids: Asteroids::new(rng, assets)?,
+        })
     }
 
-    /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window, _ttf_context: &Sdl2TtfContext) {
-        let w = window.width();
-        let h = window.height();
-        self.spaceship.update(&logic.spaceship, w, h);
-
-        let ids = logic.asteroids.get_ids();
-        self.asteroids.vec.retain(|asteroid|{ids.contains(&asteroid.logic_id)});
-
-        for logic_asteroid in &logic.asteroids.vec {
-            let id = logic_asteroid.get_id();
-
-            if !self.asteroids.is_existing(id){
-                self.asteroids.create(id);
-            }
-            self.asteroids.update(logic_asteroid.get_id(), logic_asteroid.as_rect(), w, h);
-        }
-
+    /// Keep the positions of the fast elements before a step of the logic.
+    pub fn save_positions(&mut self, logic: &Logic) {
+        self.spaceship.save_positions(&logic.world);
+    }
 
+    /// Update the dynamic elements accordingly to the state of the game, alpha being the fraction of a step not simulated yet.
+    pub fn update(&mut self, logic: &Logic, window: &Window, alpha: f32) {
+        self.camera.fit(window);
+        self.spaceship.update(&logic.spaceship, &logic.world, &self.camera, alpha);
+        self.asteroids.update(&logic.world, &self.camera);
     }
 
     /// Draw the game.
@@ -183,10 +232,26 @@ impl Graphics<'_> {
     pub fn draw(&self, window: &mut Window) {
         window.clear();
 
-        let canvas = &mut window.canvas;
-        self.spaceship.draw(canvas);
-        self.asteroids.draw(canvas);
+        let bounds = self.camera.to_screen_rect(&geometry::Rect::new(0., 0., 1., 1.));
+        let canvas = window.canvas.as_mut();
+        self.spaceship.draw(canvas, bounds);
+        self.asteroids.draw(canvas, bounds);
+        self.particles.draw(canvas, &self.camera);
 
+        self.camera.draw_letterbox(canvas);
         canvas.present();
     }
+
+    /// Break an asteroid destroyed at the position, in the world, in debris.
+    pub fn explode(&mut self, position: Vec2) {
+        self.particles.burst(position, DEBRIS_PARTICLE_COUNT, &DEBRIS);
+    }
+
+    /// Advance the animations of the sprites and the particles.
+    pub fn animate(&mut self, dt: f32) {
+        self.spaceship.animation.update(dt);
+        let spaceship = &mut self.spaceship;
+        spaceship.exhaust.update(dt, spaceship.exhaust_position, &mut self.particles);
+        self.particles.update(dt);
+    }
 }
\ No newline at end of file
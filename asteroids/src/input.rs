@@ -1,3 +0,0 @@
-use crate::logic::{Logic};
-
-pub fn handle_input(_event_pump: &sdl2::EventPump, _logic: &mut Logic) {}
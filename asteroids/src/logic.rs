@@ -1,6 +1,9 @@
+use engine::ecs::{Entity, Systems, World};
 use engine::geometry::{AsRect, Rect};
-use engine::physics::{CircleSolid, Position, Velocity};
-use engine::random::rand;
+use engine::math::Vec2;
+use engine::physics::{Body, Boundary, CircleSolid, Position, Velocity};
+use engine::random::Rng;
+use engine::spatial::Grid;
 
 pub const SPACESHIP_RADIUS: f32 = 0.04;
 pub const SPACESHIP_STARTING_POSITION_X0: f32 = 0.5;
@@ -24,185 +27,103 @@ pub enum Turning {
     RIGHT,
 }
 
-pub struct Asteroid {
-    solid: CircleSolid,
-    id: u32,
-    pub is_destroyed : bool
-}
+/// Component of the asteroid entities, moving on a circle solid.
+pub struct Asteroid;
 
 impl Asteroid {
-
-    pub fn random(id: u32) -> Asteroid {
-        let x = rand(0, 100) as f32 / 100.;
-        let y = rand(0, 100) as f32 / 100.;
-        let orientation = rand(0, 628) as f32 / 100.;
+    /// Spawn an asteroid at a random position, going in a random direction.
+    pub fn spawn_random(world: &mut World, rng: &mut Rng) -> Entity {
+        let x = rng.rand(0, 100) as f32 / 100.;
+        let y = rng.rand(0, 100) as f32 / 100.;
+        let orientation = rng.rand(0, 628) as f32 / 100.;
         let size_index: usize = 0;
         let speed = ASTEROID_SPEEDS[size_index];
         let r = ASTEROID_RADII[size_index];
 
         let position = Position::new(x, y);
-        let velocity = Velocity::new(speed * orientation.cos(), speed * orientation.sin());
-        let limit = Rect::new(-1., -1., 3., 3.);
-        let circle_solid = CircleSolid::new(position, velocity, r, limit);
-        Asteroid {
-            solid: circle_solid,
-            id,
-            is_destroyed : false
-        }
-    }
-
-    pub fn update(&mut self, dt: f32) {
-        self.solid.update(dt);
-        self.handle_out_of_limit();
-    }
-
-    pub fn get_id(&self)-> u32{
-        self.id
-    }
+        let velocity = Velocity::polar(speed, orientation);
+        let mut solid = CircleSolid::new(position, velocity, r, Rect::new(0., 0., 1., 1.));
+        solid.set_boundary(Boundary::Wrap);
 
-    fn handle_out_of_limit(&mut self) {
-        let pos_x = self.solid.pos.x();
-        let pos_y = self.solid.pos.y();
-        if pos_x < 0. {
-            self.solid.pos.set_x(1. - pos_x);
-        }
-        if pos_x > 1. {
-            self.solid.pos.set_x(pos_x - 1.);
-        }
-        if pos_y < 0. {
-            self.solid.pos.set_y(1. - pos_y);
-        }
-        if pos_y > 1. {
-            self.solid.pos.set_y(pos_y - 1.);
-        }
+        let entity = world.spawn();
+        world.insert(entity, solid);
+        world.insert(entity, Asteroid);
+        entity
     }
 
-    pub fn destroy(&mut self){
-        self.is_destroyed = true;
-    }
-}
-
-pub struct Asteroids {
-    pub vec: Vec<Asteroid>
-}
-
-impl Asteroids {
-    pub fn new(asteroid_count: u32) -> Asteroids {
-
-        let mut counter = 0;
-        let mut asteroids = Vec::new();
-        for _ in 0..asteroid_count {
-            let asteroid = Asteroid::random(counter);
-            asteroids.push(asteroid);
-            counter+=1;
-        };
-        Asteroids {
-            vec: asteroids
-        }
-    }
-
-    pub fn update(&mut self, dt: f32) {
-        self.vec.retain(|asteroid|{!asteroid.is_destroyed});
-        for asteroid in &mut self.vec {
-            asteroid.update(dt);
-        }
-    }
-
-    pub fn get_ids(&self) -> Vec<u32>{
-        let mut ids : Vec<u32> = Vec::new();
-        for asteroid in &self.vec{
-            ids.push(asteroid.id);
-        }
-        ids
-    }
-}
-
-impl AsRect for Asteroid {
-    fn as_rect(&self) -> Rect {
-        let x = self.solid.pos.x();
-        let y = self.solid.pos.y();
-        let r = self.solid.r;
+    /// Rectangle around the solid of an asteroid.
+    pub fn rect(solid: &CircleSolid) -> Rect {
+        let (x, y) = (solid.pos.x, solid.pos.y);
+        let r = solid.r;
         Rect::new(x - r, y - r, 2. * r, 2. * r)
     }
 }
 
-
-pub struct Bullet {
-    pub solid: CircleSolid,
-    pub is_destroyed : bool
-}
+/// Component of the bullet entities, fired by the spaceship.
+pub struct Bullet;
 
 impl Bullet {
-    pub fn new(x: f32, y: f32, orientation: f32) -> Bullet {
+    pub fn spawn(world: &mut World, x: f32, y: f32, orientation: f32) -> Entity {
         let position = Position::new(x, y);
-        let velocity = Velocity::new(BULLET_SPEED * orientation.cos(), BULLET_SPEED * orientation.sin());
-        let limit = Rect::new(-1., -1., 3., 3.);
-        let circle_solid = CircleSolid::new(position, velocity, BULLET_RADIUS, limit);
-        Bullet {
-            solid: circle_solid,
-            is_destroyed: false
-        }
-    }
+        let velocity = Velocity::polar(BULLET_SPEED, orientation);
+        let mut solid = CircleSolid::new(position, velocity, BULLET_RADIUS, Rect::new(0., 0., 1., 1.));
+        solid.set_boundary(Boundary::Despawn);
 
-    pub fn is_in_limit(&self) -> bool {
-        let x = self.solid.pos.x();
-        let y = self.solid.pos.y();
-        if x < 0. || x > 1. {
-            return false;
-        }
-        if y < 0. || y > 1. {
-            return false;
-        }
-
-        return true;
+        let entity = world.spawn();
+        world.insert(entity, solid);
+        world.insert(entity, Bullet);
+        entity
     }
 
-    pub fn update(&mut self, dt: f32) {
-        if self.is_destroyed {
-            return
-        }
-
-        self.solid.update(dt);
+    /// Rectangle around the solid of a bullet.
+    pub fn rect(solid: &CircleSolid) -> Rect {
+        let (x, y) = (solid.pos.x, solid.pos.y);
+        let r = solid.r;
+        Rect::new(x - r, y - r, r, r)
     }
+}
 
-    pub fn destroy(&mut self){
-        self.is_destroyed = true
+/// Move all the solids of the world.
+fn move_solids(world: &mut World, dt: f32) {
+    for (_, solid) in world.storage_mut::<CircleSolid>().iter_mut() {
+        solid.update(dt);
     }
 }
 
-impl AsRect for Bullet {
-    fn as_rect(&self) -> Rect {
-        let x = self.solid.pos.x();
-        let y = self.solid.pos.y();
-        let r = self.solid.r;
-        Rect::new(x - r, y - r, r, r)
+/// The bullets leaving the board are lost.
+fn despawn_lost_bullets(world: &mut World, _dt: f32) {
+    let solids = world.storage::<CircleSolid>();
+    for (entity, _) in world.storage::<Bullet>().iter() {
+        if solids.get(entity).unwrap().is_out() {
+            world.despawn(entity);
+        }
     }
 }
 
+/// The spaceship of the player, its body giving its orientation.
 pub struct Spaceship {
     pub solid: CircleSolid,
-    pub orientation: f32,
+    pub body: Body,
     pub accelerating: bool,
     pub firing: bool,
     firing_delay: f32,
-    turning: Turning,
-    pub bullets: Vec<Bullet>,
 }
 
 impl Spaceship {
     pub fn new() -> Spaceship {
         let position = Position::new(SPACESHIP_STARTING_POSITION_X0, SPACESHIP_STARTING_POSITION_Y0);
-        let velocity = Velocity::new(0., 0.);
-        let limit = Rect::new(-1., -1., 3., 3.);
+        let velocity = Velocity::ZERO;
+        let mut solid = CircleSolid::new(position, velocity, SPACESHIP_RADIUS, Rect::new(0., 0., 1., 1.));
+        solid.set_boundary(Boundary::Wrap);
+        let mut body = Body::new(1.);
+        body.max_speed = Some(SPACESHIP_MAX_SPEED);
 
         Spaceship {
-            solid: CircleSolid::new(position, velocity, SPACESHIP_RADIUS, limit),
-            orientation: 0.,
+            solid,
+            body,
             accelerating: false,
             firing: false,
             firing_delay: SPACESHIP_FIRING_DELAY,
-            turning: Turning::NONE,
-            bullets: Vec::new(),
         }
     }
 
@@ -215,123 +136,87 @@ impl Spaceship {
     }
 
     pub fn turn(&mut self, turn: Turning) {
-        self.turning = turn;
-    }
-
-    pub fn update(&mut self, dt: f32) {
-        self.update_orientation(dt);
-        self.update_speed(dt);
-        self.update_position(dt);
-        self.update_firing(dt);
-        self.update_bullets(dt);
+        self.body.angular_velocity = match turn {
+            Turning::LEFT => -SPACESHIP_ROTATION_SPEED,
+            Turning::RIGHT => SPACESHIP_ROTATION_SPEED,
+            Turning::NONE => 0.,
+        };
     }
 
-    pub fn bullets(&self) -> &Vec<Bullet> {
-        &self.bullets
+    /// Orientation of the spaceship, in radians.
+    pub fn orientation(&self) -> f32 {
+        self.body.angle
     }
 
-    fn update_orientation(&mut self, dt: f32) {
-        match &self.turning {
-            Turning::LEFT => self.orientation -= SPACESHIP_ROTATION_SPEED * dt,
-            Turning::RIGHT => self.orientation += SPACESHIP_ROTATION_SPEED * dt,
-            Turning::NONE => (),
-        }
-    }
-
-    fn update_speed(&mut self, dt: f32) {
+    /// Move the spaceship, the bullets fired being spawned in the world.
+    pub fn update(&mut self, dt: f32, world: &mut World) {
         if self.accelerating {
-            let mut vx = self.solid.vel.vx();
-            let mut vy = self.solid.vel.vy();
-
-            vx += SPACESHIP_ACCELERATION * dt * self.orientation.cos();
-            vy += SPACESHIP_ACCELERATION * dt * self.orientation.sin();
-
-            if vx > SPACESHIP_MAX_SPEED {
-                vx = SPACESHIP_MAX_SPEED
-            }
-            if vx < -SPACESHIP_MAX_SPEED {
-                vx = -SPACESHIP_MAX_SPEED
-            }
-            if vy > SPACESHIP_MAX_SPEED {
-                vy = SPACESHIP_MAX_SPEED
-            }
-            if vy < -SPACESHIP_MAX_SPEED {
-                vy = -SPACESHIP_MAX_SPEED
-            }
-
-            self.solid.vel.set_vx(vx);
-            self.solid.vel.set_vy(vy);
+            self.body.accelerate(Vec2::polar(SPACESHIP_ACCELERATION, self.orientation()));
         }
-    }
-
-    fn update_position(&mut self, dt: f32) {
-        self.handle_out_of_limit();
+        self.body.integrate(&mut self.solid.vel, dt);
         self.solid.update(dt);
+        self.update_firing(dt, world);
     }
 
-    fn update_firing(&mut self, dt: f32) {
+    fn update_firing(&mut self, dt: f32, world: &mut World) {
         if self.firing && self.firing_delay > SPACESHIP_FIRING_DELAY {
             self.firing_delay = 0.;
             let rect = self.as_rect();
-            let bullet = Bullet::new(rect.xc(), rect.yc(), self.orientation);
-            self.bullets.push(bullet);
+            Bullet::spawn(world, rect.xc(), rect.yc(), self.orientation());
         }
         self.firing_delay += dt;
     }
-
-    fn update_bullets(&mut self, dt: f32) {
-        self.bullets.retain(|bullet| { return bullet.is_in_limit() && !bullet.is_destroyed ; });
-        for bullet in &mut self.bullets { bullet.update(dt); }
-    }
-
-    fn handle_out_of_limit(&mut self) {
-        let pos_x = self.solid.pos.x();
-        let pos_y = self.solid.pos.y();
-        if pos_x < 0. {
-            self.solid.pos.set_x(1. - pos_x);
-        }
-        if pos_x > 1. {
-            self.solid.pos.set_x(pos_x - 1.);
-        }
-        if pos_y < 0. {
-            self.solid.pos.set_y(1. - pos_y);
-        }
-        if pos_y > 1. {
-            self.solid.pos.set_y(pos_y - 1.);
-        }
-    }
 }
 
 impl AsRect for Spaceship {
     fn as_rect(&self) -> Rect {
-        let x = self.solid.pos.x();
-        let y = self.solid.pos.y();
+        let (x, y) = (self.solid.pos.x, self.solid.pos.y);
         let r = self.solid.r;
         Rect::new(x - r, y - r, r, r)
     }
 }
 
 /// Logic is a structure that contains all entities from the game.
+///
+/// The asteroids and the bullets are entities of the world, the spaceship is on its own.
 pub struct Logic {
     is_over: bool,
     pub spaceship: Spaceship,
-    pub asteroids: Asteroids,
+    pub world: World,
+    pub asteroid_grid: Grid<Entity>,
+    systems: Systems,
 }
 
 impl Logic {
-    /// Create a new game logic with default values for game settings
-    pub fn new() -> Logic {
+    /// Create a new game logic with default values for game settings.
+    ///
+    /// The random generator places the asteroids.
+    pub fn new(mut rng: Rng) -> Logic {
+        let mut world = World::new();
+        world.register::<CircleSolid>();
+        world.register::<Asteroid>();
+        world.register::<Bullet>();
+        for _ in 0..ASTEROID_STARTING_NUMBER {
+            Asteroid::spawn_random(&mut world, &mut rng);
+        }
+
+        let mut systems = Systems::new();
+        systems.add(move_solids);
+        systems.add(despawn_lost_bullets);
+
         Logic {
             is_over: false,
             spaceship: Spaceship::new(),
-            asteroids: Asteroids::new(ASTEROID_STARTING_NUMBER),
+            world,
+            asteroid_grid: Grid::new(Rect::new(0., 0., 1., 1.), 8, 8),
+            systems,
         }
     }
 
     /// Update each entity of a delta of time and check if the game is over.
     pub fn update(&mut self, dt: f32) {
-        self.spaceship.update(dt);
-        self.asteroids.update(dt);
+        self.spaceship.update(dt, &mut self.world);
+        self.systems.run(&mut self.world, dt);
     }
 
     /// Set the game over
@@ -1,28 +1,50 @@
-use sdl2::mixer::Chunk;
+use engine::error::Error;
+use engine::audio::synth::SynthParams;
+use engine::audio::{Mixer, SoundManager};
+use engine::math::Vec2;
+use engine::res;
+
+use crate::logic::BLOCK_ROW_N;
+
+pub const CHANNEL_COUNT: i32 = 4;
+pub const SOUND_BANK_PATH: &str = res!("sounds.bank");
+pub const BLOCK_SOUND_PATH: &str = res!("block.sfx");
+/// Number of values of the blocks, two rows of blocks sharing the same value.
+pub const BLOCK_VALUE_COUNT: u8 = BLOCK_ROW_N.div_ceil(2);
+/// Semitones the bounce on a block is pitched up by for each point of value of the block.
+pub const BLOCK_PITCH_STEP: f32 = 4.;
 
 /// Structure containing all the sounds that will be played during the game.
+///
+/// The bounces are heard on the side of the board they happen on,
+/// the bounces on the blocks being generated higher for the blocks of higher value.
 pub struct Audio {
-    wall_bounce: Chunk,
-    racket_bounce: Chunk,
-    block_bounce: Chunk,
+    sounds: SoundManager,
 }
 
 impl Audio {
-    pub fn new() -> Audio {
-        Audio {
-            wall_bounce: sdl2::mixer::Chunk::from_file("res/wall.wav").unwrap(),
-            racket_bounce: sdl2::mixer::Chunk::from_file("res/racket.wav").unwrap(),
-            block_bounce: sdl2::mixer::Chunk::from_file("res/block.wav").unwrap(),
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.load_bank(SOUND_BANK_PATH)?;
+        let block = SynthParams::load(BLOCK_SOUND_PATH)?;
+        for value in 0..BLOCK_VALUE_COUNT {
+            sounds.add_synth(&block_sound(value), &block.pitched(value as f32 * BLOCK_PITCH_STEP), 0, 1.)?;
         }
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+        Ok(Audio { sounds })
     }
 
-    pub fn play_wall_bounce(&self) {
-        sdl2::mixer::Channel::all().play(&self.wall_bounce, 0).unwrap();
+    pub fn play_wall_bounce(&self, position: Vec2) {
+        self.sounds.play_at("wall", position);
     }
-    pub fn play_racket_bounce(&self) {
-        sdl2::mixer::Channel::all().play(&self.racket_bounce, 0).unwrap();
+    pub fn play_racket_bounce(&self, position: Vec2) {
+        self.sounds.play_at("racket", position);
     }
-    pub fn play_block_bounce(&self, loops: i32) {
-        sdl2::mixer::Channel::all().play(&self.block_bounce, loops).unwrap();
+    pub fn play_block_bounce(&self, value: u8, position: Vec2) {
+        self.sounds.play_at(&block_sound(value), position);
     }
 }
+
+fn block_sound(value: u8) -> String {
+    format!("block_{}", value)
+}
@@ -1,8 +1,10 @@
 use engine::collide::collide;
 use engine::geometry::{AsRect, Rect};
+use engine::math::Vec2;
+use engine::physics::RectSolid;
 
 use crate::audio::Audio;
-use crate::logic::{BOARD_LEFT_LIMIT_X, BOARD_RIGHT_LIMIT_X, BOARD_TOP_LIMIT_Y, Logic};
+use crate::logic::{Block, BOARD_LEFT_LIMIT_X, BOARD_RIGHT_LIMIT_X, BOARD_TOP_LIMIT_Y, Logic};
 
 /// Handle the collision between the racket and the ball. It produces a sound when there is a bounce.
 pub fn collide_ball_and_racket(logic: &mut Logic, audio: &Audio) {
@@ -11,7 +13,7 @@ pub fn collide_ball_and_racket(logic: &mut Logic, audio: &Audio) {
 
     match collide(&ball, &racket) {
         Some(rect) => {
-            audio.play_racket_bounce();
+            audio.play_racket_bounce(ball.center());
             if rect.yc() > racket.yc() {
                 ()
             }
@@ -28,7 +30,7 @@ pub fn collide_ball_and_wall(logic: &mut Logic, audio: &Audio) {
     let left_wall = Rect::from_2_points(0., 0., BOARD_LEFT_LIMIT_X, 1.);
     match collide(&ball, &left_wall) {
         Some(rect) => {
-            audio.play_wall_bounce();
+            audio.play_wall_bounce(ball.center());
             logic.ball.reflect_x(rect.w());
         }
         None => (),
@@ -37,7 +39,7 @@ pub fn collide_ball_and_wall(logic: &mut Logic, audio: &Audio) {
     let right_wall = Rect::from_2_points(BOARD_RIGHT_LIMIT_X, 0., 1., 1.);
     match collide(&ball, &right_wall) {
         Some(rect) => {
-            audio.play_wall_bounce();
+            audio.play_wall_bounce(ball.center());
             logic.ball.reflect_x(-rect.w());
         }
         None => (),
@@ -46,7 +48,7 @@ pub fn collide_ball_and_wall(logic: &mut Logic, audio: &Audio) {
     let top_wall = Rect::from_2_points(0., 0., 1., BOARD_TOP_LIMIT_Y);
     match collide(&ball, &top_wall) {
         Some(_rect) => {
-            audio.play_wall_bounce();
+            audio.play_wall_bounce(ball.center());
             logic.ball.reflect_y(BOARD_TOP_LIMIT_Y);
         }
         None => (),
@@ -54,18 +56,24 @@ pub fn collide_ball_and_wall(logic: &mut Logic, audio: &Audio) {
 }
 
 /// Handle the collision between the blocks and the ball, producing a sound when it happens.
-pub fn collide_ball_and_blocks(logic: &mut Logic, audio: &Audio) {
+///
+/// The blocks hit are despawned once all of them have been checked, their centers and values being returned.
+pub fn collide_ball_and_blocks(logic: &mut Logic, audio: &Audio) -> Vec<(Vec2, u8)> {
     let ball = logic.ball.as_rect();
+    let mut destroyed = Vec::new();
 
-    for block in &mut logic.blocks.block_vec {
-        if block.is_destroyed() { continue; }
-
-        let block_rec = block.as_rect();
+    let solids = logic.world.storage::<RectSolid>();
+    let blocks = logic.world.storage::<Block>();
+    for entity in logic.blocks.query(&ball) {
+        let block = blocks.get(entity).unwrap();
+        let block_rec = solids.get(entity).unwrap().as_rect();
         match collide(&ball, &block_rec) {
             Some(rect) => {
                 logic.score.add((block.get_value() + 1) as u32);
-                block.destroy();
-                audio.play_block_bounce(block.get_value() as i32);
+                logic.blocks.remove(entity, &block_rec);
+                logic.world.despawn(entity);
+                audio.play_block_bounce(block.get_value(), block_rec.center());
+                destroyed.push((block_rec.center(), block.get_value()));
 
                 if rect.w() > rect.h() {
                     let mut y_shift = rect.h();
@@ -84,4 +92,9 @@ pub fn collide_ball_and_blocks(logic: &mut Logic, audio: &Audio) {
             None => (),
         };
     }
-}
\ No newline at end of file
+    drop(solids);
+    drop(blocks);
+
+    logic.world.maintain();
+    destroyed
+}
@@ -12,7 +12,7 @@ pub fn handle_event(event: Event, logic: &mut Logic) {
         }
 
         Event::KeyDown { keycode: Some(Keycode::Space), .. } => {
-            logic.ball.reset();
+            logic.reset_ball();
         }
 
         Event::KeyDown { keycode: Some(Keycode::Left), repeat: false, .. } => {
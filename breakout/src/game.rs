@@ -0,0 +1,125 @@
+use sdl2::event::Event;
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::graphics::Window;
+use engine::random::Rng;
+
+use crate::audio::Audio;
+use crate::collide::{collide_ball_and_blocks, collide_ball_and_racket, collide_ball_and_wall};
+use crate::event::handle_event;
+use crate::graphics::Graphics;
+use crate::logic::Logic;
+
+/// The breakout game, gathering the logic, the audio and the graphics for the game loop.
+pub struct Breakout<'a> {
+    logic: Logic,
+    audio: &'a Audio,
+    graphics: Graphics,
+    assets: Assets<'a>,
+}
+
+impl Breakout<'_> {
+    /// Create a new game, the seed giving all its random events.
+    pub fn new<'a>(audio: &'a Audio, ttf_context: &'a Sdl2TtfContext, seed: u64) -> Result<Breakout<'a>, Error> {
+        let mut rng = Rng::new(seed);
+        let mut assets = Assets::new(ttf_context);
+        Ok(Breakout {
+            logic: Logic::new(rng.fork()),
+            audio,
+            graphics: Graphics::new(rng.fork(), &mut assets)?,
+            assets,
+        })
+    }
+}
+
+impl Game for Breakout<'_> {
+    fn handle_event(&mut self, event: Event) {
+        handle_event(event, &mut self.logic);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.graphics.save_positions(&self.logic);
+        self.logic.update(dt);
+        self.graphics.animate(dt);
+    }
+
+    fn collide(&mut self, _dt: f32) {
+        collide_ball_and_racket(&mut self.logic, self.audio);
+        collide_ball_and_wall(&mut self.logic, self.audio);
+        for (position, value) in collide_ball_and_blocks(&mut self.logic, self.audio) {
+            self.graphics.break_block(position, value);
+        }
+    }
+
+    fn render(&mut self, window: &mut Window, alpha: f32) {
+        self.graphics.update(&self.logic, window, &self.assets, alpha);
+        self.graphics.draw(window);
+    }
+
+    fn is_over(&self) -> bool {
+        self.logic.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::audio::NullMixer;
+    use engine::game_loop::{run, simulate};
+    use engine::geometry::AsRect;
+    use engine::graphics::NullRenderer;
+    use engine::replay::{Input, Replay};
+
+    use super::*;
+
+    #[test]
+    fn full_match_runs_headless() {
+        let mixer = NullMixer::new();
+        let sounds = mixer.record();
+        let audio = Audio::new(Box::new(mixer)).unwrap();
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let renderer = NullRenderer::new(600, 600);
+        let frames = renderer.record();
+        let mut window = Window { canvas: Box::new(renderer), event_pump: None };
+
+        let mut breakout = Breakout::new(&audio, &ttf_context, 1).unwrap();
+        simulate(&mut breakout, &mut window, 1_000_000);
+
+        assert!(breakout.is_over());
+        assert_eq!(breakout.logic.life.get(), 0);
+        assert!(!sounds.borrow().played.is_empty());
+        assert!(frames.borrow().frame_count > 0);
+    }
+
+    #[test]
+    fn replay_gives_the_same_game() {
+        let recording = "replay 1\nseed 5\n\
+            frame 0.5\nkey_down 0 Left\n\
+            frame 0.5\nkey_up 0 Left\nkey_down 0 Right\n\
+            frame 0.5\nframe 0.5\n\
+            frame 0.5\nkey_up 0 Right\nkey_down 0 Space\n\
+            frame 0.5\nframe 0.5\nframe 0.5\n";
+
+        let audio = Audio::new(Box::new(NullMixer::new())).unwrap();
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let mut window = Window::headless(600, 600);
+
+        let mut games = Vec::new();
+        for _ in 0..2 {
+            let replay = Replay::parse(recording).unwrap();
+            let mut breakout = Breakout::new(&audio, &ttf_context, replay.seed()).unwrap();
+            run(&mut breakout, &mut window, Input::Replay(replay)).unwrap();
+            games.push(breakout);
+        }
+
+        let first = games[0].logic.ball.as_rect();
+        let second = games[1].logic.ball.as_rect();
+        assert_eq!((first.x0(), first.y0()), (second.x0(), second.y0()));
+        let first = games[0].logic.racket.as_rect();
+        let second = games[1].logic.racket.as_rect();
+        assert_eq!(first.x0(), second.x0());
+        assert_eq!(games[0].logic.score.get(), games[1].logic.score.get());
+    }
+}
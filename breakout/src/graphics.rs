@@ -1,17 +1,28 @@
+use std::f32::consts::FRAC_PI_2;
+
 use sdl2::pixels::Color;
-use sdl2::ttf::Sdl2TtfContext;
 
+use engine::assets::Assets;
+use engine::ecs::Storage;
+use engine::error::Error;
+use engine::game_loop::interpolate;
 use engine::geometry::{AsRect, Rect};
-use engine::graphics::{RectSprite, RenderedString, Window};
+use engine::graphics::{Camera, RectSprite, RenderedString, Window};
+use engine::math::Vec2;
+use engine::particles::{ParticleConfig, ParticleSystem};
+use engine::physics::RectSolid;
+use engine::random::Rng;
+use engine::res;
 
-use crate::logic::{BLOCK_COL_N, BLOCK_ROW_N, BOARD_LEFT_LIMIT_X, BOARD_RIGHT_LIMIT_X, BOARD_TOP_LIMIT_Y, Logic};
+use crate::logic::{Block, BOARD_LEFT_LIMIT_X, BOARD_RIGHT_LIMIT_X, BOARD_TOP_LIMIT_Y, Logic};
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
 
-pub const RACKET_COLOR: Color = Color { r: 62, g: 117, b: 207, a: 0 };
+pub const RACKET_COLOR: Color = Color { r: 62, g: 117, b: 207, a: 255 };
 pub const LIMIT_COLOR: Color = Color::WHITE;
 pub const BLOCK_COLORS: [Color; 4] = [Color::YELLOW, Color::GREEN, Color::BLUE, Color::RED];
 pub const BALL_COLOR: Color = Color::WHITE;
 
-pub const FONT_PATH: &str = "res/atari.ttf";
+pub const FONT_PATH: &str = res!("atari.ttf");
 pub const FONT_SIZE: u16 = 48;
 
 pub const SCORE_POSITION_X: i32 = 100;
@@ -19,106 +30,128 @@ pub const SCORE_POSITION_Y: i32 = 70;
 pub const LIFE_POSITION_X: i32 = 500;
 pub const LIFE_POSITION_Y: i32 = 70;
 
+/// Farthest the ball is drawn from its previous position, it has been thrown again when it moves more in a step.
+pub const BALL_MAX_STEP: f32 = 0.1;
+
+pub const PARTICLE_CAPACITY: usize = 512;
+pub const BLOCK_PARTICLE_COUNT: usize = 24;
+/// Shards of a destroyed block, falling down, their color being the one of the block.
+pub const BLOCK_SHARDS: ParticleConfig = ParticleConfig {
+    lifetime: (0.4, 0.9),
+    speed: (0.1, 0.3),
+    direction: -FRAC_PI_2,
+    spread: 2.,
+    gravity: Vec2::new(0., 1.5),
+    colors: (Color::WHITE, Color::WHITE),
+    sizes: (0.012, 0.004),
+};
+
 /// Struct containing all basic dynamic elements required to draw the game.
 ///
+/// The board is shown by a camera keeping it square whatever the size of the window.
+/// The blocks break in shards when they are destroyed.
+/// The ball is drawn between its positions before and after the last step.
 pub struct Graphics {
+    camera: Camera,
     racket: RectSprite,
     left_limit: RectSprite,
     right_limit: RectSprite,
     top_limit: RectSprite,
-    blocks: Vec<RectSprite>,
+    blocks: Storage<RectSprite>,
     ball: RectSprite,
-    score: String,
-    life: String,
+    previous_ball: Vec2,
+    score: RenderedString,
+    life: RenderedString,
+    particles: ParticleSystem,
 }
 
 impl Graphics {
-    /// Init the dynamic elements required to draw the game
-    pub fn new() -> Graphics {
-        let mut blocks: Vec<RectSprite> = Vec::new();
-        for _i in 0..BLOCK_ROW_N {
-            for _j in 0..BLOCK_COL_N {
-                blocks.push(RectSprite::default(Color::WHITE));
-            }
-        }
+    /// Init the dynamic elements required to draw the game, the random generator throwing the shards of the blocks.
+    pub fn new(rng: Rng, assets: &mut Assets) -> Result<Graphics, Error> {
+        let font = assets.font(FONT_PATH, FONT_SIZE)?;
 
-        Graphics {
+        Ok(Graphics {
+            camera: Camera::new(Rect::new(0., 0., 1., 1.), WINDOW_WIDTH, WINDOW_HEIGHT),
             racket: RectSprite::default(RACKET_COLOR),
             left_limit: RectSprite::default(LIMIT_COLOR),
             right_limit: RectSprite::default(LIMIT_COLOR),
             top_limit: RectSprite::default(LIMIT_COLOR),
-            blocks,
+            blocks: Storage::new(),
             ball: RectSprite::default(BALL_COLOR),
-            score: "0".parse().unwrap(),
-            life: "0".parse().unwrap(),
-        }
+            previous_ball: Vec2::ZERO,
+            score: RenderedString::new("0", SCORE_POSITION_X, SCORE_POSITION_Y, assets, font)?,
+            life: RenderedString::new("0", LIFE_POSITION_X, LIFE_POSITION_Y, assets, font)?,
+            particles: ParticleSystem::new(PARTICLE_CAPACITY, rng),
+        })
+    }
+
+    /// Keep the position of the ball before a step of the logic.
+    pub fn save_positions(&mut self, logic: &Logic) {
+        self.previous_ball = logic.ball.as_rect().min();
     }
 
-    /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window) {
-        let w = window.width();
-        let h = window.height();
-
-        self.racket.update(logic.racket.as_rect(), w, h);
-        self.ball.update(logic.ball.as_rect(), w, h);
-        self.left_limit.update(Rect::from_2_points(0., 0., BOARD_LEFT_LIMIT_X, 1.), w, h);
-        self.right_limit.update(Rect::from_2_points(BOARD_RIGHT_LIMIT_X, 0., 1.01, 1.), w, h);
-        self.top_limit.update(Rect::from_2_points(0., 0., 1.0, BOARD_TOP_LIMIT_Y), w, h);
-
-        for i in 0..self.blocks.len() {
-            let block_logic = logic.blocks.get(i);
-            let block_graphics = &mut self.blocks[i];
-
-            if block_logic.is_destroyed() {
-                block_graphics.hide();
-            } else {
-                block_graphics.color = BLOCK_COLORS[block_logic.get_value() as usize];
-                block_graphics.update(block_logic.as_rect(), w, h);
+    /// Update the dynamic elements accordingly to the state of the game, alpha being the fraction of a step not simulated yet.
+    pub fn update(&mut self, logic: &Logic, window: &Window, assets: &Assets, alpha: f32) {
+        self.camera.fit(window);
+        let camera = &self.camera;
+
+        self.racket.update(logic.racket.as_rect(), camera);
+        let ball = logic.ball.as_rect();
+        let ball_position = interpolate(self.previous_ball, ball.min(), alpha, BALL_MAX_STEP);
+        self.ball.update(Rect::from_min_size(ball_position, ball.size()), camera);
+        self.left_limit.update(Rect::from_2_points(0., 0., BOARD_LEFT_LIMIT_X, 1.), camera);
+        self.right_limit.update(Rect::from_2_points(BOARD_RIGHT_LIMIT_X, 0., 1.01, 1.), camera);
+        self.top_limit.update(Rect::from_2_points(0., 0., 1.0, BOARD_TOP_LIMIT_Y), camera);
+
+        self.blocks.retain(|entity, _| logic.world.is_alive(entity));
+        let solids = logic.world.storage::<RectSolid>();
+        for (entity, block) in logic.world.storage::<Block>().iter() {
+            if !self.blocks.contains(entity) {
+                self.blocks.insert(entity, RectSprite::default(BLOCK_COLORS[block.get_value() as usize]));
             }
+            self.blocks.get_mut(entity).unwrap().update(solids.get(entity).unwrap().as_rect(), camera);
         }
 
-        self.score = logic.score.get().to_string();
-        self.life = logic.life.get().to_string();
+        self.score.set_text(&logic.score.get().to_string(), assets).expect("can't render the score");
+        self.life.set_text(&logic.life.get().to_string(), assets).expect("can't render the lives");
     }
 
     /// Draw the game.
     ///
     /// Start by clearing the all board.
     /// It draws each dynamic element and show the canvas
-    pub fn draw(&self, window: &mut Window, ttf_context: &Sdl2TtfContext) {
+    pub fn draw(&self, window: &mut Window) {
         window.clear();
 
-        let canvas = &mut window.canvas;
+        let canvas = window.canvas.as_mut();
         self.racket.draw(canvas);
         self.ball.draw(canvas);
         self.left_limit.draw(canvas);
         self.right_limit.draw(canvas);
         self.top_limit.draw(canvas);
-        for b in &self.blocks {
+        for (_, b) in self.blocks.iter() {
             b.draw(canvas);
         }
 
-        let rendered_score = RenderedString::new
-            (
-                &self.score,
-                SCORE_POSITION_X,
-                SCORE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-            );
-        rendered_score.draw(canvas);
-
-        let rendered_life = RenderedString::new
-            (
-                &self.life,
-                LIFE_POSITION_X,
-                LIFE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-            );
-        rendered_life.draw(canvas);
+        self.particles.draw(canvas, &self.camera);
+
+        self.score.draw_scaled(canvas, &self.camera);
+        self.life.draw_scaled(canvas, &self.camera);
+
+        self.camera.draw_letterbox(canvas);
         canvas.present();
     }
-}
\ No newline at end of file
+
+    /// Break a block of the value destroyed at the position, in the world, in shards of its color.
+    pub fn break_block(&mut self, position: Vec2, value: u8) {
+        let color = BLOCK_COLORS[value as usize];
+        let faded = Color::RGBA(color.r, color.g, color.b, 0);
+        let shards = ParticleConfig { colors: (color, faded), ..BLOCK_SHARDS };
+        self.particles.burst(position, BLOCK_PARTICLE_COUNT, &shards);
+    }
+
+    /// Advance the shards of the destroyed blocks.
+    pub fn animate(&mut self, dt: f32) {
+        self.particles.update(dt);
+    }
+}
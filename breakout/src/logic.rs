@@ -1,6 +1,8 @@
+use engine::ecs::{Entity, World};
 use engine::geometry::{AsRect, Rect};
 use engine::physics::{Position, RectSolid, Velocity};
-use engine::random::rand;
+use engine::random::Rng;
+use engine::spatial::Grid;
 
 pub const RACKET_WIDTH: f32 = 0.08;
 pub const RACKET_HEIGHT: f32 = 0.02;
@@ -43,7 +45,7 @@ impl Racket {
     /// Create a new racket in the center of the board.
     pub fn new() -> Racket {
         let pos = Position::new(0.5 - (RACKET_WIDTH / 2.), RACKET_Y0);
-        let vel = Velocity::new(0., 0.);
+        let vel = Velocity::ZERO;
         let w = RACKET_WIDTH;
         let h = RACKET_HEIGHT;
         let limit = Rect::from_2_points(BOARD_LEFT_LIMIT_X, 0., BOARD_RIGHT_LIMIT_X, 1.);
@@ -57,14 +59,12 @@ impl Racket {
 
     /// To make the racket start moving.
     pub fn accelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vx(pv.vx() + RACKET_SPEED);
+        self.solid.vel.x += RACKET_SPEED;
     }
 
     /// To make the racket stop moving.
     pub fn decelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vx(pv.vx() - RACKET_SPEED);
+        self.solid.vel.x -= RACKET_SPEED;
     }
 
     /// Racket update is just the solid physics updating.
@@ -88,66 +88,38 @@ impl AsRect for Racket {
     }
 }
 
-/// Represent a block which can be destroyed on collision with ball.
+/// Component of the block entities, which are destroyed on collision with the ball.
 ///
-/// The block can be destroyed when collided by the ball.
 /// Each block has a value, which determines the score earned when it is destroyed.
+/// A block entity also has a fixed [`RectSolid`].
 pub struct Block {
-    solid: RectSolid,
     value: u8,
-    is_destroyed: bool,
 }
 
 impl Block {
-    /// Create a new block add the given position with a given value.
-    pub fn new(x: f32, y: f32, value: u8) -> Block {
-        Block {
-            solid: RectSolid::fixed(x, y, BLOCK_WIDTH, BLOCK_HEIGHT),
-            value,
-            is_destroyed: false,
-        }
+    /// Spawn a new block at the given position with a given value.
+    pub fn spawn(world: &mut World, x: f32, y: f32, value: u8) -> Entity {
+        let entity = world.spawn();
+        world.insert(entity, RectSolid::fixed(x, y, BLOCK_WIDTH, BLOCK_HEIGHT));
+        world.insert(entity, Block { value });
+        entity
     }
 
-    pub fn get_value(&self) -> u8 { self.value }
-    pub fn destroy(&mut self) { self.is_destroyed = true }
-    pub fn is_destroyed(&self) -> bool { self.is_destroyed }
-}
-
-impl AsRect for Block {
-    fn as_rect(&self) -> Rect {
-        self.solid.as_rect()
-    }
-}
-
-/// Represents all the blocks in one struct to handle drawing and collision more easily
-///
-/// Blocks are represented as a vector of blocks
-pub struct Blocks {
-    pub block_vec: Vec<Block>
-}
-
-impl Blocks {
-    /// Create all the blocks of the game
-    pub fn new() -> Blocks {
-        let mut blocks: Vec<Block> = Vec::new();
+    /// Spawn all the blocks of the game.
+    pub fn spawn_all(world: &mut World) {
         for i in 0..BLOCK_ROW_N {
             for j in 0..BLOCK_COL_N {
-                blocks.push(
-                    Block::new(
-                        j as f32 * (BLOCK_WIDTH + BLOCK_STEP_X) + BLOCKS_X0,
-                        i as f32 * (BLOCK_HEIGHT + BLOCK_STEP_Y) + BLOCKS_Y0,
-                        (BLOCK_ROW_N - i - 1) / 2 as u8,
-                    )
+                Block::spawn(
+                    world,
+                    j as f32 * (BLOCK_WIDTH + BLOCK_STEP_X) + BLOCKS_X0,
+                    i as f32 * (BLOCK_HEIGHT + BLOCK_STEP_Y) + BLOCKS_Y0,
+                    (BLOCK_ROW_N - i - 1) / 2,
                 );
             }
         }
-        Blocks { block_vec: blocks }
     }
 
-    /// Get a block at a given index.
-    pub fn get(&self, i: usize) -> &Block {
-        &self.block_vec[i]
-    }
+    pub fn get_value(&self) -> u8 { self.value }
 }
 
 /// The ball which move across the board, between rackets.
@@ -156,64 +128,74 @@ impl Blocks {
 /// It is reflected on rackets.
 ///
 pub struct Ball {
-    solid: RectSolid
+    solid: RectSolid,
+    /// Time of the last update left after the ball was stopped by an obstacle, to move once it has bounced.
+    time_left: f32,
 }
 
 impl Ball {
     /// Create a new ball with a random direction
-    fn new() -> Ball {
-        let random_angle = (rand(90 - 45, 90 + 45) as f32).to_radians();
+    fn new(rng: &mut Rng) -> Ball {
+        let random_angle = (rng.rand(90 - 45, 90 + 45) as f32).to_radians();
         let pos = Position::new(BALL_X0, BALL_Y0);
-        let vel = Velocity::new(random_angle.cos() * BALL_SPEED, random_angle.sin() * BALL_SPEED);
+        let vel = Velocity::polar(BALL_SPEED, random_angle);
         let limit = Rect::from_2_points(0., 0., 1., 2.);
 
         Ball {
-            solid: RectSolid::new(pos, vel, BALL_DIM, BALL_DIM, limit)
+            solid: RectSolid::new(pos, vel, BALL_DIM, BALL_DIM, limit),
+            time_left: 0.,
         }
     }
 
-    /// Ball update is just the solid physics updating.
-    fn update(&mut self, dt: f32) {
-        self.solid.update(dt);
+    /// Move the ball, stopping it against the obstacles so a fast ball can't go through them.
+    ///
+    /// The ball moves for the rest of the time once it has bounced.
+    fn update(&mut self, dt: f32, obstacles: &[Rect]) {
+        self.time_left = match self.solid.update_until_hit(dt, obstacles) {
+            Some(hit) => dt - hit.time,
+            None => 0.,
+        };
+    }
+
+    /// Move for the time left after the contact with an obstacle.
+    fn move_on(&mut self) {
+        self.solid.update(self.time_left);
+        self.time_left = 0.;
     }
 
     /// Reflect ball on x direction
     pub fn reflect_x(&mut self, x_shift: f32) {
-        let x = self.solid.pos.x();
-        self.solid.pos.set_x(x + x_shift);
-        let vx = self.solid.vel.vx();
-        self.solid.vel.set_vx(-vx);
+        self.solid.pos.x += x_shift;
+        self.solid.vel.x = -self.solid.vel.x;
+        self.move_on();
     }
 
     /// Reflect ball on y direction
     pub fn reflect_y(&mut self, y_shift: f32) {
-        let y = self.solid.pos.y();
-        self.solid.pos.set_y(y + y_shift);
-        let vy = self.solid.vel.vy();
-        self.solid.vel.set_vy(-vy);
+        self.solid.pos.y += y_shift;
+        self.solid.vel.y = -self.solid.vel.y;
+        self.move_on();
     }
 
     /// Bounce at a given angle.
     pub fn bounce(&mut self, angle: f32, y_shift: f32) {
 
         // Shift the ball outside the collision
-        let y = self.solid.pos.y();
-        self.solid.pos.set_y(y + y_shift);
+        self.solid.pos.y += y_shift;
 
         // Set the new speed
-        let vx = self.solid.vel.mag() * angle.cos();
-        let vy = self.solid.vel.mag() * angle.sin();
-        self.solid.vel.set_vx(vx);
-        self.solid.vel.set_vy(vy);
+        self.solid.vel = Velocity::polar(self.solid.vel.length(), angle);
+        self.move_on();
     }
 
     /// Reset ball position at the center of the board
-    pub fn reset(&mut self) {
-        let random_angle = (rand(90 - 45, 90 + 45) as f32).to_radians();
+    pub fn reset(&mut self, rng: &mut Rng) {
+        let random_angle = (rng.rand(90 - 45, 90 + 45) as f32).to_radians();
         let pos = Position::new(BALL_X0, BALL_Y0);
-        let vel = Velocity::new(random_angle.cos() * BALL_SPEED, random_angle.sin() * BALL_SPEED);
+        let vel = Velocity::polar(BALL_SPEED, random_angle);
         self.solid.vel = vel;
         self.solid.pos = pos;
+        self.time_left = 0.;
     }
 }
 
@@ -277,24 +259,40 @@ impl Life {
 
 /// Logic is a structure that contains all entities from the game.
 ///
+/// The blocks are entities of the world, despawned when the ball hits them.
+/// They are also indexed by a grid, to find the blocks next to the ball.
 pub struct Logic {
     pub racket: Racket,
-    pub blocks: Blocks,
+    pub world: World,
+    pub blocks: Grid<Entity>,
     pub ball: Ball,
     pub score: Score,
     pub life: Life,
+    rng: Rng,
     is_over: bool,
 }
 
 impl Logic {
-    /// Create a new game logic with default values for game settings
-    pub fn new() -> Logic {
+    /// Create a new game logic with default values for game settings.
+    ///
+    /// The random generator gives the direction of the ball each time it is thrown.
+    pub fn new(mut rng: Rng) -> Logic {
+        let mut world = World::new();
+        Block::spawn_all(&mut world);
+
+        let mut blocks = Grid::new(Rect::new(0., 0., 1., 1.), BLOCK_COL_N as usize, BLOCK_ROW_N as usize * 4);
+        for (entity, solid) in world.storage::<RectSolid>().iter() {
+            blocks.insert(entity, solid);
+        }
+
         Logic {
             racket: Racket::new(),
-            blocks: Blocks::new(),
-            ball: Ball::new(),
+            world,
+            blocks,
+            ball: Ball::new(&mut rng),
             score: Score::new(),
             life: Life::new(),
+            rng,
             is_over: false,
         }
     }
@@ -302,18 +300,29 @@ impl Logic {
     /// Update each entity of a delta of time and check if the game is over.
     pub fn update(&mut self, dt: f32) {
         self.racket.update(dt);
-        self.ball.update(dt);
+        let solids = self.world.storage::<RectSolid>();
+        let mut obstacles = vec![self.racket.as_rect()];
+        for block in self.blocks.query(&self.ball.solid.swept_rect(dt)) {
+            obstacles.push(solids.get(block).unwrap().as_rect());
+        }
+        drop(solids);
+        self.ball.update(dt, &obstacles);
 
-        if self.ball.solid.pos.y() > 1. {
+        if self.ball.solid.pos.y > 1. {
             self.life.remove();
-            self.ball.reset();
+            self.reset_ball();
         }
 
-        if self.life.count <= 0 {
+        if self.life.count == 0 {
             self.over();
         }
     }
 
+    /// Throw the ball again from the center of the board.
+    pub fn reset_ball(&mut self) {
+        self.ball.reset(&mut self.rng);
+    }
+
     /// Set the game over
     pub fn over(&mut self) {
         self.is_over = true;
@@ -323,4 +332,23 @@ impl Logic {
     pub fn is_over(&self) -> bool {
         self.is_over
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bouncing_ball_moves_for_the_time_left() {
+        let mut ball = Ball::new(&mut Rng::new(0));
+        ball.solid.vel = Velocity::new(0., -1.);
+        let y0 = ball.as_rect().y0();
+        let block = Rect::new(0., y0 - 0.06, 1., 0.01);
+
+        // The ball touches the block after half of the update, and goes back during the other half.
+        ball.update(0.1, &[block]);
+        assert!((ball.as_rect().y0() - (y0 - 0.05)).abs() < 1e-5);
+        ball.reflect_y(0.);
+        assert!((ball.as_rect().y0() - y0).abs() < 1e-5);
+    }
+}
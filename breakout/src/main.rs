@@ -1,50 +1,31 @@
-use std::time::SystemTime;
-
-use engine::audio::init_audio;
+use engine::error::Error;
+use engine::audio::open_mixer;
+use engine::backend::Backend;
+use engine::game_loop::run;
 use engine::graphics::Window;
+use engine::random::time_seed;
+use engine::replay::Input;
 
-use crate::audio::Audio;
-use crate::collide::{collide_ball_and_blocks, collide_ball_and_racket, collide_ball_and_wall};
-use crate::event::handle_event;
-use crate::graphics::Graphics;
-use crate::logic::Logic;
+use crate::audio::{Audio, CHANNEL_COUNT};
+use crate::game::Breakout;
 
 mod logic;
 mod event;
 mod graphics;
 mod collide;
 mod audio;
+mod game;
 
-fn main() {
-    init_audio(4);
-    let audio = Audio::new();
-    let mut logic = Logic::new();
-
-    let ttf_context = sdl2::ttf::init().unwrap();
-    let mut window = Window::new(600, 600);
-    let mut graphics = Graphics::new();
-
-    let mut previous = SystemTime::now();
-    'game_loop: loop {
-        let next = SystemTime::now();
-        let dt = next.duration_since(previous).unwrap().as_secs_f32();
-        previous = next;
-
-        let event_pump = &mut window.event_pump;
-        for event in event_pump.poll_iter() {
-            handle_event(event, &mut logic);
-        }
-
-        logic.update(dt);
-        if logic.is_over() {
-            break 'game_loop;
-        }
+pub const WINDOW_WIDTH: u32 = 600;
+pub const WINDOW_HEIGHT: u32 = 600;
 
-        collide_ball_and_racket(&mut logic, &audio);
-        collide_ball_and_wall(&mut logic, &audio);
-        collide_ball_and_blocks(&mut logic, &audio);
+fn main() -> Result<(), Error> {
+    let backend = Backend::from_args();
+    let (input, seed) = Input::from_args(time_seed())?;
+    let audio = Audio::new(open_mixer(backend, CHANNEL_COUNT))?;
+    let ttf_context = sdl2::ttf::init()?;
+    let mut window = Window::open(backend, WINDOW_WIDTH, WINDOW_HEIGHT)?;
 
-        graphics.update(&logic, &window);
-        graphics.draw(&mut window, &ttf_context);
-    }
-}
\ No newline at end of file
+    let mut breakout = Breakout::new(&audio, &ttf_context, seed)?;
+    run(&mut breakout, &mut window, input)
+}
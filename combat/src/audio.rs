@@ -1,98 +1,71 @@
-use sdl2::mixer::{Channel, Chunk};
+use engine::error::Error;
+use engine::audio::{Mixer, SoundHandle, SoundManager};
+use engine::geometry::AsRect;
+use engine::math::Vec2;
+use engine::res;
 
-use engine::audio::init_audio;
-
-use crate::logic::Logic;
+use crate::logic::{Logic, Tank};
 
 pub const CHANNEL_COUNT: i32 = 6;
+pub const SOUND_BANK_PATH: &str = res!("sounds.bank");
 
+/// The sounds of the game, the engine and turning loops of each tank being kept by their handles.
+///
+/// The sounds are heard where they happen on the board, the loops following the tanks.
 pub struct Audio {
-    explosion: Chunk,
-    forward: Chunk,
-    turning: Chunk,
-    shoot: Chunk,
+    sounds: SoundManager,
 
-    left_tank_move_channel: Channel,
-    right_tank_move_channel: Channel,
-    left_tank_turn_channel: Channel,
-    right_tank_turn_channel: Channel,
-    left_tank_fire_channel: Channel,
-    right_tank_fire_channel: Channel,
+    left_tank_move: Option<SoundHandle>,
+    right_tank_move: Option<SoundHandle>,
+    left_tank_turn: Option<SoundHandle>,
+    right_tank_turn: Option<SoundHandle>,
 }
 
 impl Audio {
-    pub fn new() -> Audio {
-        init_audio(CHANNEL_COUNT);
-
-        Audio {
-            explosion: sdl2::mixer::Chunk::from_file("res/explosion.wav").unwrap(),
-            forward: sdl2::mixer::Chunk::from_file("res/forward.wav").unwrap(),
-            turning: sdl2::mixer::Chunk::from_file("res/turning.wav").unwrap(),
-            shoot: sdl2::mixer::Chunk::from_file("res/shoot.wav").unwrap(),
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.load_bank(SOUND_BANK_PATH)?;
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+        Ok(Audio {
+            sounds,
 
-            left_tank_move_channel: sdl2::mixer::Channel(0),
-            right_tank_move_channel: sdl2::mixer::Channel(1),
-            left_tank_turn_channel: sdl2::mixer::Channel(2),
-            right_tank_turn_channel: sdl2::mixer::Channel(3),
-            left_tank_fire_channel: sdl2::mixer::Channel(4),
-            right_tank_fire_channel: sdl2::mixer::Channel(5),
-        }
+            left_tank_move: None,
+            right_tank_move: None,
+            left_tank_turn: None,
+            right_tank_turn: None,
+        })
     }
 
     pub fn update(&mut self, logic: &Logic) {
-        if logic.left_tank.is_moving() {
-            if !self.left_tank_move_channel.is_playing() {
-                self.left_tank_move_channel.play(&self.forward, -1).unwrap();
-            }
-        } else {
-            if self.left_tank_move_channel.is_playing() {
-                self.left_tank_move_channel.halt();
-            }
-        }
-
-        if logic.left_tank.is_turning() {
-            if !self.left_tank_turn_channel.is_playing() {
-                self.left_tank_turn_channel.play(&self.turning, -1).unwrap();
-            }
-        } else {
-            if self.left_tank_turn_channel.is_playing() {
-                self.left_tank_turn_channel.halt();
-            }
-        }
-
-
-        if logic.right_tank.is_moving() {
-            if !self.right_tank_move_channel.is_playing() {
-                self.right_tank_move_channel.play(&self.forward, -1).unwrap();
-            }
-        } else {
-            if self.right_tank_move_channel.is_playing() {
-                self.right_tank_move_channel.halt();
-            }
-        }
-
-        if logic.right_tank.is_turning() {
-            if !self.right_tank_turn_channel.is_playing() {
-                self.right_tank_turn_channel.play(&self.turning, -1).unwrap();
-            }
-        } else {
-            if self.right_tank_turn_channel.is_playing() {
-                self.right_tank_turn_channel.halt();
-            }
-        }
+        let sounds = &self.sounds;
+        let left_tank = &logic.left_tank;
+        let right_tank = &logic.right_tank;
+        keep_looping(sounds, &mut self.left_tank_move, "forward", left_tank.is_moving(), left_tank);
+        keep_looping(sounds, &mut self.left_tank_turn, "turning", left_tank.is_turning(), left_tank);
+        keep_looping(sounds, &mut self.right_tank_move, "forward", right_tank.is_moving(), right_tank);
+        keep_looping(sounds, &mut self.right_tank_turn, "turning", right_tank.is_turning(), right_tank);
     }
 
-    pub fn play_left_explosion(&self) {
-        self.left_tank_fire_channel.play(&self.explosion, 0).unwrap();
+    pub fn play_explosion(&self, tank: &Tank) {
+        self.sounds.play_at("explosion", tank.as_rect().center());
     }
 
-    pub fn play_right_explosion(&self) {
-        self.right_tank_fire_channel.play(&self.explosion, 0).unwrap();
-    }
-    pub fn play_left_shoot(&self) {
-        self.left_tank_fire_channel.play(&self.shoot, 0).unwrap();
+    pub fn play_shoot(&self, tank: &Tank) {
+        self.sounds.play_at("shoot", tank.as_rect().center());
     }
-    pub fn play_right_shoot(&self) {
-        self.right_tank_fire_channel.play(&self.shoot, 0).unwrap();
+}
+
+/// Loop a sound at a tank while a condition holds, playing it again if its channel has been taken,
+/// and stop it once it doesn't.
+fn keep_looping(sounds: &SoundManager, handle: &mut Option<SoundHandle>, name: &str, is_on: bool, tank: &Tank) {
+    let position = tank.as_rect().center();
+    match *handle {
+        Some(playing) if is_on && sounds.is_playing(playing) => sounds.set_position(playing, position),
+        _ if is_on => *handle = sounds.play_looped_at(name, position),
+        _ => {
+            if let Some(handle) = handle.take() {
+                sounds.stop(handle);
+            }
+        }
     }
 }
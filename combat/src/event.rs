@@ -17,14 +17,26 @@ pub fn handle_event(event: Event, logic: &mut Logic, audio: &Audio) {
         Event::KeyUp { keycode: Some(Keycode::Up), repeat: false, .. } => {
             logic.left_tank.decelerate();
         }
+        Event::KeyDown { keycode: Some(Keycode::Left), repeat: false, .. } => {
+            logic.left_tank.set_turning_left(true);
+        }
+        Event::KeyUp { keycode: Some(Keycode::Left), repeat: false, .. } => {
+            logic.left_tank.set_turning_left(false);
+        }
+        Event::KeyDown { keycode: Some(Keycode::Right), repeat: false, .. } => {
+            logic.left_tank.set_turning_right(true);
+        }
+        Event::KeyUp { keycode: Some(Keycode::Right), repeat: false, .. } => {
+            logic.left_tank.set_turning_right(false);
+        }
         Event::KeyDown { keycode: Some(Keycode::Down), repeat: false, .. } => {
             if logic.left_tank.fire() {
-                audio.play_left_shoot();
+                audio.play_shoot(&logic.left_tank);
             }
         }
         Event::KeyDown { keycode: Some(Keycode::S), repeat: false, .. } => {
             if logic.right_tank.fire() {
-                audio.play_right_shoot();
+                audio.play_shoot(&logic.right_tank);
             }
         }
         Event::KeyDown { keycode: Some(Keycode::Z), repeat: false, .. } => {
@@ -33,6 +45,18 @@ pub fn handle_event(event: Event, logic: &mut Logic, audio: &Audio) {
         Event::KeyUp { keycode: Some(Keycode::Z), repeat: false, .. } => {
             logic.right_tank.decelerate();
         }
+        Event::KeyDown { keycode: Some(Keycode::A), repeat: false, .. } => {
+            logic.right_tank.set_turning_left(true);
+        }
+        Event::KeyUp { keycode: Some(Keycode::A), repeat: false, .. } => {
+            logic.right_tank.set_turning_left(false);
+        }
+        Event::KeyDown { keycode: Some(Keycode::D), repeat: false, .. } => {
+            logic.right_tank.set_turning_right(true);
+        }
+        Event::KeyUp { keycode: Some(Keycode::D), repeat: false, .. } => {
+            logic.right_tank.set_turning_right(false);
+        }
         Event::KeyDown { keycode: Some(Keycode::R), repeat: false, .. } => {
             logic.change_map();
         }
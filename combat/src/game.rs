@@ -0,0 +1,106 @@
+use sdl2::event::Event;
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::graphics::Window;
+use engine::random::Rng;
+
+use crate::audio::Audio;
+use crate::collide::check_collision;
+use crate::event::handle_event;
+use crate::graphics::Graphics;
+use crate::logic::Logic;
+
+/// The combat game, gathering the logic, the audio and the graphics for the game loop.
+pub struct Combat<'a> {
+    logic: Logic,
+    audio: Audio,
+    graphics: Graphics,
+    assets: Assets<'a>,
+}
+
+impl Combat<'_> {
+    /// Create a new game, the seed giving the random effects.
+    pub fn new(audio: Audio, canvas_width: u32, canvas_height: u32, ttf_context: &Sdl2TtfContext, seed: u64) -> Result<Combat<'_>, Error> {
+        let mut assets = Assets::new(ttf_context);
+        Ok(Combat {
+            logic: Logic::new()?,
+            audio,
+            graphics: Graphics::new(Rng::new(seed), canvas_width, canvas_height, &mut assets)?,
+            assets,
+        })
+    }
+}
+
+impl Game for Combat<'_> {
+    fn handle_event(&mut self, event: Event) {
+        handle_event(event, &mut self.logic, &self.audio);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.graphics.save_positions(&self.logic);
+        self.logic.update(dt);
+        self.audio.update(&self.logic);
+        self.graphics.animate(dt);
+    }
+
+    fn collide(&mut self, _dt: f32) {
+        for position in check_collision(&mut self.logic, &self.audio) {
+            self.graphics.explode(position);
+        }
+    }
+
+    fn render(&mut self, window: &mut Window, alpha: f32) {
+        self.graphics.update(&self.logic, window, &self.assets, alpha);
+        self.graphics.draw(window);
+    }
+
+    fn is_over(&self) -> bool {
+        self.logic.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Keycode;
+
+    use engine::audio::NullMixer;
+    use engine::game_loop::simulate;
+    use engine::graphics::NullRenderer;
+    use engine::backend::key_down;
+
+    use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+    use super::*;
+
+    #[test]
+    fn full_match_runs_headless() {
+        let mixer = NullMixer::new();
+        let sounds = mixer.record();
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let renderer = NullRenderer::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+        let frames = renderer.record();
+        let mut window = Window { canvas: Box::new(renderer), event_pump: None };
+
+        let mut combat = Combat::new(Audio::new(Box::new(mixer)).unwrap(), WINDOW_WIDTH, WINDOW_HEIGHT, &ttf_context, 0).unwrap();
+
+        // Both tanks move forward and fire once per second during a minute.
+        combat.handle_event(key_down(Keycode::Up));
+        combat.handle_event(key_down(Keycode::Z));
+        for _ in 0..60 {
+            combat.handle_event(key_down(Keycode::Down));
+            combat.handle_event(key_down(Keycode::S));
+            simulate(&mut combat, &mut window, 120);
+        }
+
+        let sounds = sounds.borrow();
+        let shoot_count = sounds.played.iter().filter(|path| path.ends_with("shoot.wav")).count();
+        let explosion_count = sounds.played.iter().filter(|path| path.ends_with("explosion.wav")).count();
+        let score = &combat.logic.score;
+        assert!(shoot_count > 0);
+        assert_eq!(explosion_count as u32, score.get_left_score() + score.get_right_score());
+        assert_eq!(frames.borrow().frame_count, 60 * 120);
+    }
+}
@@ -1,26 +1,48 @@
+use std::f32::consts::TAU;
+
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
-use sdl2::ttf::Sdl2TtfContext;
 
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::interpolate;
 use engine::geometry;
 use engine::geometry::AsRect;
-use engine::graphics::{RectSprite, RenderedString, Sprite, Window};
+use engine::graphics::{Camera, RectSprite, Renderer, RenderedString, Sprite, Window};
+use engine::math::Vec2;
+use engine::particles::{ParticleConfig, ParticleSystem};
+use engine::random::Rng;
+use engine::res;
 
 use crate::logic::{BLOCK_COL_COUNT, BLOCK_ROW_COUNT, BOARD_BOTTOM_LIMIT, BOARD_LEFT_LIMIT, BOARD_RIGHT_LIMIT, BOARD_TOP_LIMIT, BOARD_TOP_LIMIT_HEIGHT, Logic, Map, TANK_HEIGHT, TANK_WIDTH};
 use crate::logic;
 
-pub const FONT_PATH: &str = "res/atari.ttf";
+pub const FONT_PATH: &str = res!("atari.ttf");
 pub const FONT_SIZE: u16 = 96;
 
-pub const TANK_SPRITE_PATH: &str = "res/tank.bmp";
+pub const TANK_SPRITE_PATH: &str = res!("tank.bmp");
+/// Farthest a shell is drawn from its previous position, it has been fired again when it moves more in a step.
+pub const SHELL_MAX_STEP: f32 = 0.1;
 pub const LEFT_TANK_COLOR: Color = Color::RGB(255, 0, 0);
 pub const RIGHT_TANK_COLOR: Color = Color::RGB(0, 0, 255);
 
 pub const LIMIT_COLOR: Color = Color::WHITE;
 pub const DECOR_COLOR: Color = Color::WHITE;
 
-pub const BLOCK_SIZE: i32 = 18;
+pub const IMPACT_SHAKE_AMPLITUDE: f32 = 0.01;
+pub const IMPACT_SHAKE_DURATION: f32 = 0.3;
+
+pub const PARTICLE_CAPACITY: usize = 256;
+pub const EXPLOSION_PARTICLE_COUNT: usize = 60;
+pub const EXPLOSION: ParticleConfig = ParticleConfig {
+    lifetime: (0.3, 0.8),
+    speed: (0.05, 0.3),
+    direction: 0.,
+    spread: TAU,
+    gravity: Vec2::ZERO,
+    colors: (Color::RGB(255, 220, 0), Color::RGBA(255, 0, 0, 0)),
+    sizes: (0.012, 0.004),
+};
 
 pub const LEFT_SCORE_POSITION_X: i32 = 100;
 pub const LEFT_SCORE_POSITION_Y: i32 = 50;
@@ -34,38 +56,28 @@ pub const RIGHT_SCORE_COLOR: Color = RIGHT_TANK_COLOR;
 /// It is a grid of rectangle figures.
 pub struct Decor {
     blocks: [[RectSprite; BLOCK_COL_COUNT]; BLOCK_ROW_COUNT],
-    x_shift: i32,
-    y_shift: i32,
 }
 
 impl Decor {
     /// Create a grid of rect that will be updated accordingly to a map
-    pub fn new(x_shift: i32, y_shift: i32, canvas_width: u32) -> Decor {
-        let ys = y_shift as f32 + (canvas_width as f32 * BOARD_TOP_LIMIT);
-        let xs = x_shift as f32 + (canvas_width as f32 * BOARD_LEFT_LIMIT);
-
+    pub fn new() -> Decor {
         let blocks = [[RectSprite::new(0, 0, DECOR_COLOR); BLOCK_COL_COUNT]; BLOCK_ROW_COUNT];
         Decor {
             blocks,
-            x_shift: xs as i32,
-            y_shift: ys as i32,
         }
     }
 
     /// Update the grid of rectangle
-    fn update(&mut self, map: &Map) {
+    fn update(&mut self, map: &Map, camera: &Camera) {
         for j in 0..BLOCK_ROW_COUNT {
             for i in 0..BLOCK_COL_COUNT {
                 let block = &mut self.blocks[j][i];
                 let map_block = map.get_block(i as u32, j as u32);
                 match map_block {
                     None => block.hide(),
-                    Some(_) => {
+                    Some(rect) => {
                         block.show();
-                        block.rect.set_width(BLOCK_SIZE as u32);
-                        block.rect.set_height(BLOCK_SIZE as u32);
-                        block.rect.set_x(self.x_shift + (BLOCK_SIZE * i as i32));
-                        block.rect.set_y(self.y_shift + (BLOCK_SIZE * j as i32));
+                        block.update(rect, camera);
                     }
                 }
             }
@@ -73,7 +85,7 @@ impl Decor {
     }
 
     /// Draw to the screen the decor
-    fn draw(&self, canvas: &mut WindowCanvas) {
+    fn draw(&self, canvas: &mut dyn Renderer) {
         for j in 0..BLOCK_ROW_COUNT {
             for i in 0..BLOCK_COL_COUNT {
                 let block = &self.blocks[j][i];
@@ -92,11 +104,11 @@ pub struct Limit {
 }
 
 impl Limit {
-    pub fn new(y_shift: i32) -> Limit {
-        let left_limit = RectSprite::new(0, y_shift, LIMIT_COLOR);
-        let right_limit = RectSprite::new(0, y_shift, LIMIT_COLOR);
-        let top_limit = RectSprite::new(0, y_shift, LIMIT_COLOR);
-        let bottom_limit = RectSprite::new(0, y_shift, LIMIT_COLOR);
+    pub fn new() -> Limit {
+        let left_limit = RectSprite::default(LIMIT_COLOR);
+        let right_limit = RectSprite::default(LIMIT_COLOR);
+        let top_limit = RectSprite::default(LIMIT_COLOR);
+        let bottom_limit = RectSprite::default(LIMIT_COLOR);
 
         Limit {
             left_limit,
@@ -106,30 +118,26 @@ impl Limit {
         }
     }
 
-    pub fn update(&mut self, w: u32) {
+    pub fn update(&mut self, camera: &Camera) {
         self.left_limit.update(
             geometry::Rect::from_2_points(0., BOARD_TOP_LIMIT - BOARD_TOP_LIMIT_HEIGHT, BOARD_LEFT_LIMIT, 1.01),
-            w,
-            w,
+            camera,
         );
         self.top_limit.update(
             geometry::Rect::from_2_points(0., 0., 1.01, BOARD_TOP_LIMIT),
-            w,
-            w,
+            camera,
         );
         self.right_limit.update(
             geometry::Rect::from_2_points(BOARD_RIGHT_LIMIT, BOARD_TOP_LIMIT - BOARD_TOP_LIMIT_HEIGHT, 1.01, 1.01),
-            w,
-            w,
+            camera,
         );
         self.bottom_limit.update(
             geometry::Rect::from_2_points(0., BOARD_BOTTOM_LIMIT, 1.01, 1.01),
-            w,
-            w,
+            camera,
         );
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
         self.left_limit.draw(canvas);
         self.right_limit.draw(canvas);
         self.top_limit.draw(canvas);
@@ -137,153 +145,132 @@ impl Limit {
     }
 }
 
-pub struct Score<'a> {
-    left_score: String,
-    right_score: String,
-    rendered_left_score: RenderedString<'a>,
-    rendered_right_score: RenderedString<'a>,
+pub struct Score {
+    left_score: RenderedString,
+    right_score: RenderedString,
 }
 
-impl Score<'_> {
-    pub fn new(ttf_context: &Sdl2TtfContext) -> Score {
-        let left_score = "0".parse().unwrap();
-        let right_score = "0".parse().unwrap();
-
-        Score {
-            left_score,
-            right_score,
-            rendered_left_score: RenderedString::new_colored(
-                &"0".parse().unwrap(),
-                LEFT_SCORE_POSITION_X,
-                LEFT_SCORE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-                LEFT_SCORE_COLOR,
-            ),
-            rendered_right_score: RenderedString::new_colored(
-                &"0".parse().unwrap(),
-                RIGHT_SCORE_POSITION_X,
-                RIGHT_SCORE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-                RIGHT_SCORE_COLOR,
-            ),
-        }
+impl Score {
+    pub fn new(assets: &mut Assets) -> Result<Score, Error> {
+        let font = assets.font(FONT_PATH, FONT_SIZE)?;
+        Ok(Score {
+            left_score: RenderedString::new_colored("0", LEFT_SCORE_POSITION_X, LEFT_SCORE_POSITION_Y, assets, font, LEFT_SCORE_COLOR)?,
+            right_score: RenderedString::new_colored("0", RIGHT_SCORE_POSITION_X, RIGHT_SCORE_POSITION_Y, assets, font, RIGHT_SCORE_COLOR)?,
+        })
     }
 
-    pub fn update(&mut self, logic: &Logic, ttf_context: &Sdl2TtfContext) {
-        if !self.left_score.eq(&logic.score.get_left_score().to_string()) {
-            self.left_score = logic.score.get_left_score().to_string();
-            self.rendered_left_score = RenderedString::new_colored(
-                &self.left_score,
-                LEFT_SCORE_POSITION_X,
-                LEFT_SCORE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-                LEFT_SCORE_COLOR,
-            )
-        }
-
-        if !self.right_score.eq(&logic.score.get_right_score().to_string()) {
-            self.right_score = logic.score.get_right_score().to_string();
-            self.rendered_right_score = RenderedString::new_colored(
-                &self.right_score,
-                RIGHT_SCORE_POSITION_X,
-                RIGHT_SCORE_POSITION_Y,
-                ttf_context,
-                FONT_PATH,
-                FONT_SIZE,
-                RIGHT_SCORE_COLOR,
-            )
-        }
+    pub fn update(&mut self, logic: &Logic, assets: &Assets) {
+        self.left_score.set_text(&logic.score.get_left_score().to_string(), assets).expect("can't render the left score");
+        self.right_score.set_text(&logic.score.get_right_score().to_string(), assets).expect("can't render the right score");
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
-        self.rendered_right_score.draw(canvas);
-        self.rendered_left_score.draw(canvas);
+    pub fn draw(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        self.right_score.draw_scaled(canvas, camera);
+        self.left_score.draw_scaled(canvas, camera);
     }
 }
 
 /// Graphics representation of the tank and of his bullet
-pub struct Tank<'a> {
-    tank: Sprite<'a>,
+///
+/// The shell is drawn between its positions before and after the last step.
+pub struct Tank {
+    tank: Sprite,
     shell: RectSprite,
+    previous_shell: Vec2,
 }
 
-impl Tank<'_> {
-    pub fn new(y_shift: i32, color: Color, canvas_width: u32) -> Tank<'static> {
+impl Tank {
+    pub fn new(color: Color, canvas_width: u32, assets: &mut Assets) -> Result<Tank, Error> {
         let tank_rect = Rect::new(0, 0, (TANK_WIDTH * canvas_width as f32) as u32, (TANK_HEIGHT * canvas_width as f32) as u32);
-        let tank = Sprite::new(0, y_shift, TANK_SPRITE_PATH, tank_rect, color);
-        let shell = RectSprite::new(0, y_shift, color);
-        Tank {
+        let tank = Sprite::simple_new(assets.tinted_image(TANK_SPRITE_PATH, color)?, tank_rect);
+        let shell = RectSprite::default(color);
+        Ok(Tank {
             tank,
             shell,
-        }
+            previous_shell: Vec2::ZERO,
+        })
+    }
+
+    /// Keep the position of the shell before a step of the logic.
+    pub fn save_position(&mut self, logic_tank: &logic::Tank) {
+        self.previous_shell = logic_tank.get_shell().as_rect().min();
     }
 
-    pub fn update(&mut self, logic_tank: &logic::Tank, w: u32) {
+    /// Update the sprites of the tank and of its shell.
+    pub fn update(&mut self, logic_tank: &logic::Tank, camera: &Camera, alpha: f32) {
         let mut tank_angle = logic_tank.get_orientation().to_degrees() as f64;
         if logic_tank.is_impacted() {
             tank_angle = self.tank.angle + 45.;
         }
-        self.tank.update(logic_tank.as_rect(), tank_angle, w, w);
+        self.tank.update(logic_tank.as_rect(), tank_angle, camera);
         let logic_shell = logic_tank.get_shell();
 
         if logic_shell.is_destroyed() {
             self.shell.hide();
         } else {
             self.shell.show();
-            self.shell.update(logic_shell.as_rect(), w, w);
+            let shell = logic_shell.as_rect();
+            let position = interpolate(self.previous_shell, shell.min(), alpha, SHELL_MAX_STEP);
+            self.shell.update(geometry::Rect::from_min_size(position, shell.size()), camera);
         }
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
         self.shell.draw(canvas);
         self.tank.draw(canvas);
     }
 }
 
-pub struct Graphics<'a> {
-    left_tank: Tank<'a>,
-    right_tank: Tank<'a>,
+/// The board is a square at the bottom of the window, the scores being above it.
+pub struct Graphics {
+    camera: Camera,
+    left_tank: Tank,
+    right_tank: Tank,
     limit: Limit,
     decor: Decor,
-    score: Score<'a>,
+    score: Score,
+    particles: ParticleSystem,
 }
 
-impl Graphics<'_> {
+impl Graphics {
     /// Init the dynamic elements required to draw the game
-    pub fn new(canvas_width: u32, canvas_height: u32, ttf_context: &Sdl2TtfContext) -> Graphics {
-        let y_shift = (canvas_height - canvas_width) as i32;
-
-        let left_tank = Tank::new(y_shift, LEFT_TANK_COLOR, canvas_width);
-        let right_tank = Tank::new(y_shift, RIGHT_TANK_COLOR, canvas_width);
-        let limit = Limit::new(y_shift);
-        let decor = Decor::new(0, y_shift, canvas_width);
-        let score = Score::new(&ttf_context);
-        Graphics {
+    pub fn new(rng: Rng, canvas_width: u32, canvas_height: u32, assets: &mut Assets) -> Result<Graphics, Error> {
+        let world = geometry::Rect::from_2_points(0., 1. - canvas_height as f32 / canvas_width as f32, 1., 1.);
+        let camera = Camera::new(world, canvas_width, canvas_height);
+
+        let left_tank = Tank::new(LEFT_TANK_COLOR, canvas_width, assets)?;
+        let right_tank = Tank::new(RIGHT_TANK_COLOR, canvas_width, assets)?;
+        let limit = Limit::new();
+        let decor = Decor::new();
+        let score = Score::new(assets)?;
+        Ok(Graphics {
+            camera,
             left_tank,
             right_tank,
             limit,
             decor,
             score,
-        }
+            particles: ParticleSystem::new(PARTICLE_CAPACITY, rng),
+        })
+    }
+
+    /// Keep the positions of the fast elements before a step of the logic.
+    pub fn save_positions(&mut self, logic: &Logic) {
+        self.left_tank.save_position(&logic.left_tank);
+        self.right_tank.save_position(&logic.right_tank);
     }
 
-    /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window, ttf_context: &Sdl2TtfContext) {
-        let w = window.width();
+    /// Update the dynamic elements accordingly to the state of the game, alpha being the fraction of a step not simulated yet.
+    pub fn update(&mut self, logic: &Logic, window: &Window, assets: &Assets, alpha: f32) {
+        self.camera.fit(window);
 
-        self.left_tank.update(&logic.left_tank, w);
-        self.right_tank.update(&logic.right_tank, w);
+        self.left_tank.update(&logic.left_tank, &self.camera, alpha);
+        self.right_tank.update(&logic.right_tank, &self.camera, alpha);
 
-        self.limit.update(w);
-        self.decor.update(&logic.map);
+        self.limit.update(&self.camera);
+        self.decor.update(&logic.map, &self.camera);
 
-        self.score.update(logic, ttf_context);
+        self.score.update(logic, assets);
     }
 
     /// Draw the game.
@@ -293,14 +280,28 @@ impl Graphics<'_> {
     pub fn draw(&self, window: &mut Window) {
         window.clear();
 
-        let canvas = &mut window.canvas;
+        let canvas = window.canvas.as_mut();
 
         self.left_tank.draw(canvas);
         self.right_tank.draw(canvas);
         self.limit.draw(canvas);
         self.decor.draw(canvas);
-        self.score.draw(canvas);
+        self.particles.draw(canvas, &self.camera);
+        self.score.draw(canvas, &self.camera);
 
+        self.camera.draw_letterbox(canvas);
         canvas.present();
     }
+
+    /// Blow up a tank hit at the position, in the world, shaking the camera.
+    pub fn explode(&mut self, position: Vec2) {
+        self.camera.shake(IMPACT_SHAKE_AMPLITUDE, IMPACT_SHAKE_DURATION);
+        self.particles.burst(position, EXPLOSION_PARTICLE_COUNT, &EXPLOSION);
+    }
+
+    /// Advance the effects of the graphics, like the shake of the camera and the explosions.
+    pub fn animate(&mut self, dt: f32) {
+        self.camera.update(dt);
+        self.particles.update(dt);
+    }
 }
\ No newline at end of file
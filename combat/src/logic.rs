@@ -1,7 +1,10 @@
-use sdl2::surface::Surface;
-
+use engine::assets::load_surface;
+use engine::error::Error;
 use engine::geometry::{AsRect, Rect};
 use engine::physics::{Position, RectSolid, Velocity};
+use engine::res;
+use engine::spatial::TileGrid;
+use engine::tween::{Animate, Ease, Tween};
 
 pub const BOARD_LEFT_LIMIT: f32 = 0.05;
 pub const BOARD_RIGHT_LIMIT: f32 = 0.95;
@@ -15,6 +18,8 @@ pub const TANK_VELOCITY: f32 = 0.10;
 pub const TANK_ROTATION_ANGLE: f32 = std::f32::consts::PI / 8.;
 pub const TANK_ROTATION_DELAY: f32 = 0.25;
 pub const TANK_IMPACT_DELAY: f32 = 0.5;
+/// Speed of a tank just hit by a shell, slowing down to a stop at the end of the impact.
+pub const TANK_IMPACT_VELOCITY: f32 = 0.30;
 
 pub const SHELL_WIDTH: f32 = 0.005;
 pub const SHELL_HEIGHT: f32 = 0.005;
@@ -27,7 +32,7 @@ pub const RIGHT_TANK_Y0: f32 = 0.50;
 
 pub const BLOCK_ROW_COUNT: usize = 30;
 pub const BLOCK_COL_COUNT: usize = 30;
-pub const LEVELS: [&str; 3] = ["res/level_1.bmp", "res/level_2.bmp", "res/level_3.bmp"];
+pub const LEVELS: [&str; 3] = [res!("level_1.bmp"), res!("level_2.bmp"), res!("level_3.bmp")];
 
 /// Score of the game is one score per tank
 pub struct Score {
@@ -64,9 +69,10 @@ impl Score {
 }
 
 /// Represents the current level
+#[derive(Clone)]
 pub struct Map {
     /// A map is a grid of blocks that can exist or not
-    blocks: [[bool; BLOCK_COL_COUNT]; BLOCK_ROW_COUNT],
+    blocks: TileGrid,
 
     /// Index of the current level
     pub index: usize,
@@ -74,52 +80,40 @@ pub struct Map {
 
 impl Map {
     /// Load the map at the given index
-    pub fn load(map_index: usize) -> Map {
-        let mut blocks = [[false; BLOCK_COL_COUNT]; BLOCK_ROW_COUNT];
-        let surface = Surface::load_bmp(LEVELS[map_index as usize]).unwrap();
-        let pixels = surface.without_lock().unwrap();
+    pub fn load(map_index: usize) -> Result<Map, Error> {
+        let bounds = Rect::from_2_points(BOARD_LEFT_LIMIT, BOARD_TOP_LIMIT, BOARD_RIGHT_LIMIT, BOARD_BOTTOM_LIMIT);
+        let mut blocks = TileGrid::new(bounds, BLOCK_COL_COUNT, BLOCK_ROW_COUNT);
+        let path = LEVELS[map_index];
+        let surface = load_surface(path)?;
+        let pixels = surface.without_lock().ok_or_else(|| Error::Decode {
+            path: path.to_string(),
+            reason: "the pixels can't be read without locking the surface".to_string(),
+        })?;
         for j in 0..surface.height() {
             for i in 0..surface.width() {
                 let index = j * surface.pitch() + i;
                 let pixel = pixels[index as usize];
-                let mut block: bool = false;
-                if pixel == 0 {
-                    block = true;
-                }
-                blocks[j as usize][i as usize] = block;
+                blocks.set(i as usize, j as usize, pixel == 0);
             }
         }
-        Map {
+        Ok(Map {
             blocks,
             index: map_index,
-        }
-    }
-
-    fn width() -> f32 {
-        (BOARD_RIGHT_LIMIT - BOARD_LEFT_LIMIT) as f32 / BLOCK_COL_COUNT as f32
-    }
-    fn height() -> f32 {
-        (BOARD_BOTTOM_LIMIT - BOARD_TOP_LIMIT) as f32 / BLOCK_ROW_COUNT as f32
+        })
     }
 
     /// Get the rectangle representing a block at a given row and column of the grid
     pub fn get_block(&self, i: u32, j: u32) -> Option<Rect> {
-        let block = self.blocks[j as usize][i as usize];
-        let w = Map::width();
-        let h = Map::height();
-
-        match block {
+        match self.blocks.is_solid(i as usize, j as usize) {
             false => None,
-            true => Some(
-                Rect::new(
-                    BOARD_LEFT_LIMIT + (i as f32 * w),
-                    BOARD_TOP_LIMIT + (j as f32 * h),
-                    w,
-                    h,
-                )
-            )
+            true => Some(self.blocks.tile_rect(i as usize, j as usize)),
         }
     }
+
+    /// Get the rectangles of the blocks under a rectangle
+    pub fn blocks_under(&self, rect: &Rect) -> Vec<Rect> {
+        self.blocks.solid_tiles(rect)
+    }
 }
 
 /// A shell is a bullet fired by a tank
@@ -131,7 +125,7 @@ pub struct Shell {
 impl Shell {
     pub fn new() -> Shell {
         let pos = Position::new(0., 0.);
-        let vel = Velocity::new(0., 0.);
+        let vel = Velocity::ZERO;
         let w = SHELL_WIDTH;
         let h = SHELL_HEIGHT;
         let limit = Rect::from_2_points(-1., -1.0, 2.0, 2.0);
@@ -155,7 +149,7 @@ impl Shell {
     fn launch(&mut self, x0: f32, y0: f32, angle: f32) {
         self.is_destroyed = false;
         let pos = Position::new(x0 - (SHELL_WIDTH as f32 / 2.), y0 - (SHELL_HEIGHT as f32 / 2.));
-        let vel = Velocity::new(SHELL_VELOCITY * angle.cos(), SHELL_VELOCITY * angle.sin());
+        let vel = Velocity::polar(SHELL_VELOCITY, angle);
         self.solid.pos = pos;
         self.solid.vel = vel;
     }
@@ -177,21 +171,22 @@ impl AsRect for Shell {
 
 /// Each player is represented by a tank.
 /// It has an orientation which gives the move direction and the shell direction when launched.
-/// The tank has an impact state, live for a given delay, when it is hit by a shell.
+/// The tank is knocked back for a given delay when it is hit by a shell.
 pub struct Tank {
     solid: RectSolid,
     pub shell: Shell,
     orientation: f32,
     rotation_delay: f32,
-    is_impacted: bool,
-    impact_delay: f32,
+    knock_back: Option<Tween<f32>>,
+    turning_left: bool,
+    turning_right: bool,
 }
 
 impl Tank {
     /// Create a tank at a given position and a given orientation
     pub fn new(x0: f32, y0: f32, orientation: f32) -> Tank {
         let pos = Position::new(x0, y0);
-        let vel = Velocity::new(0., 0.);
+        let vel = Velocity::ZERO;
         let w = TANK_WIDTH;
         let h = TANK_HEIGHT;
         let limit = Rect::from_2_points(BOARD_LEFT_LIMIT, BOARD_TOP_LIMIT, BOARD_RIGHT_LIMIT, BOARD_BOTTOM_LIMIT);
@@ -203,8 +198,9 @@ impl Tank {
             shell: Shell::new(),
             orientation,
             rotation_delay: TANK_ROTATION_DELAY,
-            is_impacted: false,
-            impact_delay: 0.,
+            knock_back: None,
+            turning_left: false,
+            turning_right: false,
         }
     }
 
@@ -216,37 +212,34 @@ impl Tank {
         self.orientation
     }
 
-    pub fn is_impacted(&self) -> bool { self.is_impacted }
+    pub fn is_impacted(&self) -> bool { self.knock_back.is_some() }
 
-    /// When hit by a shell, the tank is impacted and is pushed backward, in the direction of the incoming shell.
+    /// When hit by a shell, the tank is impacted and is pushed backward, in the direction of the incoming shell,
+    /// slowing down until the end of the impact.
     pub fn impact(&mut self, angle: f32) {
-        if self.is_impacted { () }
+        if self.is_impacted() { return; }
         self.orientation = angle;
-        self.accelerate();
-        self.is_impacted = true;
+        self.solid.vel = Velocity::polar(TANK_IMPACT_VELOCITY, angle);
+        self.knock_back = Some(Tween::new(TANK_IMPACT_VELOCITY, 0., TANK_IMPACT_DELAY, Ease::QuadOut));
     }
 
     pub fn accelerate(&mut self) -> bool {
-        if self.is_impacted { return false; }
+        if self.is_impacted() { return false; }
 
-        self.solid.vel.set_vx(TANK_VELOCITY * self.orientation.cos());
-        self.solid.vel.set_vy(TANK_VELOCITY * self.orientation.sin());
+        self.solid.vel = Velocity::polar(TANK_VELOCITY, self.orientation);
         return true;
     }
 
     pub fn decelerate(&mut self) -> bool {
-        if self.is_impacted { return false; }
+        if self.is_impacted() { return false; }
 
-        self.solid.vel.set_vx(0.);
-        self.solid.vel.set_vy(0.);
+        self.solid.vel = Velocity::ZERO;
         return true;
     }
 
     fn turn(&mut self, dir: bool) {
         if dir { self.orientation += TANK_ROTATION_ANGLE; } else { self.orientation -= TANK_ROTATION_ANGLE; }
-        let v = self.solid.vel.mag();
-        self.solid.vel.set_vx(v * self.orientation.cos());
-        self.solid.vel.set_vy(v * self.orientation.sin());
+        self.solid.vel = Velocity::polar(self.solid.vel.length(), self.orientation);
     }
 
     pub fn is_turning(&self) -> bool {
@@ -254,11 +247,11 @@ impl Tank {
     }
 
     pub fn is_moving(&self) -> bool {
-        self.solid.vel.mag() > 0.0001
+        self.solid.vel.length() > 0.0001
     }
 
     pub fn turn_left(&mut self) -> bool {
-        if self.is_impacted { return false; }
+        if self.is_impacted() { return false; }
 
         if self.rotation_delay < TANK_ROTATION_DELAY {
             return false;
@@ -269,7 +262,7 @@ impl Tank {
     }
 
     pub fn turn_right(&mut self) -> bool {
-        if self.is_impacted { return false; }
+        if self.is_impacted() { return false; }
 
         if self.rotation_delay < TANK_ROTATION_DELAY {
             return false;
@@ -280,9 +273,19 @@ impl Tank {
         return true;
     }
 
+    /// Keep turning left while the command is held.
+    pub fn set_turning_left(&mut self, turning: bool) {
+        self.turning_left = turning;
+    }
+
+    /// Keep turning right while the command is held.
+    pub fn set_turning_right(&mut self, turning: bool) {
+        self.turning_right = turning;
+    }
+
     /// Fire a shell if the previous one is destroyed.
     pub fn fire(&mut self) -> bool {
-        if self.is_impacted { return false; }
+        if self.is_impacted() { return false; }
 
         if self.shell.is_destroyed {
             let rect = self.solid.as_rect();
@@ -292,28 +295,29 @@ impl Tank {
         return false;
     }
 
-    fn update(&mut self, dt: f32) {
+    /// Move the tank, stopping it against the blocks of the map and the other tank.
+    fn update(&mut self, dt: f32, map: &Map, other_tank: Rect) {
+        if self.turning_left {
+            self.turn_left();
+        }
+        if self.turning_right {
+            self.turn_right();
+        }
         self.rotation_delay += dt;
 
-        if self.is_impacted {
-            self.impact_delay += dt;
-
-            if self.impact_delay > TANK_IMPACT_DELAY {
-                self.impact_delay = 0.;
-                self.is_impacted = false;
-                self.solid.vel.set_vx(0.);
-                self.solid.vel.set_vy(0.);
+        if let Some(knock_back) = &mut self.knock_back {
+            knock_back.update(dt);
+            self.solid.vel = Velocity::polar(knock_back.value(), self.orientation);
+            if knock_back.is_finished() {
+                self.knock_back = None;
             }
         }
 
-        self.solid.update(dt);
+        let mut obstacles = map.blocks_under(&self.solid.swept_rect(dt));
+        obstacles.push(other_tank);
+        self.solid.update_until_hit(dt, &obstacles);
         self.shell.update(dt);
     }
-
-    /// Move backward the tank if a collision has happen
-    pub fn move_back(&mut self, dt: f32) {
-        self.solid.update(-dt);
-    }
 }
 
 impl AsRect for Tank {
@@ -329,19 +333,24 @@ pub struct Logic {
     pub left_tank: Tank,
     pub right_tank: Tank,
     pub map: Map,
+    maps: Vec<Map>,
     is_over: bool,
 }
 
 impl Logic {
-    /// Create a new game logic with default values for game settings
-    pub fn new() -> Logic {
-        Logic {
+    /// Create a new game logic with default values for game settings.
+    ///
+    /// All the levels are loaded at once, changing of map doesn't read them again.
+    pub fn new() -> Result<Logic, Error> {
+        let maps = (0..LEVELS.len()).map(Map::load).collect::<Result<Vec<Map>, Error>>()?;
+        Ok(Logic {
             score: Score::new(),
             left_tank: Tank::new(LEFT_TANK_X0, LEFT_TANK_Y0, 0.),
             right_tank: Tank::new(RIGHT_TANK_X0, RIGHT_TANK_Y0, std::f32::consts::PI),
-            map: Map::load(0),
+            map: maps[0].clone(),
+            maps,
             is_over: false,
-        }
+        })
     }
 
     /// Change current map, reset the score and the tank positions.
@@ -349,14 +358,16 @@ impl Logic {
         self.score = Score::new();
         self.left_tank = Tank::new(LEFT_TANK_X0, LEFT_TANK_Y0, 0.);
         self.right_tank = Tank::new(RIGHT_TANK_X0, RIGHT_TANK_Y0, std::f32::consts::PI);
-        self.map = Map::load((self.map.index + 1) % LEVELS.len());
+        self.map = self.maps[(self.map.index + 1) % LEVELS.len()].clone();
         self.is_over = false;
     }
 
     /// Update each entity of a delta of time and check if the game is over.
     pub fn update(&mut self, dt: f32) {
-        self.left_tank.update(dt);
-        self.right_tank.update(dt);
+        let right_tank = self.right_tank.as_rect();
+        self.left_tank.update(dt, &self.map, right_tank);
+        let left_tank = self.left_tank.as_rect();
+        self.right_tank.update(dt, &self.map, left_tank);
     }
 
     /// Set the game over
@@ -368,4 +379,25 @@ impl Logic {
     pub fn is_over(&self) -> bool {
         self.is_over
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tanks_stop_against_the_blocks() {
+        let bounds = Rect::from_2_points(BOARD_LEFT_LIMIT, BOARD_TOP_LIMIT, BOARD_RIGHT_LIMIT, BOARD_BOTTOM_LIMIT);
+        let mut blocks = TileGrid::new(bounds, BLOCK_COL_COUNT, BLOCK_ROW_COUNT);
+        blocks.set(15, 15, true);
+        let map = Map { blocks, index: 0 };
+        let block = map.get_block(15, 15).unwrap();
+
+        let mut tank = Tank::new(block.x0() - TANK_WIDTH - 0.05, block.y0(), 0.);
+        tank.accelerate();
+        for _ in 0..120 {
+            tank.update(1. / 120., &map, Rect::new(0., 0., 0., 0.));
+        }
+        assert!((tank.as_rect().x1() - block.x0()).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,108 @@
+//! Compare the broad phase of a grid with testing every pair of objects.
+//!
+//! Run with `cargo bench -p engine --bench spatial`.
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use engine::collide::collide;
+use engine::geometry::Rect;
+use engine::random::Rng;
+use engine::spatial::{Grid, TileGrid};
+
+const FRAMES: u32 = 100;
+
+fn random_rects(rng: &mut Rng, count: usize, size: f32) -> Vec<Rect> {
+    (0..count)
+        .map(|_| Rect::new(rng.rand(0, 1000) as f32 / 1000., rng.rand(0, 1000) as f32 / 1000., size, size))
+        .collect()
+}
+
+fn time<F: FnMut() -> usize>(mut frame: F) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for _ in 0..FRAMES {
+        hits = black_box(frame());
+    }
+    (start.elapsed() / FRAMES, hits)
+}
+
+fn report(name: &str, naive: (Duration, usize), indexed: (Duration, usize)) {
+    assert_eq!(naive.1, indexed.1, "{}: both methods must find the same hits", name);
+    println!(
+        "{:<30} naive {:>10.1?}  indexed {:>10.1?}  x{:.1}",
+        name,
+        naive.0,
+        indexed.0,
+        naive.0.as_secs_f64() / indexed.0.as_secs_f64()
+    );
+}
+
+/// Bullets against asteroids, the grid being rebuilt each frame.
+fn moving_objects(rng: &mut Rng, bullets: usize, asteroids: usize) {
+    let bullet_rects = random_rects(rng, bullets, 0.005);
+    let asteroid_rects = random_rects(rng, asteroids, 0.05);
+
+    let naive = time(|| {
+        bullet_rects
+            .iter()
+            .map(|bullet| asteroid_rects.iter().filter(|asteroid| collide(bullet, asteroid).is_some()).count())
+            .sum()
+    });
+
+    let mut grid = Grid::new(Rect::new(0., 0., 1., 1.), 16, 16);
+    let indexed = time(|| {
+        grid.clear();
+        for (i, asteroid) in asteroid_rects.iter().enumerate() {
+            grid.insert(i, asteroid);
+        }
+        bullet_rects
+            .iter()
+            .map(|bullet| grid.query(bullet).into_iter().filter(|i| collide(bullet, &asteroid_rects[*i]).is_some()).count())
+            .sum()
+    });
+
+    report(&format!("{} bullets x {} asteroids", bullets, asteroids), naive, indexed);
+}
+
+/// Tanks against the walls of a 30 × 30 map, as in combat.
+fn tile_map(rng: &mut Rng, tanks: usize) {
+    let mut tiles = TileGrid::new(Rect::new(0., 0., 1., 1.), 30, 30);
+    for row in 0..tiles.rows() {
+        for col in 0..tiles.cols() {
+            tiles.set(col, row, rng.rand(0, 4) == 0);
+        }
+    }
+    let tank_rects = random_rects(rng, tanks, 0.05);
+
+    let naive = time(|| {
+        let mut hits = 0;
+        for tank in &tank_rects {
+            for row in 0..tiles.rows() {
+                for col in 0..tiles.cols() {
+                    if tiles.is_solid(col, row) && collide(tank, &tiles.tile_rect(col, row)).is_some() {
+                        hits += 1;
+                    }
+                }
+            }
+        }
+        hits
+    });
+
+    let indexed = time(|| {
+        tank_rects
+            .iter()
+            .map(|tank| tiles.solid_tiles(tank).iter().filter(|tile| collide(tank, tile).is_some()).count())
+            .sum()
+    });
+
+    report(&format!("{} tanks x 30x30 tiles", tanks), naive, indexed);
+}
+
+fn main() {
+    let mut rng = Rng::new(0);
+    moving_objects(&mut rng, 10, 10);
+    moving_objects(&mut rng, 100, 100);
+    moving_objects(&mut rng, 1000, 1000);
+    tile_map(&mut rng, 2);
+    tile_map(&mut rng, 100);
+}
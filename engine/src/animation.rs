@@ -0,0 +1,249 @@
+//! Frame animation of the sprites.
+//!
+//! A [`SpriteSheet`] lists the frames of a sprite, each being a rectangle of an image.
+//! A [`Clip`] is a sequence of frames of a sheet, each shown for its own duration.
+//! An [`Animation`] plays the clips of a sprite by name, advanced by the `dt` of the game,
+//! and [`Animation::apply`] shows its current frame on a [`Sprite`].
+use std::collections::HashMap;
+
+use sdl2::rect::Rect;
+
+use crate::assets::Image;
+use crate::graphics::Sprite;
+
+/// Frames of a sprite, each being a part of an image.
+///
+/// The frames can be cut in a single image, or be whole images loaded from separate files.
+#[derive(Clone, Default)]
+pub struct SpriteSheet {
+    frames: Vec<(Image, Rect)>,
+}
+
+impl SpriteSheet {
+    pub fn new() -> SpriteSheet {
+        SpriteSheet::default()
+    }
+
+    /// Cut an image in frames of the same size, row by row.
+    pub fn from_grid(image: Image, frame_width: u32, frame_height: u32) -> SpriteSheet {
+        let mut sheet = SpriteSheet::new();
+        for row in 0..image.height() / frame_height {
+            for col in 0..image.width() / frame_width {
+                let rect = Rect::new((col * frame_width) as i32, (row * frame_height) as i32, frame_width, frame_height);
+                sheet.add_frame(image.clone(), rect);
+            }
+        }
+        sheet
+    }
+
+    /// A sheet whose frames are whole images, in order.
+    pub fn from_images(images: Vec<Image>) -> SpriteSheet {
+        let mut sheet = SpriteSheet::new();
+        for image in images {
+            let rect = image.rect();
+            sheet.add_frame(image, rect);
+        }
+        sheet
+    }
+
+    /// Add a frame, returning its index.
+    pub fn add_frame(&mut self, image: Image, rect: Rect) -> usize {
+        self.frames.push((image, rect));
+        self.frames.len() - 1
+    }
+
+    pub fn frame(&self, index: usize) -> (&Image, Rect) {
+        let (image, rect) = &self.frames[index];
+        (image, *rect)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// What a clip does once its last frame is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playback {
+    /// Start again from the first frame.
+    Loop,
+    /// Stay on the last frame, the clip being finished.
+    Once,
+}
+
+/// A sequence of frames of a sheet, with the duration of each of them in seconds.
+pub struct Clip {
+    frames: Vec<(usize, f32)>,
+    playback: Playback,
+    on_complete: Option<Box<dyn FnMut()>>,
+}
+
+impl Clip {
+    /// An empty clip, the frames being added with [`Clip::frame`].
+    pub fn new(playback: Playback) -> Clip {
+        Clip {
+            frames: Vec::new(),
+            playback,
+            on_complete: None,
+        }
+    }
+
+    /// A clip showing each frame for the same duration.
+    pub fn uniform(frames: &[usize], duration: f32, playback: Playback) -> Clip {
+        frames.iter().fold(Clip::new(playback), |clip, frame| clip.frame(*frame, duration))
+    }
+
+    /// Add a frame of the sheet, shown for a duration.
+    pub fn frame(mut self, index: usize, duration: f32) -> Clip {
+        assert!(duration > 0., "a frame must last some time");
+        self.frames.push((index, duration));
+        self
+    }
+
+    /// Call a function each time the clip completes: at the end of each loop, or once for a one-shot clip.
+    pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Clip {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    fn complete(&mut self) {
+        if let Some(callback) = &mut self.on_complete {
+            callback();
+        }
+    }
+}
+
+/// Clips of a sprite by name, one of them being played.
+#[derive(Default)]
+pub struct Animation {
+    clips: HashMap<String, Clip>,
+    current: Option<String>,
+    position: usize,
+    time: f32,
+    is_finished: bool,
+}
+
+impl Animation {
+    pub fn new() -> Animation {
+        Animation::default()
+    }
+
+    /// Add a clip, replacing the one of the same name.
+    pub fn add(&mut self, name: &str, clip: Clip) {
+        assert!(!clip.frames.is_empty(), "clip {} has no frame", name);
+        self.clips.insert(name.to_string(), clip);
+    }
+
+    /// Play a clip from its first frame, unless it is already playing.
+    ///
+    /// Panics if there is no clip of this name.
+    pub fn play(&mut self, name: &str) {
+        assert!(self.clips.contains_key(name), "unknown clip {}", name);
+        if self.current.as_deref() != Some(name) || self.is_finished {
+            self.current = Some(name.to_string());
+            self.restart();
+        }
+    }
+
+    /// Play the current clip again from its first frame.
+    pub fn restart(&mut self) {
+        self.position = 0;
+        self.time = 0.;
+        self.is_finished = false;
+    }
+
+    /// Name of the clip played.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Is the clip played a one-shot clip which is over?
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    /// Advance the clip played of dt, calling its completion callback when it completes.
+    pub fn update(&mut self, dt: f32) {
+        let clip = match &self.current {
+            Some(name) if !self.is_finished => self.clips.get_mut(name).unwrap(),
+            _ => return,
+        };
+
+        self.time += dt;
+        while self.time >= clip.frames[self.position].1 {
+            self.time -= clip.frames[self.position].1;
+            if self.position + 1 < clip.frames.len() {
+                self.position += 1;
+                continue;
+            }
+
+            match clip.playback {
+                Playback::Loop => self.position = 0,
+                Playback::Once => {
+                    self.time = 0.;
+                    self.is_finished = true;
+                }
+            }
+            clip.complete();
+            if self.is_finished {
+                break;
+            }
+        }
+    }
+
+    /// Index in the sheet of the frame shown, if a clip is played.
+    pub fn frame(&self) -> Option<usize> {
+        let clip = self.clips.get(self.current.as_ref()?)?;
+        Some(clip.frames[self.position].0)
+    }
+
+    /// Show the current frame on a sprite.
+    pub fn apply(&self, sheet: &SpriteSheet, sprite: &mut Sprite) {
+        if let Some(index) = self.frame() {
+            let (image, rect) = sheet.frame(index);
+            sprite.set_frame(image, rect);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn clips_loop_or_stop_on_their_last_frame() {
+        let completed = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&completed);
+
+        let mut animation = Animation::new();
+        animation.add("walk", Clip::uniform(&[0, 1], 0.5, Playback::Loop));
+        animation.add("explode", Clip::new(Playback::Once).frame(2, 0.1).frame(3, 0.3).on_complete(move || counter.set(counter.get() + 1)));
+
+        animation.play("walk");
+        animation.update(0.6);
+        assert_eq!(animation.frame(), Some(1));
+        animation.update(0.5);
+        assert_eq!(animation.frame(), Some(0));
+
+        animation.play("explode");
+        animation.update(0.2);
+        assert_eq!(animation.frame(), Some(3));
+        assert!(!animation.is_finished());
+
+        animation.update(10.);
+        assert!(animation.is_finished());
+        assert_eq!(animation.frame(), Some(3));
+        assert_eq!(completed.get(), 1);
+
+        // Playing a finished clip starts it again.
+        animation.play("explode");
+        assert_eq!(animation.frame(), Some(2));
+    }
+}
@@ -0,0 +1,188 @@
+//! Loading of the files used by the games: fonts, images and sounds.
+//!
+//! Each file is loaded once and shared afterwards: loading the same path again gives back the cached asset.
+//! A missing or invalid file is reported as an [`Error`] instead of a panic.
+//!
+//! Sounds are cached by the mixer itself, see [`crate::audio::Mixer::load`].
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::surface::Surface;
+use sdl2::ttf::{Font, Sdl2TtfContext};
+
+use crate::error::{Error, Result};
+
+/// Absolute path of a file of the `res` directory of the crate using the macro.
+///
+/// The path is resolved when the game is compiled,
+/// so it finds its resources whatever the directory it is launched from.
+///
+/// ```
+/// const FONT_PATH: &str = engine::res!("atari.ttf");
+/// assert!(FONT_PATH.ends_with("/res/atari.ttf"));
+/// ```
+#[macro_export]
+macro_rules! res {
+    ($path:expr) => {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/res/", $path)
+    };
+}
+
+/// Load a BMP file as a surface, for when its pixels are read rather than drawn.
+pub fn load_surface(path: &str) -> Result<Surface<'static>> {
+    Surface::load_bmp(path).map_err(|reason| Error::asset(path, reason))
+}
+
+/// A surface shared by all the sprites drawing it.
+///
+/// Cloning an image is cheap, and the clones are the same image:
+/// the renderer copies it once in its texture atlas, and keeps it there as long as one clone exists.
+#[derive(Clone)]
+pub struct Image(Rc<Surface<'static>>);
+
+impl Image {
+    pub fn new(surface: Surface<'static>) -> Image {
+        Image(Rc::new(surface))
+    }
+
+    pub fn surface(&self) -> &Surface<'static> {
+        &self.0
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    /// The whole image, at the origin.
+    pub fn rect(&self) -> Rect {
+        self.0.rect()
+    }
+
+    /// Key shared by the clones of the image, and by no other image while one of them exists.
+    pub(crate) fn key(&self) -> usize {
+        Rc::as_ptr(&self.0) as usize
+    }
+
+    pub(crate) fn downgrade(&self) -> Weak<Surface<'static>> {
+        Rc::downgrade(&self.0)
+    }
+
+    /// The image of a weak reference, unless all its clones are dropped.
+    pub(crate) fn upgrade(surface: &Weak<Surface<'static>>) -> Option<Image> {
+        surface.upgrade().map(Image)
+    }
+}
+
+/// Handle on a font loaded by [`Assets::font`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+/// Cache of the fonts and images of a game.
+pub struct Assets<'ttf> {
+    ttf_context: &'ttf Sdl2TtfContext,
+    fonts: Vec<Font<'ttf, 'static>>,
+    font_ids: HashMap<(String, u16), FontId>,
+    images: HashMap<(String, Option<Color>), Image>,
+    glyphs: RefCell<HashMap<(FontId, char), Image>>,
+}
+
+impl<'ttf> Assets<'ttf> {
+    pub fn new(ttf_context: &'ttf Sdl2TtfContext) -> Assets<'ttf> {
+        Assets {
+            ttf_context,
+            fonts: Vec::new(),
+            font_ids: HashMap::new(),
+            images: HashMap::new(),
+            glyphs: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Load a TTF font at the given point size.
+    pub fn font(&mut self, path: &str, point_size: u16) -> Result<FontId> {
+        let key = (path.to_string(), point_size);
+        if let Some(&id) = self.font_ids.get(&key) {
+            return Ok(id);
+        }
+
+        let font = self.ttf_context.load_font(path, point_size).map_err(|reason| Error::asset(path, reason))?;
+        let id = FontId(self.fonts.len());
+        self.fonts.push(font);
+        self.font_ids.insert(key, id);
+        Ok(id)
+    }
+
+    /// Load a BMP image.
+    pub fn image(&mut self, path: &str) -> Result<Image> {
+        self.load_image(path, None)
+    }
+
+    /// Load a BMP image, its colors being multiplied by the given color.
+    ///
+    /// Each tint of the same file is a different image.
+    pub fn tinted_image(&mut self, path: &str, color: Color) -> Result<Image> {
+        self.load_image(path, Some(color))
+    }
+
+    fn load_image(&mut self, path: &str, tint: Option<Color>) -> Result<Image> {
+        let key = (path.to_string(), tint);
+        if let Some(image) = self.images.get(&key) {
+            return Ok(image.clone());
+        }
+
+        let mut surface = load_surface(path)?;
+        if let Some(color) = tint {
+            surface.set_color_mod(color);
+        }
+        let image = Image::new(surface);
+        self.images.insert(key, image.clone());
+        Ok(image)
+    }
+
+    /// Render a text in a new image.
+    ///
+    /// The result is not cached, see [`crate::graphics::RenderedString`] to render a text only when it changes.
+    pub fn render_text(&self, font: FontId, text: &str, color: Color) -> Result<Image> {
+        self.fonts[font.0]
+            .render(text)
+            .solid(color)
+            .map(Image::new)
+            .map_err(|error| Error::Render { text: text.to_string(), reason: error.to_string() })
+    }
+
+    /// Render a character in white, only the first time it is asked for.
+    ///
+    /// The glyphs of a font all have the height of the font, and the width they take in a text.
+    pub fn glyph(&self, font: FontId, ch: char) -> Result<Image> {
+        if let Some(image) = self.glyphs.borrow().get(&(font, ch)) {
+            return Ok(image.clone());
+        }
+        let image = self.render_text(font, &ch.to_string(), Color::WHITE)?;
+        self.glyphs.borrow_mut().insert((font, ch), image.clone());
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_files_are_reported() {
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let mut assets = Assets::new(&ttf_context);
+
+        let error = assets.image("res/missing.bmp").err().unwrap();
+        assert!(matches!(error, Error::AssetNotFound { ref path } if path == "res/missing.bmp"));
+        assert!(matches!(assets.font("res/missing.ttf", 12), Err(Error::AssetNotFound { .. })));
+
+        // The manifest of the crate exists, but is not an image.
+        assert!(matches!(assets.image("Cargo.toml"), Err(Error::Decode { .. })));
+    }
+}
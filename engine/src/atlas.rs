@@ -0,0 +1,218 @@
+//! Texture atlas: the images are packed in a few big pages, each uploaded as a single texture.
+//!
+//! The images are copied in the pages the first time they are drawn, and a page is uploaded again only when it changes.
+//! The space of the dropped images is taken back by packing the images again when the pages are full.
+use std::collections::HashMap;
+use std::rc::Weak;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture, TextureCreator};
+use sdl2::surface::Surface;
+use sdl2::video::WindowContext;
+
+use crate::assets::Image;
+
+/// Size of the pages, big enough for all the images of the games.
+pub const PAGE_SIZE: u32 = 1024;
+
+/// Space left around each image, so the images do not bleed on each other when scaled.
+const PADDING: u32 = 1;
+
+/// Packs rectangles in an area, side by side on shelves stacked from the top.
+#[derive(Debug, Clone)]
+pub struct ShelfPacker {
+    width: u32,
+    height: u32,
+    x: u32,
+    y: u32,
+    shelf_height: u32,
+}
+
+impl ShelfPacker {
+    pub fn new(width: u32, height: u32) -> ShelfPacker {
+        ShelfPacker { width, height, x: 0, y: 0, shelf_height: 0 }
+    }
+
+    /// Place for a rectangle of this size, if there is some room left.
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<Rect> {
+        if width > self.width || height > self.height {
+            return None;
+        }
+        if self.x + width > self.width {
+            // Start a new shelf, if there is room for it.
+            if self.y + self.shelf_height + height > self.height {
+                return None;
+            }
+            self.y += self.shelf_height;
+            self.x = 0;
+            self.shelf_height = 0;
+        } else if self.y + height > self.height {
+            return None;
+        }
+
+        let rect = Rect::new(self.x as i32, self.y as i32, width, height);
+        self.x += width;
+        self.shelf_height = self.shelf_height.max(height);
+        Some(rect)
+    }
+
+    /// Free the whole area.
+    pub fn reset(&mut self) {
+        self.x = 0;
+        self.y = 0;
+        self.shelf_height = 0;
+    }
+}
+
+struct Page {
+    surface: Surface<'static>,
+    packer: ShelfPacker,
+    texture: Option<Texture>,
+    is_dirty: bool,
+}
+
+impl Page {
+    fn new(width: u32, height: u32) -> Page {
+        Page {
+            surface: Surface::new(width, height, PixelFormatEnum::ARGB8888).unwrap(),
+            packer: ShelfPacker::new(width, height),
+            texture: None,
+            is_dirty: true,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.surface.fill_rect(None, Color::RGBA(0, 0, 0, 0)).unwrap();
+        self.packer.reset();
+        self.is_dirty = true;
+    }
+
+    /// Copy an image in the page, if it fits, returning where it is.
+    fn insert(&mut self, image: &Image) -> Option<Rect> {
+        let place = self.packer.allocate(image.width() + PADDING, image.height() + PADDING)?;
+        let rect = Rect::new(place.x(), place.y(), image.width(), image.height());
+
+        // The pixels are copied as they are, with the tint of the image and its transparent color turned into alpha.
+        let mut pixels = image.surface().convert_format(PixelFormatEnum::ARGB8888).unwrap();
+        pixels.set_blend_mode(BlendMode::None).unwrap();
+        pixels.blit(None, &mut self.surface, rect).unwrap();
+        self.is_dirty = true;
+        Some(rect)
+    }
+}
+
+/// The pages of the images, and where each image is in them.
+///
+/// An image bigger than a page gets a page of its own.
+#[derive(Default)]
+pub struct TextureAtlas {
+    pages: Vec<Page>,
+    places: HashMap<usize, (Weak<Surface<'static>>, usize, Rect)>,
+}
+
+impl TextureAtlas {
+    pub fn new() -> TextureAtlas {
+        TextureAtlas::default()
+    }
+
+    /// Page of an image and its place in the page, copying it in the atlas if it is not there yet.
+    ///
+    /// Adding an image can move the other ones, which must be located again afterwards.
+    pub fn locate(&mut self, image: &Image) -> (usize, Rect) {
+        if let Some((_, page, rect)) = self.places.get(&image.key()) {
+            return (*page, *rect);
+        }
+
+        let place = match self.insert(image) {
+            Some(place) => place,
+            None => {
+                self.compact();
+                self.insert_or_add_page(image)
+            }
+        };
+        self.places.insert(image.key(), (image.downgrade(), place.0, place.1));
+        place
+    }
+
+    /// Page and place of an image already in the atlas.
+    pub fn place(&self, image: &Image) -> Option<(usize, Rect)> {
+        self.places.get(&image.key()).map(|(_, page, rect)| (*page, *rect))
+    }
+
+    fn insert(&mut self, image: &Image) -> Option<(usize, Rect)> {
+        self.pages.iter_mut().enumerate().find_map(|(index, page)| Some((index, page.insert(image)?)))
+    }
+
+    fn insert_or_add_page(&mut self, image: &Image) -> (usize, Rect) {
+        self.insert(image).unwrap_or_else(|| {
+            let size = PAGE_SIZE.max(image.width() + PADDING).max(image.height() + PADDING);
+            let mut page = Page::new(size, size);
+            let rect = page.insert(image).unwrap();
+            self.pages.push(page);
+            (self.pages.len() - 1, rect)
+        })
+    }
+
+    /// Pack again the images still used, dropping the other ones.
+    fn compact(&mut self) {
+        let mut images: Vec<Image> = self.places.values().filter_map(|(surface, _, _)| Image::upgrade(surface)).collect();
+        images.sort_by_key(|image| std::cmp::Reverse(image.height()));
+
+        for page in &mut self.pages {
+            page.clear();
+        }
+        self.places.clear();
+        for image in images {
+            let (page, rect) = self.insert_or_add_page(&image);
+            self.places.insert(image.key(), (image.downgrade(), page, rect));
+        }
+    }
+
+    /// Upload the pages changed since the last upload, returning how many there were.
+    pub fn upload(&mut self, texture_creator: &TextureCreator<WindowContext>) -> usize {
+        let mut uploads = 0;
+        for page in self.pages.iter_mut().filter(|page| page.is_dirty) {
+            let (width, height) = page.surface.size();
+            let texture = page.texture.get_or_insert_with(|| {
+                let mut texture = texture_creator.create_texture_static(PixelFormatEnum::ARGB8888, width, height).unwrap();
+                texture.set_blend_mode(BlendMode::Blend);
+                texture
+            });
+            let pitch = page.surface.pitch() as usize;
+            texture.update(None, page.surface.without_lock().unwrap(), pitch).unwrap();
+            page.is_dirty = false;
+            uploads += 1;
+        }
+        uploads
+    }
+
+    /// Texture of a page, once uploaded.
+    pub fn texture(&self, page: usize) -> &Texture {
+        self.pages[page].texture.as_ref().expect("page not uploaded")
+    }
+
+    /// Forget the places of the dropped images, their space being taken back at the next compaction.
+    pub fn drop_unused(&mut self) {
+        self.places.retain(|_, (surface, _, _)| surface.upgrade().is_some());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shelves_are_filled_then_stacked() {
+        let mut packer = ShelfPacker::new(10, 10);
+        assert_eq!(packer.allocate(6, 4), Some(Rect::new(0, 0, 6, 4)));
+        assert_eq!(packer.allocate(4, 2), Some(Rect::new(6, 0, 4, 2)));
+        assert_eq!(packer.allocate(5, 5), Some(Rect::new(0, 4, 5, 5)));
+        assert_eq!(packer.allocate(5, 2), Some(Rect::new(5, 4, 5, 2)));
+        assert_eq!(packer.allocate(1, 2), None);
+        assert_eq!(packer.allocate(11, 1), None);
+
+        packer.reset();
+        assert_eq!(packer.allocate(10, 10), Some(Rect::new(0, 0, 10, 10)));
+    }
+}
@@ -1,13 +1,754 @@
-use sdl2::mixer::{AUDIO_S16LSB, DEFAULT_CHANNELS};
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
 
-pub fn init_audio(channel_count: i32) {
+use sdl2::mixer::{AUDIO_S16LSB, Channel, Chunk, DEFAULT_CHANNELS, InitFlag, MAX_VOLUME, Music, Sdl2MixerContext};
+
+use crate::backend::Backend;
+use crate::error::{Error, Result};
+use crate::math::Vec2;
+
+use self::music::MusicState;
+use self::synth::SynthParams;
+
+pub mod music;
+pub mod synth;
+
+pub fn init_audio(channel_count: i32) -> Result<()> {
     let frequency = 44_100;
     let format = AUDIO_S16LSB; // signed 16 bit samples, in little-endian byte order
     let channels = DEFAULT_CHANNELS; // Stereo
     let chunk_size = 1_024;
-    sdl2::mixer::open_audio(frequency, format, channels, chunk_size).unwrap();
+    sdl2::mixer::open_audio(frequency, format, channels, chunk_size).map_err(Error::AudioInit)?;
 
     // Number of mixing channels available for sound effect `Chunk`s to play
     // simultaneously.
     sdl2::mixer::allocate_channels(channel_count);
+    Ok(())
+}
+
+/// Handle on a sound loaded by a [`Mixer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sound(usize);
+
+/// Handle on a music loaded by a [`Mixer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Track(usize);
+
+/// Plays the sounds of a game on mixing channels.
+///
+/// It is implemented by [`SdlMixer`] to play on the audio device,
+/// and by [`NullMixer`] to run the games silently.
+pub trait Mixer {
+    /// Load a sound file, the returned handle is used to play it.
+    ///
+    /// A file is loaded only once: loading it again gives the same handle.
+    fn load(&mut self, path: &str) -> Result<Sound>;
+
+    /// Load mono samples at [`synth::SAMPLE_RATE`] under a name, replacing the samples of the same name.
+    fn load_samples(&mut self, name: &str, samples: &[i16]) -> Result<Sound>;
+
+    /// Play a sound on the given channel, or on the first free channel if `None`.
+    ///
+    /// The sound is played `loops + 1` times, or forever if `loops` is -1.
+    /// A sound which can't be played, because all the channels are busy for instance, is skipped.
+    fn play(&self, sound: Sound, channel: Option<i32>, loops: i32);
+
+    /// Stop the sound played on a channel.
+    fn halt(&self, channel: i32);
+
+    fn is_playing(&self, channel: i32) -> bool;
+
+    /// Set the volume of a channel, from 0 for silence to 1 for the full volume.
+    fn set_volume(&self, channel: i32, volume: f32);
+
+    /// Place the sounds of a channel: the pan goes from -1 on the left to 1 on the right,
+    /// and the distance from 0, heard at full volume, to 1 for the quietest.
+    fn set_position(&self, channel: i32, pan: f32, distance: f32);
+
+    /// Load a music file, OGG or WAV, streamed while it is played.
+    ///
+    /// A file is loaded only once: loading it again gives the same handle.
+    fn load_music(&mut self, path: &str) -> Result<Track>;
+
+    /// Play a music in place of the one playing, fading in during a duration in seconds.
+    ///
+    /// The music is played `loops + 1` times, or forever if `loops` is -1.
+    fn play_music(&self, track: Track, loops: i32, fade_in: f32);
+
+    /// Fade out the music playing during a duration in seconds, stopping it at once if the duration is 0.
+    fn fade_out_music(&self, duration: f32);
+
+    fn is_music_playing(&self) -> bool;
+
+    /// Set the volume of the music, from 0 for silence to 1 for the full volume.
+    fn set_music_volume(&self, volume: f32);
+}
+
+/// Open the mixer matching the backend selected at startup, with a given number of channels.
+///
+/// If the audio device can't be opened, the game runs silently with a [`NullMixer`].
+pub fn open_mixer(backend: Backend, channel_count: i32) -> Box<dyn Mixer> {
+    match backend {
+        Backend::Sdl => match SdlMixer::new(channel_count) {
+            Ok(mixer) => Box::new(mixer),
+            Err(error) => {
+                eprintln!("{}, running without sound", error);
+                Box::new(NullMixer::new())
+            }
+        },
+        Backend::Headless => Box::new(NullMixer::new()),
+    }
+}
+
+/// Mixer playing the sounds through SDL_mixer.
+pub struct SdlMixer {
+    chunks: Vec<Chunk>,
+    sounds: HashMap<String, Sound>,
+    musics: Vec<Music<'static>>,
+    tracks: HashMap<String, Track>,
+    _context: Option<Sdl2MixerContext>,
+}
+
+impl SdlMixer {
+    /// Open the audio device with the given number of channels.
+    ///
+    /// Without the OGG decoder, only the WAV musics can be played.
+    pub fn new(channel_count: i32) -> Result<SdlMixer> {
+        init_audio(channel_count)?;
+        let context = sdl2::mixer::init(InitFlag::OGG).map_err(|error| eprintln!("{}, playing WAV musics only", error)).ok();
+        Ok(SdlMixer { chunks: Vec::new(), sounds: HashMap::new(), musics: Vec::new(), tracks: HashMap::new(), _context: context })
+    }
+}
+
+/// Duration in seconds as milliseconds, for the fades of SDL_mixer.
+fn millis(duration: f32) -> i32 {
+    (duration * 1000.).round() as i32
+}
+
+impl Mixer for SdlMixer {
+    fn load(&mut self, path: &str) -> Result<Sound> {
+        if let Some(&sound) = self.sounds.get(path) {
+            return Ok(sound);
+        }
+
+        let chunk = Chunk::from_file(path).map_err(|reason| Error::asset(path, reason))?;
+        let sound = Sound(self.chunks.len());
+        self.chunks.push(chunk);
+        self.sounds.insert(path.to_string(), sound);
+        Ok(sound)
+    }
+
+    fn load_samples(&mut self, name: &str, samples: &[i16]) -> Result<Sound> {
+        // The device plays stereo samples: each sample is played on both sides.
+        let stereo = samples.iter().flat_map(|sample| std::iter::repeat_n(*sample, 2)).collect::<Box<[i16]>>();
+        let chunk = Chunk::from_raw_buffer(stereo).map_err(|reason| Error::Decode { path: name.to_string(), reason })?;
+        match self.sounds.get(name) {
+            Some(&sound) => {
+                self.chunks[sound.0] = chunk;
+                Ok(sound)
+            }
+            None => {
+                let sound = Sound(self.chunks.len());
+                self.chunks.push(chunk);
+                self.sounds.insert(name.to_string(), sound);
+                Ok(sound)
+            }
+        }
+    }
+
+    fn play(&self, sound: Sound, channel: Option<i32>, loops: i32) {
+        let channel = channel.map_or(Channel::all(), Channel);
+        let _ = channel.play(&self.chunks[sound.0], loops);
+    }
+
+    fn halt(&self, channel: i32) {
+        Channel(channel).halt();
+    }
+
+    fn is_playing(&self, channel: i32) -> bool {
+        Channel(channel).is_playing()
+    }
+
+    fn set_volume(&self, channel: i32, volume: f32) {
+        Channel(channel).set_volume((volume * MAX_VOLUME as f32).round() as i32);
+    }
+
+    fn set_position(&self, channel: i32, pan: f32, distance: f32) {
+        // A centered sound is at full volume on both sides, SDL_mixer then removing the effect.
+        let side = |gain: f32| (gain.clamp(0., 1.) * 255.).round() as u8;
+        let _ = Channel(channel).set_panning(side(1. - pan), side(1. + pan));
+        let _ = Channel(channel).set_distance((distance.clamp(0., 1.) * 255.).round() as u8);
+    }
+
+    fn load_music(&mut self, path: &str) -> Result<Track> {
+        if let Some(&track) = self.tracks.get(path) {
+            return Ok(track);
+        }
+
+        let music = Music::from_file(path).map_err(|reason| Error::asset(path, reason))?;
+        let track = Track(self.musics.len());
+        self.musics.push(music);
+        self.tracks.insert(path.to_string(), track);
+        Ok(track)
+    }
+
+    fn play_music(&self, track: Track, loops: i32, fade_in: f32) {
+        let music = &self.musics[track.0];
+        let _ = if fade_in > 0. { music.fade_in(loops, millis(fade_in)) } else { music.play(loops) };
+    }
+
+    fn fade_out_music(&self, duration: f32) {
+        if duration > 0. {
+            let _ = Music::fade_out(millis(duration));
+        } else {
+            Music::halt();
+        }
+    }
+
+    fn is_music_playing(&self) -> bool {
+        Music::is_playing()
+    }
+
+    fn set_music_volume(&self, volume: f32) {
+        Music::set_volume((volume * MAX_VOLUME as f32).round() as i32);
+    }
+}
+
+/// What has been played by a [`NullMixer`].
+#[derive(Debug, Default)]
+pub struct AudioRecord {
+    /// Path of every sound played, in order, or its name for the samples.
+    pub played: Vec<String>,
+
+    /// Sounds looping forever, by channel.
+    pub looping: HashMap<i32, String>,
+
+    /// Last volume set on each channel.
+    pub volumes: HashMap<i32, f32>,
+
+    /// Last pan and distance set on each channel.
+    pub positions: HashMap<i32, (f32, f32)>,
+
+    /// Path of every music started, in order.
+    pub musics: Vec<String>,
+
+    /// Path of the music playing. The musics are never over, unless this is set back to `None`.
+    pub music: Option<String>,
+
+    /// Last volume set on the music.
+    pub music_volume: Option<f32>,
+}
+
+/// Mixer without audio device, recording the played sounds in memory.
+///
+/// The sound files are not read, but a missing file is still reported when loaded.
+pub struct NullMixer {
+    paths: Vec<String>,
+    music_paths: Vec<String>,
+    record: Rc<RefCell<AudioRecord>>,
+}
+
+impl NullMixer {
+    pub fn new() -> NullMixer {
+        NullMixer {
+            paths: Vec::new(),
+            music_paths: Vec::new(),
+            record: Rc::new(RefCell::new(AudioRecord::default())),
+        }
+    }
+
+    /// Shared access to the recorded sounds, still valid once the mixer is given to a game.
+    pub fn record(&self) -> Rc<RefCell<AudioRecord>> {
+        Rc::clone(&self.record)
+    }
+}
+
+impl Default for NullMixer {
+    fn default() -> NullMixer {
+        NullMixer::new()
+    }
+}
+
+impl Mixer for NullMixer {
+    fn load(&mut self, path: &str) -> Result<Sound> {
+        if let Some(index) = self.paths.iter().position(|loaded| loaded == path) {
+            return Ok(Sound(index));
+        }
+
+        std::fs::metadata(path).map_err(|error| Error::asset(path, error))?;
+        self.paths.push(path.to_string());
+        Ok(Sound(self.paths.len() - 1))
+    }
+
+    fn load_samples(&mut self, name: &str, _samples: &[i16]) -> Result<Sound> {
+        if let Some(index) = self.paths.iter().position(|loaded| loaded == name) {
+            return Ok(Sound(index));
+        }
+
+        self.paths.push(name.to_string());
+        Ok(Sound(self.paths.len() - 1))
+    }
+
+    fn play(&self, sound: Sound, channel: Option<i32>, loops: i32) {
+        let path = &self.paths[sound.0];
+        let mut record = self.record.borrow_mut();
+        record.played.push(path.clone());
+        if let (Some(channel), -1) = (channel, loops) {
+            record.looping.insert(channel, path.clone());
+        }
+    }
+
+    fn halt(&self, channel: i32) {
+        self.record.borrow_mut().looping.remove(&channel);
+    }
+
+    fn is_playing(&self, channel: i32) -> bool {
+        self.record.borrow().looping.contains_key(&channel)
+    }
+
+    fn set_volume(&self, channel: i32, volume: f32) {
+        self.record.borrow_mut().volumes.insert(channel, volume);
+    }
+
+    fn set_position(&self, channel: i32, pan: f32, distance: f32) {
+        self.record.borrow_mut().positions.insert(channel, (pan, distance));
+    }
+
+    fn load_music(&mut self, path: &str) -> Result<Track> {
+        if let Some(index) = self.music_paths.iter().position(|loaded| loaded == path) {
+            return Ok(Track(index));
+        }
+
+        std::fs::metadata(path).map_err(|error| Error::asset(path, error))?;
+        self.music_paths.push(path.to_string());
+        Ok(Track(self.music_paths.len() - 1))
+    }
+
+    fn play_music(&self, track: Track, _loops: i32, _fade_in: f32) {
+        let path = &self.music_paths[track.0];
+        let mut record = self.record.borrow_mut();
+        record.musics.push(path.clone());
+        record.music = Some(path.clone());
+    }
+
+    fn fade_out_music(&self, _duration: f32) {
+        self.record.borrow_mut().music = None;
+    }
+
+    fn is_music_playing(&self) -> bool {
+        self.record.borrow().music.is_some()
+    }
+
+    fn set_music_volume(&self, volume: f32) {
+        self.record.borrow_mut().music_volume = Some(volume);
+    }
+}
+
+/// Volume buses: the master bus scales both the music and the sound effects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bus {
+    Master,
+    Music,
+    Sfx,
+}
+
+/// Handle on a sound played by a [`SoundManager`], to stop a looped sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SoundHandle {
+    channel: i32,
+    id: u64,
+}
+
+/// A sound of a bank, with the priority and the volume it is played with.
+struct BankSound {
+    sound: Sound,
+    priority: u8,
+    volume: f32,
+}
+
+/// A sound played on a channel, the ids increasing with the time the sounds are played.
+#[derive(Clone, Copy)]
+struct Voice {
+    id: u64,
+    priority: u8,
+    volume: f32,
+    bus: Bus,
+}
+
+/// Distance given to the mixer for the sounds at the range of the listener or beyond,
+/// far enough for them to be quieter but not so far that they are silent.
+pub const MAX_DISTANCE: f32 = 0.4;
+
+/// Smallest range of the listener, so every position is located, even with a range of 0 given by a setting.
+pub const MIN_RANGE: f32 = 1e-3;
+
+/// Plays the sounds of a bank by name, picking the channels itself.
+///
+/// When all the channels are busy, a sound takes the channel of the oldest sound of the lowest priority,
+/// unless all of them have a higher priority than its own: then it is skipped.
+/// A sound missing from the bank is skipped too, and reported the first time it is played.
+/// The volume of a sound is its own volume scaled by the master and the sfx buses,
+/// or by the music bus for the clips of the music.
+///
+/// A sound played at a world position is panned and attenuated according to its place relative to the listener:
+/// it is fully on one side and at its quietest at the range of the listener, where it is still heard.
+pub struct SoundManager {
+    mixer: Box<dyn Mixer>,
+    sounds: HashMap<String, BankSound>,
+    voices: RefCell<Vec<Option<Voice>>>,
+    next_id: Cell<u64>,
+    volumes: HashMap<Bus, f32>,
+    muted: HashMap<Bus, bool>,
+    listener: Vec2,
+    range: f32,
+    tracks: HashMap<String, Track>,
+    music: MusicState,
+    /// Names played without a sound, already reported.
+    missing: RefCell<HashSet<String>>,
+}
+
+impl SoundManager {
+    /// A manager without sound, playing on the given number of channels of the mixer.
+    pub fn new(mixer: Box<dyn Mixer>, channel_count: i32) -> SoundManager {
+        SoundManager {
+            mixer,
+            sounds: HashMap::new(),
+            voices: RefCell::new(vec![None; channel_count.max(0) as usize]),
+            next_id: Cell::new(0),
+            volumes: HashMap::new(),
+            muted: HashMap::new(),
+            listener: Vec2::ZERO,
+            range: 1.,
+            tracks: HashMap::new(),
+            music: MusicState::default(),
+            missing: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Place the listener in the world, the sounds farther than the range being heard at their quietest.
+    ///
+    /// The range is at least [`MIN_RANGE`].
+    pub fn set_listener(&mut self, position: Vec2, range: f32) {
+        self.listener = position;
+        self.range = range.max(MIN_RANGE);
+    }
+
+    /// Pan and distance of a world position for the listener.
+    pub fn locate(&self, position: Vec2) -> (f32, f32) {
+        let offset = position - self.listener;
+        let pan = (offset.x / self.range).clamp(-1., 1.);
+        let distance = (offset.length() / self.range).min(1.) * MAX_DISTANCE;
+        (pan, distance)
+    }
+
+    /// Load a sound under a name, replacing the sound of the same name.
+    ///
+    /// The higher the priority, the less the sound can be interrupted. The volume goes from 0 to 1.
+    pub fn add(&mut self, name: &str, path: &str, priority: u8, volume: f32) -> Result<()> {
+        let sound = self.mixer.load(path)?;
+        self.sounds.insert(name.to_string(), BankSound { sound, priority, volume });
+        Ok(())
+    }
+
+    /// Generate a sound from synth parameters under a name, replacing the sound of the same name.
+    pub fn add_synth(&mut self, name: &str, params: &SynthParams, priority: u8, volume: f32) -> Result<()> {
+        let sound = self.mixer.load_samples(name, &params.generate())?;
+        self.sounds.insert(name.to_string(), BankSound { sound, priority, volume });
+        Ok(())
+    }
+
+    /// Load the sounds listed by a bank manifest, one sound per line:
+    ///
+    /// ```text
+    /// # name file [priority] [volume]
+    /// explosion explosion.wav 3
+    /// forward forward.wav 2 0.5
+    /// laser laser.sfx
+    /// ```
+    ///
+    /// The files are relative to the manifest, the `.sfx` ones being synth parameters generated by [`synth`].
+    /// The priority is 0 and the volume is 1 by default.
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load_bank(&mut self, manifest: &str) -> Result<()> {
+        let text = fs::read_to_string(manifest).map_err(|error| Error::asset(manifest, error))?;
+        let directory = Path::new(manifest).parent().unwrap_or_else(|| Path::new(""));
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let syntax_error = || Error::Decode { path: manifest.to_string(), reason: format!("invalid line {}: {:?}", index + 1, line) };
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            let (name, file) = match fields[..] {
+                [name, file, ..] if fields.len() <= 4 => (name, file),
+                _ => return Err(syntax_error()),
+            };
+            let priority = fields.get(2).map_or(Ok(0), |priority| priority.parse()).map_err(|_| syntax_error())?;
+            let volume = fields.get(3).map_or(Ok(1.), |volume| volume.parse()).map_err(|_| syntax_error())?;
+
+            let path = directory.join(file).to_string_lossy().into_owned();
+            if file.ends_with(".sfx") {
+                self.add_synth(name, &SynthParams::load(&path)?, priority, volume)?;
+            } else {
+                self.add(name, &path, priority, volume)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.sounds.contains_key(name)
+    }
+
+    /// Play a sound once, returning its handle, or `None` if there is no channel or no sound of this name.
+    pub fn play(&self, name: &str) -> Option<SoundHandle> {
+        self.play_repeated(name, 0)
+    }
+
+    /// Play a sound until it is stopped.
+    pub fn play_looped(&self, name: &str) -> Option<SoundHandle> {
+        self.play_repeated(name, -1)
+    }
+
+    /// Play a sound `loops + 1` times, or forever if `loops` is -1.
+    pub fn play_repeated(&self, name: &str, loops: i32) -> Option<SoundHandle> {
+        self.play_voice(name, loops, None, Bus::Sfx)
+    }
+
+    /// Play a sound once on a bus, like the clips of a music on the music bus.
+    pub fn play_on(&self, name: &str, bus: Bus) -> Option<SoundHandle> {
+        self.play_voice(name, 0, None, bus)
+    }
+
+    /// Play a sound once at a world position.
+    pub fn play_at(&self, name: &str, position: Vec2) -> Option<SoundHandle> {
+        self.play_voice(name, 0, Some(position), Bus::Sfx)
+    }
+
+    /// Play a sound `loops + 1` times at a world position, or forever if `loops` is -1.
+    pub fn play_repeated_at(&self, name: &str, loops: i32, position: Vec2) -> Option<SoundHandle> {
+        self.play_voice(name, loops, Some(position), Bus::Sfx)
+    }
+
+    /// Play a sound at a world position until it is stopped, the position being moved with [`SoundManager::set_position`].
+    pub fn play_looped_at(&self, name: &str, position: Vec2) -> Option<SoundHandle> {
+        self.play_voice(name, -1, Some(position), Bus::Sfx)
+    }
+
+    /// Move a sound which is still playing.
+    pub fn set_position(&self, handle: SoundHandle, position: Vec2) {
+        if self.is_playing(handle) {
+            let (pan, distance) = self.locate(position);
+            self.mixer.set_position(handle.channel, pan, distance);
+        }
+    }
+
+    fn play_voice(&self, name: &str, loops: i32, position: Option<Vec2>, bus: Bus) -> Option<SoundHandle> {
+        let bank_sound = match self.sounds.get(name) {
+            Some(bank_sound) => bank_sound,
+            None => {
+                if self.missing.borrow_mut().insert(name.to_string()) {
+                    eprintln!("unknown sound {}, not played", name);
+                }
+                return None;
+            }
+        };
+        let channel = self.free_channel(bank_sound.priority)?;
+
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let voice = Voice { id, priority: bank_sound.priority, volume: bank_sound.volume, bus };
+        self.voices.borrow_mut()[channel as usize] = Some(voice);
+
+        let (pan, distance) = position.map_or((0., 0.), |position| self.locate(position));
+        self.mixer.set_volume(channel, voice.volume * self.gain(bus));
+        self.mixer.set_position(channel, pan, distance);
+        self.mixer.play(bank_sound.sound, Some(channel), loops);
+        Some(SoundHandle { channel, id })
+    }
+
+    /// A free channel, or the one of the oldest sound of the lowest priority if not above the given one, halted.
+    fn free_channel(&self, priority: u8) -> Option<i32> {
+        let voices = self.voices.borrow();
+        let mut stolen: Option<(i32, Voice)> = None;
+        for (channel, voice) in voices.iter().enumerate() {
+            let channel = channel as i32;
+            let voice = match voice {
+                Some(voice) if self.mixer.is_playing(channel) => *voice,
+                _ => return Some(channel),
+            };
+            if stolen.is_none_or(|(_, stolen)| (voice.priority, voice.id) < (stolen.priority, stolen.id)) {
+                stolen = Some((channel, voice));
+            }
+        }
+
+        let (channel, _) = stolen.filter(|(_, voice)| voice.priority <= priority)?;
+        self.mixer.halt(channel);
+        Some(channel)
+    }
+
+    /// Is the sound of the handle still playing? A sound whose channel has been taken by another one is not.
+    pub fn is_playing(&self, handle: SoundHandle) -> bool {
+        let voices = self.voices.borrow();
+        let is_current = matches!(voices[handle.channel as usize], Some(voice) if voice.id == handle.id);
+        is_current && self.mixer.is_playing(handle.channel)
+    }
+
+    /// Stop the sound of a handle, if it is still playing.
+    pub fn stop(&self, handle: SoundHandle) {
+        if self.is_playing(handle) {
+            self.mixer.halt(handle.channel);
+            self.voices.borrow_mut()[handle.channel as usize] = None;
+        }
+    }
+
+    /// Stop all the sounds.
+    pub fn stop_all(&self) {
+        for (channel, voice) in self.voices.borrow_mut().iter_mut().enumerate() {
+            if voice.take().is_some() {
+                self.mixer.halt(channel as i32);
+            }
+        }
+    }
+
+    /// Volume of a bus, from 0 to 1, whether it is muted or not.
+    pub fn volume(&self, bus: Bus) -> f32 {
+        self.volumes.get(&bus).copied().unwrap_or(1.)
+    }
+
+    /// Set the volume of a bus, applied at once to the sounds and the music playing.
+    pub fn set_volume(&mut self, bus: Bus, volume: f32) {
+        self.volumes.insert(bus, volume.clamp(0., 1.));
+        self.apply_volumes();
+    }
+
+    pub fn is_muted(&self, bus: Bus) -> bool {
+        self.muted.get(&bus).copied().unwrap_or(false)
+    }
+
+    /// Mute or unmute a bus, keeping its volume.
+    pub fn set_muted(&mut self, bus: Bus, muted: bool) {
+        self.muted.insert(bus, muted);
+        self.apply_volumes();
+    }
+
+    /// Volume actually heard on a bus: its volume scaled by the master one, or 0 if one of them is muted.
+    pub fn gain(&self, bus: Bus) -> f32 {
+        let gain = |bus| if self.is_muted(bus) { 0. } else { self.volume(bus) };
+        match bus {
+            Bus::Master => gain(Bus::Master),
+            _ => gain(Bus::Master) * gain(bus),
+        }
+    }
+
+    fn apply_volumes(&self) {
+        for (channel, voice) in self.voices.borrow().iter().enumerate() {
+            if let Some(voice) = voice {
+                self.mixer.set_volume(channel as i32, voice.volume * self.gain(voice.bus));
+            }
+        }
+        self.mixer.set_music_volume(self.gain(Bus::Music));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+    #[test]
+    fn sounds_take_the_channels_of_lower_priorities() {
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let mut sounds = SoundManager::new(Box::new(mixer), 2);
+        sounds.add("engine", FILE, 1, 0.5).unwrap();
+        sounds.add("explosion", FILE, 2, 1.).unwrap();
+        sounds.add("click", FILE, 0, 1.).unwrap();
+
+        let left = sounds.play_looped("engine").unwrap();
+        let right = sounds.play_looped("engine").unwrap();
+        assert_eq!(sounds.play("click"), None);
+
+        let explosion = sounds.play_looped("explosion").unwrap();
+        assert!(!sounds.is_playing(left));
+        assert!(sounds.is_playing(right));
+        assert!(sounds.is_playing(explosion));
+
+        // A stolen handle doesn't stop the sound which took its channel.
+        sounds.stop(left);
+        assert!(sounds.is_playing(explosion));
+        sounds.stop(explosion);
+        assert_eq!(record.borrow().looping.len(), 1);
+
+        sounds.set_volume(Bus::Master, 0.5);
+        assert_eq!(record.borrow().volumes[&right.channel], 0.25);
+        sounds.set_muted(Bus::Sfx, true);
+        assert_eq!(record.borrow().volumes[&right.channel], 0.);
+        assert_eq!(sounds.gain(Bus::Music), 0.5);
+
+        // A misspelled sound is skipped.
+        assert!(sounds.play("explsion").is_none());
+    }
+
+    #[test]
+    fn sounds_are_panned_by_their_position_relative_to_the_listener() {
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let mut sounds = SoundManager::new(Box::new(mixer), 2);
+        sounds.add("bounce", FILE, 0, 1.).unwrap();
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+
+        assert_eq!(sounds.locate(Vec2::new(0.25, 0.5)), (-0.5, 0.5 * MAX_DISTANCE));
+        assert_eq!(sounds.locate(Vec2::new(2., 0.5)), (1., MAX_DISTANCE));
+
+        let handle = sounds.play_looped_at("bounce", Vec2::new(0.75, 0.5)).unwrap();
+        assert_eq!(record.borrow().positions[&handle.channel], (0.5, 0.5 * MAX_DISTANCE));
+        sounds.set_position(handle, Vec2::new(0.5, 0.5));
+        assert_eq!(record.borrow().positions[&handle.channel], (0., 0.));
+
+        // A sound without position is centered, whatever was played before on its channel.
+        sounds.stop(handle);
+        let handle = sounds.play_looped("bounce").unwrap();
+        assert_eq!(record.borrow().positions[&handle.channel], (0., 0.));
+
+        // A sound in a corner of the board, out of the range, is on its side and heard at more than half its volume.
+        let handle = sounds.play_at("bounce", Vec2::ZERO).unwrap();
+        let (pan, distance) = record.borrow().positions[&handle.channel];
+        assert_eq!(pan, -1.);
+        assert!(distance < 0.5);
+
+        // A listener without range hears everything on the sides, at the quietest.
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.);
+        assert_eq!(sounds.locate(Vec2::new(0.75, 0.5)), (1., MAX_DISTANCE));
+        assert_eq!(sounds.locate(Vec2::new(0.5, 0.5)), (0., 0.));
+    }
+
+    #[test]
+    fn banks_are_read_from_manifests() {
+        let directory = std::env::temp_dir().join(format!("engine-bank-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::copy(FILE, directory.join("shoot.wav")).unwrap();
+        let manifest = directory.join("sounds.bank");
+
+        let mut sounds = SoundManager::new(Box::new(NullMixer::new()), 4);
+        fs::write(directory.join("laser.sfx"), "waveform sawtooth\nslide -2\n").unwrap();
+        fs::write(&manifest, "# name file [priority] [volume]\n\nshoot shoot.wav 2 0.5\nhit shoot.wav\nlaser laser.sfx 1\n").unwrap();
+        sounds.load_bank(&manifest.to_string_lossy()).unwrap();
+        assert!(sounds.contains("shoot") && sounds.contains("hit") && sounds.contains("laser"));
+
+        fs::write(&manifest, "shoot shoot.wav loud\n").unwrap();
+        let error = sounds.load_bank(&manifest.to_string_lossy()).err().unwrap();
+        assert!(matches!(error, Error::Decode { .. }));
+
+        fs::write(&manifest, "missing missing.wav\n").unwrap();
+        let error = sounds.load_bank(&manifest.to_string_lossy()).err().unwrap();
+        assert!(matches!(error, Error::AssetNotFound { .. }));
+        fs::remove_dir_all(&directory).unwrap();
+    }
 }
@@ -0,0 +1,297 @@
+//! Background music: streamed tracks, playlists and crossfades, and a step sequencer playing clips at a tempo.
+//!
+//! The tracks are loaded in a [`SoundManager`] by name and streamed by the mixer, one at a time.
+//! As SDL_mixer streams a single music, a crossfade fades the track playing out during the first half of its duration,
+//! then fades the next one in during the second half.
+//! [`SoundManager::update_music`] is called with the `dt` of the game to go through the fades and the playlists.
+//! No game ships music tracks nor changes of scene yet, so the playlists and the crossfades are only used by the tests.
+//!
+//! A [`Sequencer`] plays short clips of the sound bank one per beat, on the music bus,
+//! at a tempo which can change while it plays.
+use super::{Bus, SoundManager};
+use crate::error::Result;
+
+/// Tracks played one after the other, from the first one again once over if looped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Playlist {
+    tracks: Vec<String>,
+    looped: bool,
+}
+
+impl Playlist {
+    /// A playlist played once.
+    ///
+    /// Panics if there is no track.
+    pub fn new(tracks: &[&str]) -> Playlist {
+        assert!(!tracks.is_empty(), "a playlist must have a track");
+        Playlist {
+            tracks: tracks.iter().map(|track| track.to_string()).collect(),
+            looped: false,
+        }
+    }
+
+    pub fn looped(mut self) -> Playlist {
+        self.looped = true;
+        self
+    }
+}
+
+/// A track waiting for the one playing to fade out.
+struct Pending {
+    name: String,
+    loops: i32,
+    fade_in: f32,
+    delay: f32,
+}
+
+/// Music played by a [`SoundManager`].
+#[derive(Default)]
+pub(super) struct MusicState {
+    playlist: Option<Playlist>,
+    position: usize,
+    current: Option<String>,
+    pending: Option<Pending>,
+}
+
+impl SoundManager {
+    /// Load a music file under a name, replacing the track of the same name.
+    pub fn add_track(&mut self, name: &str, path: &str) -> Result<()> {
+        let track = self.mixer.load_music(path)?;
+        self.tracks.insert(name.to_string(), track);
+        Ok(())
+    }
+
+    /// Play a track in loop, crossfading from the music playing during a duration in seconds.
+    ///
+    /// If there is no track of this name, the music playing goes on and the error is reported.
+    pub fn play_music(&mut self, name: &str, crossfade: f32) {
+        if !self.has_track(name) {
+            return;
+        }
+        self.music.playlist = None;
+        self.crossfade(name, -1, crossfade);
+    }
+
+    /// Play the tracks of a playlist, crossfading from the music playing to the first one.
+    ///
+    /// If a track of the playlist is missing, the music playing goes on and the error is reported.
+    pub fn play_playlist(&mut self, playlist: Playlist, crossfade: f32) {
+        if !playlist.tracks.iter().all(|track| self.has_track(track)) {
+            return;
+        }
+        let first = playlist.tracks[0].clone();
+        self.music.playlist = Some(playlist);
+        self.music.position = 0;
+        self.crossfade(&first, 0, crossfade);
+    }
+
+    /// Fade the music out during a duration in seconds.
+    pub fn stop_music(&mut self, fade_out: f32) {
+        self.music = MusicState::default();
+        self.mixer.fade_out_music(fade_out);
+    }
+
+    /// Name of the track playing, or coming once the one playing has faded out.
+    pub fn current_track(&self) -> Option<&str> {
+        self.music.current.as_deref()
+    }
+
+    /// Go through the crossfades and the playlist during dt.
+    pub fn update_music(&mut self, dt: f32) {
+        if let Some(pending) = &mut self.music.pending {
+            pending.delay -= dt;
+            if pending.delay <= 0. {
+                let pending = self.music.pending.take().unwrap();
+                self.start_track(&pending.name, pending.loops, pending.fade_in);
+            }
+            return;
+        }
+
+        if self.music.current.is_none() || self.mixer.is_music_playing() {
+            return;
+        }
+        let next = match &self.music.playlist {
+            Some(playlist) if self.music.position + 1 < playlist.tracks.len() => self.music.position + 1,
+            Some(playlist) if playlist.looped => 0,
+            _ => {
+                self.music = MusicState::default();
+                return;
+            }
+        };
+        let name = self.music.playlist.as_ref().unwrap().tracks[next].clone();
+        self.music.position = next;
+        self.start_track(&name, 0, 0.);
+    }
+
+    /// Is there a track of this name? The error is reported if there isn't.
+    fn has_track(&self, name: &str) -> bool {
+        let has_track = self.tracks.contains_key(name);
+        if !has_track {
+            eprintln!("unknown track {}, the music is not changed", name);
+        }
+        has_track
+    }
+
+    fn crossfade(&mut self, name: &str, loops: i32, duration: f32) {
+        if self.mixer.is_music_playing() && duration > 0. {
+            self.mixer.fade_out_music(duration / 2.);
+            self.music.current = Some(name.to_string());
+            self.music.pending = Some(Pending { name: name.to_string(), loops, fade_in: duration / 2., delay: duration / 2. });
+        } else {
+            self.music.pending = None;
+            self.start_track(name, loops, duration);
+        }
+    }
+
+    fn start_track(&mut self, name: &str, loops: i32, fade_in: f32) {
+        self.mixer.set_music_volume(self.gain(Bus::Music));
+        self.mixer.play_music(self.tracks[name], loops, fade_in);
+        self.music.current = Some(name.to_string());
+    }
+}
+
+/// Plays clips of a sound bank one per beat in loop, like the march of the invaders.
+pub struct Sequencer {
+    steps: Vec<String>,
+    bpm: f32,
+    position: usize,
+    time: f32,
+    is_playing: bool,
+}
+
+impl Sequencer {
+    /// A sequencer which is stopped until started.
+    ///
+    /// Panics if there is no step.
+    pub fn new(steps: &[&str], bpm: f32) -> Sequencer {
+        assert!(!steps.is_empty(), "a sequencer must have a step");
+        assert!(bpm > 0., "the tempo must be positive");
+        Sequencer {
+            steps: steps.iter().map(|step| step.to_string()).collect(),
+            bpm,
+            position: 0,
+            time: 0.,
+            is_playing: false,
+        }
+    }
+
+    /// Start from the first step, played at once.
+    pub fn start(&mut self) {
+        self.position = 0;
+        self.time = self.beat();
+        self.is_playing = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.is_playing = false;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.is_playing
+    }
+
+    /// Tempo, in beats per minute.
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// Change the tempo, keeping how far the sequencer is toward its next step.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        assert!(bpm > 0., "the tempo must be positive");
+        self.time *= self.bpm / bpm;
+        self.bpm = bpm;
+    }
+
+    /// Index of the next step to play.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn beat(&self) -> f32 {
+        60. / self.bpm
+    }
+
+    /// Play the steps due during dt on the music bus, if the sequencer is playing.
+    pub fn update(&mut self, dt: f32, sounds: &SoundManager) {
+        if !self.is_playing {
+            return;
+        }
+        self.time += dt;
+        while self.time >= self.beat() {
+            self.time -= self.beat();
+            sounds.play_on(&self.steps[self.position], Bus::Music);
+            self.position = (self.position + 1) % self.steps.len();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::NullMixer;
+
+    use super::*;
+
+    const FIRST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    const SECOND: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs");
+    const THIRD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/audio.rs");
+
+    #[test]
+    fn playlists_go_through_their_tracks_after_a_crossfade() {
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let mut sounds = SoundManager::new(Box::new(mixer), 4);
+        sounds.add_track("menu", FIRST).unwrap();
+        sounds.add_track("level_1", SECOND).unwrap();
+        sounds.add_track("level_2", THIRD).unwrap();
+
+        sounds.play_music("menu", 0.);
+        sounds.play_music("level_3", 0.);
+        sounds.play_playlist(Playlist::new(&["level_1", "level_3"]), 0.);
+        assert_eq!(sounds.current_track(), Some("menu"));
+        sounds.play_playlist(Playlist::new(&["level_1", "level_2"]), 1.);
+        assert_eq!(sounds.current_track(), Some("level_1"));
+        sounds.update_music(0.25);
+        assert_eq!(record.borrow().musics, [FIRST]);
+        sounds.update_music(0.25);
+        assert_eq!(record.borrow().music.as_deref(), Some(SECOND));
+
+        // The tracks are over when the mixer stops playing them.
+        record.borrow_mut().music = None;
+        sounds.update_music(0.1);
+        assert_eq!(sounds.current_track(), Some("level_2"));
+        record.borrow_mut().music = None;
+        sounds.update_music(0.1);
+        assert_eq!(sounds.current_track(), None);
+        assert_eq!(record.borrow().musics, [FIRST, SECOND, THIRD]);
+
+        sounds.set_volume(Bus::Music, 0.5);
+        assert_eq!(record.borrow().music_volume, Some(0.5));
+    }
+
+    #[test]
+    fn sequencers_play_a_step_per_beat_on_the_music_bus() {
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let mut sounds = SoundManager::new(Box::new(mixer), 4);
+        sounds.add("low", FIRST, 0, 1.).unwrap();
+        sounds.add("high", SECOND, 0, 1.).unwrap();
+        sounds.set_muted(Bus::Sfx, true);
+
+        let mut march = Sequencer::new(&["low", "high"], 120.);
+        march.update(1., &sounds);
+        assert!(record.borrow().played.is_empty());
+
+        march.start();
+        march.update(0.1, &sounds);
+        march.update(0.5, &sounds);
+        assert_eq!(march.position(), 0);
+
+        // A fifth of the way to the next step at 120 BPM is a fifth of the way at 60 BPM too.
+        march.set_bpm(60.);
+        march.update(0.75, &sounds);
+        assert_eq!(record.borrow().played, [FIRST, SECOND]);
+        march.update(0.1, &sounds);
+        assert_eq!(record.borrow().played, [FIRST, SECOND, FIRST]);
+        assert_eq!(record.borrow().volumes[&0], 1.);
+    }
+}
@@ -0,0 +1,292 @@
+//! Retro sound effects generated from a few parameters, in the spirit of sfxr.
+//!
+//! A [`SynthParams`] describes a sound: its waveform, its envelope, its pitch and how the pitch moves.
+//! [`SynthParams::generate`] renders it as mono samples at [`SAMPLE_RATE`], which are played with
+//! [`Mixer::load_samples`](super::Mixer::load_samples) or saved with [`save_wav`].
+//!
+//! # File format
+//!
+//! The parameters are saved in text files, one parameter per line, the missing ones keeping their default value:
+//!
+//! ```text
+//! # laser
+//! waveform square
+//! frequency 880
+//! slide -2
+//! sustain 0.05
+//! decay 0.15
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored.
+use std::f32::consts::TAU;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::random::Rng;
+
+/// Number of samples per second of the generated sounds.
+pub const SAMPLE_RATE: u32 = 44_100;
+
+/// Lowest frequency of a sound, its slide stopping there.
+pub const MIN_FREQUENCY: f32 = 20.;
+
+/// Shape of one period of the sound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Square,
+    Sawtooth,
+    Sine,
+    Triangle,
+    /// A new random value on each period, for explosions and hits.
+    Noise,
+}
+
+impl Waveform {
+    pub fn name(self) -> &'static str {
+        match self {
+            Waveform::Square => "square",
+            Waveform::Sawtooth => "sawtooth",
+            Waveform::Sine => "sine",
+            Waveform::Triangle => "triangle",
+            Waveform::Noise => "noise",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Waveform> {
+        let waveforms = [Waveform::Square, Waveform::Sawtooth, Waveform::Sine, Waveform::Triangle, Waveform::Noise];
+        waveforms.iter().copied().find(|waveform| waveform.name() == name)
+    }
+}
+
+/// Parameters of a generated sound.
+///
+/// The sound rises during the attack, stays during the sustain, boosted by the punch at its start,
+/// and fades out during the decay, all of them in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SynthParams {
+    pub waveform: Waveform,
+    /// Starting frequency, in Hz.
+    pub frequency: f32,
+    /// Change of the frequency, in octaves per second: negative for a falling pitch.
+    pub slide: f32,
+    /// Part of the frequency the vibrato goes up and down by.
+    pub vibrato_depth: f32,
+    /// Frequency of the vibrato, in Hz.
+    pub vibrato_speed: f32,
+    /// Part of each period a square wave is up.
+    pub duty: f32,
+    pub attack: f32,
+    pub sustain: f32,
+    pub punch: f32,
+    pub decay: f32,
+    /// Volume, from 0 to 1.
+    pub volume: f32,
+}
+
+impl Default for SynthParams {
+    /// A short square blip.
+    fn default() -> SynthParams {
+        SynthParams {
+            waveform: Waveform::Square,
+            frequency: 440.,
+            slide: 0.,
+            vibrato_depth: 0.,
+            vibrato_speed: 0.,
+            duty: 0.5,
+            attack: 0.,
+            sustain: 0.1,
+            punch: 0.,
+            decay: 0.1,
+            volume: 0.5,
+        }
+    }
+}
+
+impl SynthParams {
+    /// Read the parameters from a file.
+    pub fn load(path: &str) -> Result<SynthParams> {
+        let text = fs::read_to_string(path).map_err(|error| Error::asset(path, error))?;
+        SynthParams::parse(&text).map_err(|reason| Error::Decode { path: path.to_string(), reason })
+    }
+
+    /// Read the parameters from their text, telling the invalid line if any.
+    pub fn parse(text: &str) -> std::result::Result<SynthParams, String> {
+        let mut params = SynthParams::default();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let syntax_error = || format!("invalid line {}: {:?}", index + 1, line);
+            let (key, value) = match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                [key, value] => (key, value),
+                _ => return Err(syntax_error()),
+            };
+            if key == "waveform" {
+                params.waveform = Waveform::from_name(value).ok_or_else(syntax_error)?;
+                continue;
+            }
+
+            let value = value.parse::<f32>().map_err(|_| syntax_error())?;
+            match key {
+                "frequency" => params.frequency = value,
+                "slide" => params.slide = value,
+                "vibrato_depth" => params.vibrato_depth = value,
+                "vibrato_speed" => params.vibrato_speed = value,
+                "duty" => params.duty = value,
+                "attack" => params.attack = value,
+                "sustain" => params.sustain = value,
+                "punch" => params.punch = value,
+                "decay" => params.decay = value,
+                "volume" => params.volume = value,
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(params)
+    }
+
+    /// Save the parameters to a file, in the format read by [`SynthParams::load`].
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// The same sound, higher or lower by a number of semitones.
+    pub fn pitched(&self, semitones: f32) -> SynthParams {
+        SynthParams { frequency: self.frequency * 2f32.powf(semitones / 12.), ..self.clone() }
+    }
+
+    /// Duration of the sound, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.attack + self.sustain + self.decay
+    }
+
+    /// Volume of the envelope at a time of the sound.
+    fn envelope(&self, t: f32) -> f32 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.sustain {
+            1. + self.punch * (1. - (t - self.attack) / self.sustain)
+        } else if t < self.duration() {
+            1. - (t - self.attack - self.sustain) / self.decay
+        } else {
+            0.
+        }
+    }
+
+    /// Render the sound as mono samples at [`SAMPLE_RATE`].
+    ///
+    /// The noise is always drawn from the same seed, so the same parameters give the same samples.
+    pub fn generate(&self) -> Vec<i16> {
+        let length = (self.duration() * SAMPLE_RATE as f32).round() as usize;
+        let mut rng = Rng::new(0);
+        let mut noise = rng.range(-1., 1.);
+        let mut phase = 0.;
+
+        (0..length).map(|index| {
+            let t = index as f32 / SAMPLE_RATE as f32;
+            let vibrato = 1. + self.vibrato_depth * (TAU * self.vibrato_speed * t).sin();
+            let frequency = (self.frequency * 2f32.powf(self.slide * t) * vibrato).max(MIN_FREQUENCY);
+            phase += frequency / SAMPLE_RATE as f32;
+            if phase >= 1. {
+                phase -= phase.floor();
+                noise = rng.range(-1., 1.);
+            }
+
+            let wave = match self.waveform {
+                Waveform::Square => if phase < self.duty { 1. } else { -1. },
+                Waveform::Sawtooth => 2. * phase - 1.,
+                Waveform::Sine => (TAU * phase).sin(),
+                Waveform::Triangle => 4. * (phase - 0.5).abs() - 1.,
+                Waveform::Noise => noise,
+            };
+            let sample = (wave * self.envelope(t) * self.volume).clamp(-1., 1.);
+            (sample * i16::MAX as f32) as i16
+        }).collect()
+    }
+}
+
+impl fmt::Display for SynthParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "waveform {}", self.waveform.name())?;
+        writeln!(f, "frequency {}", self.frequency)?;
+        writeln!(f, "slide {}", self.slide)?;
+        writeln!(f, "vibrato_depth {}", self.vibrato_depth)?;
+        writeln!(f, "vibrato_speed {}", self.vibrato_speed)?;
+        writeln!(f, "duty {}", self.duty)?;
+        writeln!(f, "attack {}", self.attack)?;
+        writeln!(f, "sustain {}", self.sustain)?;
+        writeln!(f, "punch {}", self.punch)?;
+        writeln!(f, "decay {}", self.decay)?;
+        writeln!(f, "volume {}", self.volume)
+    }
+}
+
+/// Write mono samples at [`SAMPLE_RATE`] as a 16 bit WAV file.
+pub fn write_wav<W: Write>(mut writer: W, samples: &[i16]) -> io::Result<()> {
+    let data_size = (samples.len() * 2) as u32;
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_size).to_le_bytes())?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?; // PCM
+    writer.write_all(&1u16.to_le_bytes())?; // Mono
+    writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    writer.write_all(&(SAMPLE_RATE * 2).to_le_bytes())?; // Bytes per second
+    writer.write_all(&2u16.to_le_bytes())?; // Bytes per sample
+    writer.write_all(&16u16.to_le_bytes())?; // Bits per sample
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+    writer.flush()
+}
+
+/// Save mono samples at [`SAMPLE_RATE`] to a WAV file.
+pub fn save_wav<P: AsRef<Path>>(path: P, samples: &[i16]) -> io::Result<()> {
+    write_wav(BufWriter::new(File::create(path)?), samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sounds_follow_their_envelope_and_are_deterministic() {
+        let params = SynthParams { waveform: Waveform::Noise, attack: 0.1, sustain: 0.2, decay: 0.2, volume: 1., ..SynthParams::default() };
+        let samples = params.generate();
+        assert_eq!(samples.len(), SAMPLE_RATE as usize / 2);
+        assert_eq!(samples, params.generate());
+
+        let loudest = |range: std::ops::Range<usize>| samples[range].iter().map(|sample| sample.unsigned_abs()).max().unwrap();
+        assert!(loudest(0..441) < loudest(4410..13230));
+        assert!(loudest(21609..22050) < loudest(4410..13230));
+
+        // An octave higher is twice as many periods.
+        let crossings = |params: &SynthParams| params.generate().windows(2).filter(|pair| pair[0] < 0 && pair[1] >= 0).count();
+        let square = SynthParams { sustain: 1., decay: 0., ..SynthParams::default() };
+        let (low, high) = (crossings(&square) as i32, crossings(&square.pitched(12.)) as i32);
+        assert!((low - 440).abs() <= 1);
+        assert!((high - 2 * low).abs() <= 2);
+    }
+
+    #[test]
+    fn params_are_saved_as_text_and_sounds_as_wav() {
+        let params = SynthParams { waveform: Waveform::Triangle, slide: -1.5, punch: 0.25, ..SynthParams::default() };
+        assert_eq!(SynthParams::parse(&params.to_string()), Ok(params.clone()));
+        assert_eq!(SynthParams::parse("# laser\n\nfrequency 880\n").unwrap().frequency, 880.);
+        assert_eq!(SynthParams::parse("frequency high\n"), Err("invalid line 1: \"frequency high\"".to_string()));
+        assert!(SynthParams::parse("waveform organ\n").is_err());
+
+        let mut wav = Vec::new();
+        write_wav(&mut wav, &[0, -1, i16::MAX]).unwrap();
+        assert_eq!(wav.len(), 44 + 6);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[40..44], &6u32.to_le_bytes());
+        assert_eq!(&wav[44..], &[0, 0, 0xff, 0xff, 0xff, 0x7f]);
+    }
+}
@@ -0,0 +1,45 @@
+//! Selection of the backend used for the display and the sound, and the events given to the headless games.
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod};
+
+/// Where the games draw and play their sounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// A real SDL window and audio device.
+    Sdl,
+
+    /// Nothing is shown nor played, draw calls and sounds are only recorded in memory.
+    /// Useful to run the games in tests or on a server.
+    Headless,
+}
+
+impl Backend {
+    /// Select the backend from the command line: `--headless` runs the game without window nor sound.
+    pub fn from_args() -> Backend {
+        if std::env::args().any(|arg| arg == "--headless") {
+            Backend::Headless
+        } else {
+            Backend::Sdl
+        }
+    }
+}
+
+/// A keyboard event, as sent by SDL when a key is pressed.
+pub fn key_down(keycode: Keycode) -> Event {
+    key_event(true, keycode, false)
+}
+
+/// A keyboard event, as sent by SDL when a key is released.
+pub fn key_up(keycode: Keycode) -> Event {
+    key_event(false, keycode, false)
+}
+
+pub(crate) fn key_event(down: bool, keycode: Keycode, repeat: bool) -> Event {
+    let keycode = Some(keycode);
+    let keymod = Mod::NOMOD;
+    if down {
+        Event::KeyDown { timestamp: 0, window_id: 0, keycode, scancode: None, keymod, repeat }
+    } else {
+        Event::KeyUp { timestamp: 0, window_id: 0, keycode, scancode: None, keymod, repeat }
+    }
+}
@@ -0,0 +1,153 @@
+//! Draw commands of a frame, recorded by the renderers and submitted in batches when the frame is presented.
+//!
+//! The commands are sorted by layer, then the consecutive ones sharing the same state are grouped:
+//! the color of the rectangles, the texture of the images. Inside a layer, the commands are drawn
+//! in the order they were given, so the overlapping ones stay on top of each other as expected.
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use crate::assets::Image;
+
+/// A draw of the game, waiting for the end of the frame.
+#[derive(Clone)]
+pub enum Command {
+    FillRect(Rect, Color),
+    Line(Point, Point, Color),
+    Image { image: Image, src: Rect, dst: Rect, angle: f64 },
+}
+
+/// What the commands of a batch share, so they are drawn without changing the state of the renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BatchKey {
+    Rects(u32),
+    /// Images of the same texture, given by the renderer.
+    Images(usize),
+    Lines(u32),
+}
+
+fn color_key(color: Color) -> u32 {
+    u32::from_be_bytes([color.r, color.g, color.b, color.a])
+}
+
+/// Consecutive commands drawn with the same state.
+pub struct Batch {
+    pub key: BatchKey,
+    pub commands: Vec<Command>,
+}
+
+impl Batch {
+    /// Calls to the backend needed to draw the batch: all the rectangles are filled at once.
+    pub fn draw_calls(&self) -> usize {
+        match self.key {
+            BatchKey::Rects(_) => 1,
+            _ => self.commands.len(),
+        }
+    }
+}
+
+/// Commands of the frame, with the layer they were given in.
+#[derive(Default)]
+pub struct CommandQueue {
+    layer: i32,
+    commands: Vec<(i32, Command)>,
+}
+
+impl CommandQueue {
+    pub fn new() -> CommandQueue {
+        CommandQueue::default()
+    }
+
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    /// Layer of the next commands, the higher layers being drawn over the lower ones.
+    pub fn set_layer(&mut self, layer: i32) {
+        self.layer = layer;
+    }
+
+    pub fn push(&mut self, command: Command) {
+        self.commands.push((self.layer, command));
+    }
+
+    /// Forget the commands, and go back to the layer 0.
+    pub fn clear(&mut self) {
+        self.commands.clear();
+        self.layer = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// The images drawn by the commands, with repetitions.
+    pub fn images(&self) -> impl Iterator<Item=&Image> {
+        self.commands.iter().filter_map(|(_, command)| match command {
+            Command::Image { image, .. } => Some(image),
+            _ => None,
+        })
+    }
+
+    /// Take the commands, sorted by layer and grouped in batches, the texture of each image being given by a function.
+    ///
+    /// Inside a layer, the commands are kept in the order they were given, only the consecutive ones being batched.
+    pub fn take_batches<F: FnMut(&Image) -> usize>(&mut self, mut texture: F) -> Vec<Batch> {
+        let mut commands: Vec<(i32, BatchKey, Command)> = std::mem::take(&mut self.commands)
+            .into_iter()
+            .map(|(layer, command)| {
+                let key = match &command {
+                    Command::FillRect(_, color) => BatchKey::Rects(color_key(*color)),
+                    Command::Line(_, _, color) => BatchKey::Lines(color_key(*color)),
+                    Command::Image { image, .. } => BatchKey::Images(texture(image)),
+                };
+                (layer, key, command)
+            })
+            .collect();
+        commands.sort_by_key(|(layer, _, _)| *layer);
+
+        let mut batches: Vec<Batch> = Vec::new();
+        let mut previous_layer = None;
+        for (layer, key, command) in commands {
+            match batches.last_mut() {
+                Some(batch) if batch.key == key && previous_layer == Some(layer) => batch.commands.push(command),
+                _ => batches.push(Batch { key, commands: vec![command] }),
+            }
+            previous_layer = Some(layer);
+        }
+        self.layer = 0;
+        batches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_grouped_by_layer_and_color() {
+        let mut queue = CommandQueue::new();
+        let rect = |x| Rect::new(x, 0, 10, 10);
+        queue.push(Command::FillRect(rect(0), Color::WHITE));
+        queue.push(Command::FillRect(rect(1), Color::WHITE));
+        queue.push(Command::Line(Point::new(0, 0), Point::new(1, 1), Color::WHITE));
+        queue.push(Command::FillRect(rect(2), Color::RED));
+        queue.push(Command::FillRect(rect(3), Color::WHITE));
+        queue.set_layer(-1);
+        queue.push(Command::FillRect(rect(4), Color::WHITE));
+
+        let batches = queue.take_batches(|_| 0);
+        let keys: Vec<BatchKey> = batches.iter().map(|batch| batch.key).collect();
+        let (white, red) = (color_key(Color::WHITE), color_key(Color::RED));
+        assert_eq!(keys, vec![BatchKey::Rects(white), BatchKey::Rects(white), BatchKey::Lines(white), BatchKey::Rects(red), BatchKey::Rects(white)]);
+
+        // The consecutive white rectangles of the layer 0 are filled at once, the last one staying over the red one.
+        assert!(matches!(batches[1].commands[..], [Command::FillRect(first, _), Command::FillRect(second, _)] if first.x() == 0 && second.x() == 1));
+        assert!(matches!(batches[4].commands[..], [Command::FillRect(last, _)] if last.x() == 3));
+        assert_eq!(batches.iter().map(Batch::draw_calls).sum::<usize>(), 5);
+        assert!(queue.is_empty());
+    }
+}
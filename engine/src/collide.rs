@@ -1,4 +1,5 @@
-use crate::geometry::Rect;
+use crate::geometry::{Circle, OrientedRect, Rect};
+use crate::math::Vec2;
 
 pub fn collide(r1: &Rect, r2: &Rect) -> Option<Rect> {
     if r1.x0() < r2.x0() && r2.x0() > r1.x1() {
@@ -28,4 +29,299 @@ pub fn collide(r1: &Rect, r2: &Rect) -> Option<Rect> {
     let y1 = y_array[2];
 
     Some(Rect::from_2_points(x0, y0, x1, y1))
-}
\ No newline at end of file
+}
+
+/// First contact of a moving shape with an obstacle, found by a swept test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Time, from the start of the move, when the shapes touch.
+    pub time: f32,
+
+    /// Normal of the obstacle at the contact, toward the moving shape.
+    pub normal: Vec2,
+}
+
+/// Swept test of a rectangle moving at a velocity during dt against a fixed rectangle.
+///
+/// Unlike [`collide`], a fast rectangle can't go through a thin obstacle between two frames.
+/// Rectangles already overlapping hit at time 0, unless the move gets them apart.
+pub fn sweep_rect(moving: &Rect, vel: Vec2, obstacle: &Rect, dt: f32) -> Option<Hit> {
+    // The top left corner of the moving rectangle against the obstacle grown by its size.
+    let min = obstacle.min() - moving.size();
+    let max = obstacle.max();
+    let p = moving.min();
+    let d = vel * dt;
+
+    let (x_enter, x_exit) = slab(p.x, d.x, min.x, max.x)?;
+    let (y_enter, y_exit) = slab(p.y, d.y, min.y, max.y)?;
+    let enter = x_enter.max(y_enter);
+    let exit = x_exit.min(y_exit);
+    if enter > exit || exit <= 0. || enter > 1. {
+        return None;
+    }
+
+    if enter >= 0. {
+        let normal = if x_enter > y_enter { Vec2::new(-d.x.signum(), 0.) } else { Vec2::new(0., -d.y.signum()) };
+        return Some(Hit { time: enter * dt, normal });
+    }
+
+    // Already overlapping: push out through the closest side.
+    let sides = [
+        (p.x - min.x, Vec2::new(-1., 0.)),
+        (max.x - p.x, Vec2::new(1., 0.)),
+        (p.y - min.y, Vec2::new(0., -1.)),
+        (max.y - p.y, Vec2::new(0., 1.)),
+    ];
+    let (_, normal) = sides.iter().fold(sides[0], |closest, side| if side.0 < closest.0 { *side } else { closest });
+    if d.dot(normal) > 0. {
+        return None;
+    }
+    Some(Hit { time: 0., normal })
+}
+
+/// Swept test of a circle moving at a velocity during dt against a fixed circle.
+///
+/// Circles already overlapping hit at time 0, unless the move gets them apart.
+pub fn sweep_circle(moving: &Circle, vel: Vec2, obstacle: &Circle, dt: f32) -> Option<Hit> {
+    let m = moving.center() - obstacle.center();
+    let d = vel * dt;
+    let r = moving.r() + obstacle.r();
+
+    let a = d.length_squared();
+    let b = 2. * m.dot(d);
+    let c = m.length_squared() - r * r;
+
+    if c < 0. {
+        if b > 0. {
+            return None;
+        }
+        let normal = if m == Vec2::ZERO { Vec2::new(1., 0.) } else { m.normalize() };
+        return Some(Hit { time: 0., normal });
+    }
+
+    let discriminant = b * b - 4. * a * c;
+    if a == 0. || discriminant < 0. {
+        return None;
+    }
+    let t = (-b - discriminant.sqrt()) / (2. * a);
+    if !(0. ..=1.).contains(&t) {
+        return None;
+    }
+
+    Some(Hit { time: t * dt, normal: (m + d * t) / r })
+}
+
+/// Fractions of a move of d from p, along one axis, when it enters and leaves the range [x0, x1].
+fn slab(p: f32, d: f32, x0: f32, x1: f32) -> Option<(f32, f32)> {
+    if d == 0. {
+        return if x0 < p && p < x1 { Some((f32::NEG_INFINITY, f32::INFINITY)) } else { None };
+    }
+    let t0 = (x0 - p) / d;
+    let t1 = (x1 - p) / d;
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+/// The earliest of some hits.
+pub fn first_hit<I: IntoIterator<Item=Hit>>(hits: I) -> Option<Hit> {
+    hits.into_iter().fold(None, |first: Option<Hit>, hit| match first {
+        Some(first) if first.time <= hit.time => Some(first),
+        _ => Some(hit),
+    })
+}
+
+/// Any shape which can be tested for overlap with [`overlap`].
+pub enum Shape {
+    Point(Vec2),
+    Circle(Circle),
+    Rect(Rect),
+    OrientedRect(OrientedRect),
+    /// A convex polygon, given by its vertices in order.
+    Polygon(Vec<Vec2>),
+}
+
+impl From<Circle> for Shape {
+    fn from(circle: Circle) -> Shape {
+        Shape::Circle(circle)
+    }
+}
+
+impl From<Rect> for Shape {
+    fn from(rect: Rect) -> Shape {
+        Shape::Rect(rect)
+    }
+}
+
+impl From<OrientedRect> for Shape {
+    fn from(rect: OrientedRect) -> Shape {
+        Shape::OrientedRect(rect)
+    }
+}
+
+impl Shape {
+    /// The shape as a convex polygon, rounded by a radius: a point or a circle has a single vertex.
+    fn vertices(&self) -> (Vec<Vec2>, f32) {
+        match self {
+            Shape::Point(point) => (vec![*point], 0.),
+            Shape::Circle(circle) => (vec![circle.center()], circle.r()),
+            Shape::Rect(rect) => {
+                let (min, max) = (rect.min(), rect.max());
+                (vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)], 0.)
+            }
+            Shape::OrientedRect(rect) => (rect.corners().to_vec(), 0.),
+            Shape::Polygon(points) => (points.clone(), 0.),
+        }
+    }
+}
+
+/// Overlap of two shapes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// How far the first shape must move along the normal to stop overlapping.
+    pub depth: f32,
+
+    /// Normal of the contact, from the second shape toward the first one.
+    pub normal: Vec2,
+}
+
+/// Test if two shapes overlap, giving the smallest move separating them.
+///
+/// Shapes touching without overlapping give a contact of depth 0.
+pub fn overlap(a: &Shape, b: &Shape) -> Option<Contact> {
+    let (a_vertices, a_radius) = a.vertices();
+    let (b_vertices, b_radius) = b.vertices();
+
+    let mut axes = Vec::new();
+    axes.extend(edge_normals(&a_vertices));
+    axes.extend(edge_normals(&b_vertices));
+    if a_vertices.len() == 1 {
+        axes.extend(closest_axis(a_vertices[0], &b_vertices));
+    }
+    if b_vertices.len() == 1 {
+        axes.extend(closest_axis(b_vertices[0], &a_vertices));
+    }
+    if axes.is_empty() {
+        // Two points, or round shapes with the same center.
+        axes.push(Vec2::new(1., 0.));
+    }
+
+    let mut contact: Option<Contact> = None;
+    for axis in axes {
+        let (a_min, a_max) = project(&a_vertices, a_radius, axis);
+        let (b_min, b_max) = project(&b_vertices, b_radius, axis);
+        let depth = a_max.min(b_max) - a_min.max(b_min);
+        if depth < 0. {
+            return None;
+        }
+        if contact.is_none_or(|contact| depth < contact.depth) {
+            contact = Some(Contact { depth, normal: axis });
+        }
+    }
+
+    let mut contact = contact?;
+    if (center(&a_vertices) - center(&b_vertices)).dot(contact.normal) < 0. {
+        contact.normal = -contact.normal;
+    }
+    Some(contact)
+}
+
+fn edge_normals(vertices: &[Vec2]) -> Vec<Vec2> {
+    if vertices.len() < 2 {
+        return Vec::new();
+    }
+    (0..vertices.len())
+        .map(|i| vertices[(i + 1) % vertices.len()] - vertices[i])
+        .filter(|edge| *edge != Vec2::ZERO)
+        .map(|edge| -edge.perp().normalize())
+        .collect()
+}
+
+/// Axis from a round shape to the closest vertex of the other shape.
+fn closest_axis(center: Vec2, vertices: &[Vec2]) -> Option<Vec2> {
+    let distance = |p: &&Vec2| (**p - center).length_squared();
+    let closest = vertices.iter().min_by(|p, q| distance(p).partial_cmp(&distance(q)).unwrap())?;
+    let axis = *closest - center;
+    if axis == Vec2::ZERO {
+        return None;
+    }
+    Some(axis.normalize())
+}
+
+fn project(vertices: &[Vec2], radius: f32, axis: Vec2) -> (f32, f32) {
+    let dots = vertices.iter().map(|vertex| vertex.dot(axis));
+    let min = dots.clone().fold(f32::INFINITY, f32::min);
+    let max = dots.fold(f32::NEG_INFINITY, f32::max);
+    (min - radius, max + radius)
+}
+
+fn center(vertices: &[Vec2]) -> Vec2 {
+    let sum = vertices.iter().fold(Vec2::ZERO, |sum, vertex| sum + *vertex);
+    sum / vertices.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fast_rect_does_not_go_through_thin_wall() {
+        let ball = Rect::new(0., 0., 0.01, 0.01);
+        let wall = Rect::new(0.5, -1., 0.01, 3.);
+
+        // The ball jumps over the wall in a single step.
+        assert!(collide(&Rect::new(0.9, 0., 0.01, 0.01), &wall).is_none());
+
+        let hit = sweep_rect(&ball, Vec2::new(10., 0.), &wall, 0.1).unwrap();
+        assert!((hit.time - 0.049).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec2::new(-1., 0.));
+
+        // Going away from the wall once touching it.
+        let touching = Rect::new(0.49, 0., 0.01, 0.01);
+        assert!(sweep_rect(&touching, Vec2::new(-10., 0.), &wall, 0.1).is_none());
+    }
+
+    #[test]
+    fn circles_touch_at_the_sum_of_their_radii() {
+        let bullet = Circle::new(0., 0., 0.1);
+        let asteroid = Circle::new(1., 0., 0.2);
+
+        let hit = sweep_circle(&bullet, Vec2::new(2., 0.), &asteroid, 1.).unwrap();
+        assert!((hit.time - 0.35).abs() < 1e-5);
+        assert!(hit.normal.distance(Vec2::new(-1., 0.)) < 1e-5);
+
+        assert!(sweep_circle(&bullet, Vec2::new(0., 2.), &asteroid, 1.).is_none());
+        assert!(sweep_circle(&bullet, Vec2::new(2., 0.), &asteroid, 0.1).is_none());
+    }
+
+    #[test]
+    fn circle_overlaps_give_depth_and_normal() {
+        let small = Shape::from(Circle::new(0., 0., 1.));
+        let big = Shape::from(Circle::new(2.5, 0., 2.));
+        let contact = overlap(&small, &big).unwrap();
+        assert!((contact.depth - 0.5).abs() < 1e-5);
+        assert!((contact.normal.x + 1.).abs() < 1e-5);
+
+        // The corner of the rectangle is out of the circle, even if their bounding boxes overlap.
+        let rect = Shape::from(Rect::new(0.8, 0.8, 1., 1.));
+        assert!(overlap(&small, &rect).is_none());
+
+        let rect = Shape::from(Rect::new(0.5, -1., 1., 2.));
+        let contact = overlap(&small, &rect).unwrap();
+        assert!((contact.depth - 0.5).abs() < 1e-5);
+        assert!((contact.normal.x + 1.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn rotated_rects_and_polygons() {
+        let diamond = Shape::from(OrientedRect::new(0., 0., 2., 2., std::f32::consts::FRAC_PI_4));
+        assert!(overlap(&diamond, &Shape::Point(Vec2::new(0.9, 0.3))).is_some());
+        assert!(overlap(&diamond, &Shape::Point(Vec2::new(0.9, 0.9))).is_none());
+
+        let triangle = Shape::Polygon(vec![Vec2::new(0., 0.), Vec2::new(4., 0.), Vec2::new(0., 4.)]);
+        assert!(overlap(&Shape::Point(Vec2::new(1., 1.)), &triangle).is_some());
+        assert!(overlap(&Shape::Point(Vec2::new(3., 3.)), &triangle).is_none());
+
+        let square = Shape::from(Rect::new(1.2, -0.5, 1., 1.));
+        let contact = overlap(&square, &diamond).unwrap();
+        assert!(contact.normal.x > 0.);
+    }
+}
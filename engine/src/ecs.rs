@@ -0,0 +1,356 @@
+//! A small entity component system.
+//!
+//! An [`Entity`] is only an id. Its data are components, any `'static` type,
+//! kept in one [`Storage`] per type by the [`World`].
+//! Systems are functions updating the components of the world, run in order by [`Systems`].
+//!
+//! Ids are generational: once an entity is despawned its id may be reused,
+//! but the old id never gives access to the components of the new entity.
+//! Despawning is deferred until [`World::maintain`], so entities can be despawned while iterating.
+use std::any::{Any, TypeId};
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+
+/// Id of an entity of a [`World`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+impl Entity {
+    /// Position of the entity in the storages, shared with the entities spawned later in the same slot.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+/// Components of one type, by entity.
+///
+/// A storage can also be used outside a world, to attach data to entities of another module,
+/// like the sprites of the entities of the game logic.
+pub struct Storage<T> {
+    slots: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Storage<T> {
+    pub fn new() -> Storage<T> {
+        Storage { slots: Vec::new() }
+    }
+
+    /// Set the component of an entity, returning the previous one.
+    pub fn insert(&mut self, entity: Entity, component: T) -> Option<T> {
+        let index = entity.index as usize;
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+
+        match self.slots[index].replace((entity.generation, component)) {
+            Some((generation, previous)) if generation == entity.generation => Some(previous),
+            _ => None,
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) -> Option<T> {
+        if !self.contains(entity) {
+            return None;
+        }
+        self.slots[entity.index as usize].take().map(|(_, component)| component)
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.get(entity).is_some()
+    }
+
+    pub fn get(&self, entity: Entity) -> Option<&T> {
+        match self.slots.get(entity.index as usize) {
+            Some(Some((generation, component))) if *generation == entity.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, entity: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(entity.index as usize) {
+            Some(Some((generation, component))) if *generation == entity.generation => Some(component),
+            _ => None,
+        }
+    }
+
+    /// The components with their entity, by increasing entity index.
+    pub fn iter(&self) -> impl Iterator<Item=(Entity, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.as_ref().map(|(generation, component)| (Entity { index: index as u32, generation: *generation }, component))
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=(Entity, &mut T)> {
+        self.slots.iter_mut().enumerate().filter_map(|(index, slot)| {
+            slot.as_mut().map(|(generation, component)| (Entity { index: index as u32, generation: *generation }, component))
+        })
+    }
+
+    /// The entities having a component.
+    pub fn entities(&self) -> Vec<Entity> {
+        self.iter().map(|(entity, _)| entity).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_none())
+    }
+
+    /// Keep only the components for which the predicate is true.
+    pub fn retain<F: FnMut(Entity, &mut T) -> bool>(&mut self, mut keep: F) {
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if let Some((generation, component)) = slot {
+                if !keep(Entity { index: index as u32, generation: *generation }, component) {
+                    *slot = None;
+                }
+            }
+        }
+    }
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Storage<T> {
+        Storage::new()
+    }
+}
+
+/// A storage whose type of component is unknown, so the world can remove the components of despawned entities.
+trait AnyStorage {
+    fn remove_entity(&mut self, entity: Entity);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: 'static> AnyStorage for Storage<T> {
+    fn remove_entity(&mut self, entity: Entity) {
+        self.remove(entity);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// All the entities of a game, with their components.
+///
+/// Each storage is borrowed on its own, so a system can read a type of component while modifying another.
+#[derive(Default)]
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    despawned: RefCell<Vec<Entity>>,
+    storages: HashMap<TypeId, RefCell<Box<dyn AnyStorage>>>,
+}
+
+impl World {
+    pub fn new() -> World {
+        World::default()
+    }
+
+    /// Create an entity without any component.
+    pub fn spawn(&mut self) -> Entity {
+        match self.free.pop() {
+            Some(index) => {
+                self.alive[index as usize] = true;
+                Entity { index, generation: self.generations[index as usize] }
+            }
+            None => {
+                self.generations.push(0);
+                self.alive.push(true);
+                Entity { index: self.generations.len() as u32 - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Is the entity spawned and not yet removed by [`World::maintain`]?
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        index < self.alive.len() && self.alive[index] && self.generations[index] == entity.generation
+    }
+
+    /// Number of entities alive.
+    pub fn len(&self) -> usize {
+        self.alive.iter().filter(|alive| **alive).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Despawn an entity at the next [`World::maintain`], with all its components.
+    ///
+    /// Until then, the entity and its components are still there.
+    pub fn despawn(&self, entity: Entity) {
+        self.despawned.borrow_mut().push(entity);
+    }
+
+    /// Remove the entities despawned since the last call.
+    pub fn maintain(&mut self) {
+        let despawned = std::mem::take(self.despawned.get_mut());
+        for entity in despawned {
+            if !self.is_alive(entity) {
+                continue;
+            }
+            for storage in self.storages.values_mut() {
+                storage.get_mut().remove_entity(entity);
+            }
+            let index = entity.index as usize;
+            self.alive[index] = false;
+            self.generations[index] = self.generations[index].wrapping_add(1);
+            self.free.push(entity.index);
+        }
+    }
+
+    /// Create the storage of a type of component, if it doesn't exist yet.
+    ///
+    /// Components are registered when first inserted,
+    /// but a type must be registered to be queried before any entity has it.
+    pub fn register<T: 'static>(&mut self) {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| RefCell::new(Box::new(Storage::<T>::new())));
+    }
+
+    /// Add a component to a living entity, replacing the one of the same type.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        assert!(self.is_alive(entity), "{:?} is not alive", entity);
+        self.register::<T>();
+        self.storage_mut::<T>().insert(entity, component);
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        self.storages.get_mut(&TypeId::of::<T>())?.get_mut().as_any_mut().downcast_mut::<Storage<T>>()?.remove(entity)
+    }
+
+    /// Borrow the components of a type.
+    ///
+    /// Panics if the type has never been registered, or if its storage is mutably borrowed.
+    pub fn storage<T: 'static>(&self) -> Ref<'_, Storage<T>> {
+        Ref::map(self.cell::<T>().borrow(), |storage| storage.as_any().downcast_ref().unwrap())
+    }
+
+    /// Mutably borrow the components of a type.
+    ///
+    /// Panics if the type has never been registered, or if its storage is already borrowed.
+    pub fn storage_mut<T: 'static>(&self) -> RefMut<'_, Storage<T>> {
+        RefMut::map(self.cell::<T>().borrow_mut(), |storage| storage.as_any_mut().downcast_mut().unwrap())
+    }
+
+    fn cell<T: 'static>(&self) -> &RefCell<Box<dyn AnyStorage>> {
+        self.storages
+            .get(&TypeId::of::<T>())
+            .unwrap_or_else(|| panic!("component {} is not registered", std::any::type_name::<T>()))
+    }
+
+    /// The entities having a component of a type.
+    pub fn entities_with<T: 'static>(&self) -> Vec<Entity> {
+        self.storage::<T>().entities()
+    }
+}
+
+/// A system is any function taking the world and the delta of time.
+pub type System = Box<dyn FnMut(&mut World, f32)>;
+
+/// Systems run in order, each frame.
+#[derive(Default)]
+pub struct Systems {
+    systems: Vec<System>,
+}
+
+impl Systems {
+    pub fn new() -> Systems {
+        Systems::default()
+    }
+
+    pub fn add<S: FnMut(&mut World, f32) + 'static>(&mut self, system: S) {
+        self.systems.push(Box::new(system));
+    }
+
+    /// Run all the systems, then remove the entities they despawned.
+    pub fn run(&mut self, world: &mut World, dt: f32) {
+        for system in &mut self.systems {
+            system(world, dt);
+        }
+        world.maintain();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Health(u32);
+
+    #[test]
+    fn despawn_is_deferred() {
+        let mut world = World::new();
+        let first = world.spawn();
+        let second = world.spawn();
+        world.insert(first, Health(1));
+        world.insert(second, Health(2));
+
+        for (entity, health) in world.storage::<Health>().iter() {
+            if health.0 == 1 {
+                world.despawn(entity);
+            }
+        }
+        assert!(world.is_alive(first));
+        assert_eq!(world.storage::<Health>().len(), 2);
+
+        world.maintain();
+        assert!(!world.is_alive(first));
+        assert_eq!(world.entities_with::<Health>(), vec![second]);
+    }
+
+    #[test]
+    fn reused_ids_dont_see_old_components() {
+        let mut world = World::new();
+        let old = world.spawn();
+        world.insert(old, Health(1));
+        world.despawn(old);
+        world.maintain();
+
+        let new = world.spawn();
+        assert_eq!(new.index(), old.index());
+        assert!(world.storage::<Health>().get(new).is_none());
+
+        world.insert(new, Health(2));
+        assert!(world.storage::<Health>().get(old).is_none());
+        assert_eq!(world.storage::<Health>().get(new).map(|health| health.0), Some(2));
+    }
+
+    #[test]
+    fn systems_run_in_order() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        world.insert(entity, Health(10));
+
+        let mut systems = Systems::new();
+        systems.add(|world: &mut World, _dt| {
+            for (_, health) in world.storage_mut::<Health>().iter_mut() {
+                health.0 -= 10;
+            }
+        });
+        systems.add(|world: &mut World, _dt| {
+            for (entity, health) in world.storage::<Health>().iter() {
+                if health.0 == 0 {
+                    world.despawn(entity);
+                }
+            }
+        });
+        systems.run(&mut world, 0.1);
+
+        assert!(world.is_empty());
+    }
+}
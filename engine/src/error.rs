@@ -0,0 +1,87 @@
+//! Errors reported by the engine.
+//!
+//! Every constructor which can fail (opening the window, the audio device, loading an asset...)
+//! returns a [`Result`], so a game can report what went wrong instead of panicking.
+use std::fmt;
+use std::path::Path;
+
+use sdl2::ttf::InitError;
+
+use crate::replay::ReplayError;
+
+/// Everything that can go wrong while starting a game.
+#[derive(Debug)]
+pub enum Error {
+    /// An asset file doesn't exist.
+    AssetNotFound { path: String },
+
+    /// An asset file exists, but can't be read: wrong format, corrupted...
+    Decode { path: String, reason: String },
+
+    /// A text can't be rendered with a loaded font.
+    Render { text: String, reason: String },
+
+    /// SDL, its video subsystem or the window can't be initialized.
+    VideoInit(String),
+
+    /// The audio device can't be opened.
+    AudioInit(String),
+
+    /// The font library can't be initialized.
+    FontInit(String),
+
+    /// A recording can't be read or written.
+    Replay(ReplayError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Error for an asset which can't be loaded, telling apart a missing file from a bad one.
+    pub(crate) fn asset(path: &str, reason: impl fmt::Display) -> Error {
+        if Path::new(path).exists() {
+            Error::Decode { path: path.to_string(), reason: reason.to_string() }
+        } else {
+            Error::AssetNotFound { path: path.to_string() }
+        }
+    }
+
+    pub(crate) fn video(reason: impl fmt::Display) -> Error {
+        Error::VideoInit(reason.to_string())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::AssetNotFound { path } => write!(f, "asset not found: {}", path),
+            Error::Decode { path, reason } => write!(f, "can't decode {}: {}", path, reason),
+            Error::Render { text, reason } => write!(f, "can't render {:?}: {}", text, reason),
+            Error::VideoInit(reason) => write!(f, "can't initialize the video: {}", reason),
+            Error::AudioInit(reason) => write!(f, "can't open the audio device: {}", reason),
+            Error::FontInit(reason) => write!(f, "can't initialize the fonts: {}", reason),
+            Error::Replay(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Replay(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<InitError> for Error {
+    fn from(error: InitError) -> Error {
+        Error::FontInit(error.to_string())
+    }
+}
+
+impl From<ReplayError> for Error {
+    fn from(error: ReplayError) -> Error {
+        Error::Replay(error)
+    }
+}
@@ -0,0 +1,297 @@
+//! Fixed timestep game loop shared by all the games.
+//!
+//! The simulation always advances of the same `dt`, whatever the speed of the machine.
+//! Real elapsed time is accumulated and consumed by steps of [`FIXED_DT`].
+//! The remaining fraction of a step is given to the rendering as an interpolation alpha.
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sdl2::event::Event;
+
+use crate::error::Result;
+use crate::graphics::Window;
+use crate::math::Vec2;
+use crate::replay::{Input, Recorder, ReplayError};
+
+/// Duration of one simulation step, in seconds.
+pub const FIXED_DT: f32 = 1. / 120.;
+
+/// Longest frame duration taken into account, in seconds.
+///
+/// If a frame takes longer (window dragged, breakpoint...), the extra time is dropped
+/// instead of running hundreds of steps to catch up.
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+/// Hooks called by the game loop.
+///
+/// A game is usually a small struct gathering its logic, audio and graphics parts.
+pub trait Game {
+    /// Called for each event polled during a frame.
+    fn handle_event(&mut self, event: Event);
+
+    /// Advance the game logic of a fixed delta of time.
+    fn update(&mut self, dt: f32);
+
+    /// Check and resolve the collisions, right after each update.
+    fn collide(&mut self, dt: f32);
+
+    /// Draw the game.
+    ///
+    /// `alpha` is the fraction of a step not yet simulated, between 0 and 1.
+    /// It is used to [`interpolate`] the fast elements between the previous and the current state.
+    fn render(&mut self, window: &mut Window, alpha: f32);
+
+    /// Is the game over? The loop stops as soon as it is.
+    fn is_over(&self) -> bool;
+}
+
+/// Position where to draw an element, between its positions before and after the last step.
+///
+/// An element moving farther than `max_step` in a step has jumped, like a ball thrown again, and is drawn where it is.
+pub fn interpolate(previous: Vec2, current: Vec2, alpha: f32, max_step: f32) -> Vec2 {
+    if (current - previous).length() > max_step {
+        return current;
+    }
+    previous.lerp(current, alpha)
+}
+
+/// Accumulate the elapsed time and count how many fixed steps must be simulated.
+pub struct Clock {
+    step: f32,
+    max_frame_time: f32,
+    accumulator: f32,
+}
+
+impl Clock {
+    pub fn new(step: f32, max_frame_time: f32) -> Clock {
+        Clock {
+            step,
+            max_frame_time,
+            accumulator: 0.,
+        }
+    }
+
+    /// Add the duration of the last frame, clamped to the max frame time.
+    pub fn advance(&mut self, frame_time: f32) {
+        self.accumulator += frame_time.min(self.max_frame_time).max(0.);
+    }
+
+    /// Consume one step if enough time has been accumulated.
+    pub fn consume_step(&mut self) -> bool {
+        if self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            return true;
+        }
+        false
+    }
+
+    /// Fraction of a step remaining in the accumulator.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Clock {
+        Clock::new(FIXED_DT, MAX_FRAME_TIME)
+    }
+}
+
+/// Run the game until it is over.
+///
+/// Each frame, the events are given to the game,
+/// then as many fixed steps as needed are simulated and the game is rendered.
+///
+/// When recording, each frame is written to the recording, which is flushed once the game is over.
+/// If it can't be written, the recording stops but the game goes on, the error being returned at the end.
+/// When replaying, the frames are read from the recording instead of the real time and the window events.
+/// A replay in a headless window is run as fast as possible.
+pub fn run<G: Game>(game: &mut G, window: &mut Window, mut input: Input) -> Result<()> {
+    let record_error = run_frames(game, window, &mut input);
+    if let Some(error) = record_error {
+        return Err(ReplayError::Io(error).into());
+    }
+    if let Input::Record(recorder) = &mut input {
+        recorder.flush().map_err(ReplayError::Io)?;
+    }
+    Ok(())
+}
+
+/// Record a frame and its events.
+fn record(recorder: &mut Recorder, frame_time: f32, events: &[Event]) -> io::Result<()> {
+    recorder.frame(frame_time)?;
+    for event in events {
+        recorder.event(event)?;
+    }
+    Ok(())
+}
+
+/// Run the frames until the game is over, giving the first error writing the recording, if any.
+fn run_frames<G: Game>(game: &mut G, window: &mut Window, input: &mut Input) -> Option<io::Error> {
+    let mut clock = Clock::default();
+    let mut record_error = None;
+    let mut previous = Instant::now();
+
+    while !game.is_over() {
+        let next = Instant::now();
+        let mut frame_time = next.duration_since(previous).as_secs_f32();
+        previous = next;
+
+        let mut events: Vec<Event> = match &mut window.event_pump {
+            Some(event_pump) => event_pump.poll_iter().collect(),
+            None => Vec::new(),
+        };
+
+        match input {
+            Input::Live => {}
+            Input::Record(recorder) => {
+                if let Err(error) = record(recorder, frame_time, &events) {
+                    record_error = Some(error);
+                    *input = Input::Live;
+                }
+            }
+            Input::Replay(replay) => {
+                if events.iter().any(|event| matches!(event, Event::Quit { .. })) {
+                    return record_error;
+                }
+                let frame = match replay.next_frame() {
+                    Some(frame) => frame,
+                    None => return record_error,
+                };
+                if window.event_pump.is_some() {
+                    let elapsed = previous.elapsed().as_secs_f32();
+                    if frame.dt > elapsed {
+                        thread::sleep(Duration::from_secs_f32(frame.dt - elapsed));
+                    }
+                }
+                frame_time = frame.dt;
+                events = frame.events;
+            }
+        }
+
+        for event in events {
+            window.handle_event(&event);
+            game.handle_event(event);
+        }
+
+        clock.advance(frame_time);
+        while clock.consume_step() {
+            game.update(clock.step());
+            game.collide(clock.step());
+            if game.is_over() {
+                return record_error;
+            }
+        }
+
+        game.render(window, clock.alpha());
+    }
+    record_error
+}
+
+/// Run the game as fast as possible, without waiting for real time nor polling events.
+///
+/// At most `max_steps` fixed steps are simulated, the game being rendered after each of them.
+/// It returns the number of steps simulated, less than `max_steps` if the game is over before.
+pub fn simulate<G: Game>(game: &mut G, window: &mut Window, max_steps: u32) -> u32 {
+    let clock = Clock::default();
+    for step in 0..max_steps {
+        if game.is_over() {
+            return step;
+        }
+        game.update(clock.step());
+        game.collide(clock.step());
+        game.render(window, 0.);
+    }
+    max_steps
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::error::Error;
+
+    use super::*;
+
+    /// A disk full after a few bytes.
+    struct FullDisk(usize);
+
+    impl Write for FullDisk {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.0 < buf.len() {
+                return Err(io::Error::other("disk full"));
+            }
+            self.0 -= buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A game over after a number of steps.
+    struct Steps(u32);
+
+    impl Game for Steps {
+        fn handle_event(&mut self, _event: Event) {}
+
+        fn update(&mut self, _dt: f32) {
+            self.0 -= 1;
+        }
+
+        fn collide(&mut self, _dt: f32) {}
+
+        fn render(&mut self, _window: &mut Window, _alpha: f32) {}
+
+        fn is_over(&self) -> bool {
+            self.0 == 0
+        }
+    }
+
+    #[test]
+    fn clock_consumes_whole_steps() {
+        let mut clock = Clock::new(0.1, 1.);
+        clock.advance(0.35);
+
+        let mut steps = 0;
+        while clock.consume_step() {
+            steps += 1;
+        }
+        assert_eq!(steps, 3);
+        assert!((clock.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn clock_clamps_frame_spikes() {
+        let mut clock = Clock::new(0.1, 0.25);
+        clock.advance(10.);
+
+        let mut steps = 0;
+        while clock.consume_step() {
+            steps += 1;
+        }
+        assert_eq!(steps, 2);
+    }
+
+    #[test]
+    fn jumps_are_not_interpolated() {
+        let previous = Vec2::new(0.5, 0.5);
+        assert_eq!(interpolate(previous, Vec2::new(0.75, 0.5), 0.5, 0.5), Vec2::new(0.625, 0.5));
+        assert_eq!(interpolate(previous, Vec2::new(1.5, 0.5), 0.5, 0.5), Vec2::new(1.5, 0.5));
+    }
+
+    #[test]
+    fn recording_errors_dont_stop_the_game() {
+        let recorder = Recorder::new(Box::new(FullDisk(32)), 1).unwrap();
+        let mut game = Steps(3);
+        let result = run(&mut game, &mut Window::headless(10, 10), Input::Record(recorder));
+        assert!(game.is_over());
+        assert!(matches!(result, Err(Error::Replay(ReplayError::Io(_)))));
+    }
+}
@@ -1,75 +1,95 @@
+use crate::math::Vec2;
+
+/// An axis aligned rectangle, from its top left corner.
+///
+/// The `f32` accessors are kept beside the vector ones for the existing code.
 pub struct Rect {
-    x: f32,
-    y: f32,
-    w: f32,
-    h: f32,
+    min: Vec2,
+    size: Vec2,
 }
 
 impl Rect {
     pub fn new(x: f32, y: f32, w: f32, h: f32) -> Rect {
+        Rect::from_min_size(Vec2::new(x, y), Vec2::new(w, h))
+    }
+
+    pub fn from_min_size(min: Vec2, size: Vec2) -> Rect {
         Rect {
-            x,
-            y,
-            w,
-            h,
+            min,
+            size,
         }
     }
 
     pub fn from_2_points(x0: f32, y0: f32, x1: f32, y1: f32) -> Rect {
-        Rect {
-            x: x0,
-            y: y0,
-            w: x1 - x0,
-            h: y1 - y0,
-        }
+        Rect::new(x0, y0, x1 - x0, y1 - y0)
     }
 
     pub fn default() -> Rect {
-        Rect {
-            x: 0.,
-            y: 0.,
-            w: 1.,
-            h: 1.,
-        }
+        Rect::new(0., 0., 1., 1.)
+    }
+
+    /// Top left corner.
+    pub fn min(&self) -> Vec2 {
+        self.min
+    }
+
+    /// Bottom right corner.
+    pub fn max(&self) -> Vec2 {
+        self.min + self.size
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.min + self.size / 2.
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size
     }
 
     pub fn x0(&self) -> f32 {
-        self.x
+        self.min.x
     }
 
     pub fn x1(&self) -> f32 {
-        self.x + self.w
+        self.max().x
     }
 
     pub fn xc(&self) -> f32 {
-        (self.x0() + self.x1()) / 2.
+        self.center().x
     }
 
     pub fn y0(&self) -> f32 {
-        self.y
+        self.min.y
     }
 
     pub fn y1(&self) -> f32 {
-        self.y + self.h
+        self.max().y
     }
 
     pub fn yc(&self) -> f32 {
-        (self.y0() + self.y1()) / 2.
+        self.center().y
     }
 
 
     pub fn w(&self) -> f32 {
-        self.w
+        self.size.x
     }
     pub fn h(&self) -> f32 {
-        self.h
+        self.size.y
     }
 
     pub fn set_w(&mut self, w: f32) {
-        self.w = w;
+        self.size.x = w;
     }
     pub fn set_h(&mut self, h: f32) {
-        self.h = h;
+        self.size.y = h;
+    }
+
+    /// The rectangle, with its copies on the other sides of a wrapping area when it goes over its edges.
+    pub fn wrapped(&self, area: &Rect) -> Vec<Rect> {
+        wrap_offsets(self.min(), self.max(), area)
+            .map(|offset| Rect::from_min_size(self.min + offset, self.size))
+            .collect()
     }
 }
 
@@ -77,27 +97,39 @@ pub trait AsRect {
     fn as_rect(&self) -> Rect;
 }
 
+impl AsRect for Rect {
+    fn as_rect(&self) -> Rect {
+        Rect::from_min_size(self.min, self.size)
+    }
+}
+
 pub struct Circle {
-    x: f32,
-    y: f32,
+    center: Vec2,
     r: f32,
 }
 
 impl Circle {
     pub fn new(x: f32, y: f32, r: f32) -> Circle {
+        Circle::from_center(Vec2::new(x, y), r)
+    }
+
+    pub fn from_center(center: Vec2, r: f32) -> Circle {
         Circle {
-            x,
-            y,
+            center,
             r,
         }
     }
 
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
     pub fn x(&self) -> f32 {
-        self.x
+        self.center.x
     }
 
     pub fn y(&self) -> f32 {
-        self.y
+        self.center.y
     }
 
     pub fn r(&self) -> f32 {
@@ -107,8 +139,91 @@ impl Circle {
     pub fn set_radius(&mut self, r: f32) {
         self.r = r;
     }
+
+    /// The circle, with its copies on the other sides of a wrapping area when it goes over its edges.
+    pub fn wrapped(&self, area: &Rect) -> Vec<Circle> {
+        let radius = Vec2::new(self.r, self.r);
+        wrap_offsets(self.center - radius, self.center + radius, area)
+            .map(|offset| Circle::from_center(self.center + offset, self.r))
+            .collect()
+    }
+}
+
+/// Shifts of the copies of a shape from min to max in a wrapping area, starting by the shape itself.
+pub(crate) fn wrap_offsets(min: Vec2, max: Vec2, area: &Rect) -> impl Iterator<Item=Vec2> {
+    let shifts = |p0: f32, p1: f32, a0: f32, a1: f32| {
+        let mut shifts = vec![0.];
+        if p0 < a0 {
+            shifts.push(a1 - a0);
+        }
+        if p1 > a1 {
+            shifts.push(a0 - a1);
+        }
+        shifts
+    };
+    let dxs = shifts(min.x, max.x, area.x0(), area.x1());
+    let dys = shifts(min.y, max.y, area.y0(), area.y1());
+    dys.into_iter().flat_map(move |dy| dxs.clone().into_iter().map(move |dx| Vec2::new(dx, dy)))
 }
 
 pub trait AsCircle {
     fn as_circle(&self) -> Circle;
-}
\ No newline at end of file
+}
+
+impl AsCircle for Circle {
+    fn as_circle(&self) -> Circle {
+        Circle::from_center(self.center, self.r)
+    }
+}
+
+/// A rectangle rotated around its center, the angle being in radians.
+pub struct OrientedRect {
+    center: Vec2,
+    size: Vec2,
+    angle: f32,
+}
+
+impl OrientedRect {
+    pub fn new(xc: f32, yc: f32, w: f32, h: f32, angle: f32) -> OrientedRect {
+        OrientedRect::from_center(Vec2::new(xc, yc), Vec2::new(w, h), angle)
+    }
+
+    pub fn from_center(center: Vec2, size: Vec2, angle: f32) -> OrientedRect {
+        OrientedRect {
+            center,
+            size,
+            angle,
+        }
+    }
+
+    pub fn center(&self) -> Vec2 {
+        self.center
+    }
+
+    pub fn xc(&self) -> f32 {
+        self.center.x
+    }
+
+    pub fn yc(&self) -> f32 {
+        self.center.y
+    }
+
+    pub fn w(&self) -> f32 {
+        self.size.x
+    }
+
+    pub fn h(&self) -> f32 {
+        self.size.y
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.angle
+    }
+
+    /// The four corners, turning around the rectangle.
+    pub fn corners(&self) -> [Vec2; 4] {
+        let half = self.size / 2.;
+        let corner = |x: f32, y: f32| self.center + Vec2::new(x, y).rotate(self.angle);
+        [corner(-half.x, -half.y), corner(half.x, -half.y), corner(half.x, half.y), corner(-half.x, half.y)]
+    }
+}
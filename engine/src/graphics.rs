@@ -1,130 +1,764 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
 use sdl2::EventPump;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use sdl2::rect::{Point, Rect};
-use sdl2::render::WindowCanvas;
-use sdl2::surface::Surface;
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::render::{BlendMode, TextureCreator, WindowCanvas};
+use sdl2::video::{FullscreenType, WindowContext};
 
+use crate::assets::{Assets, FontId, Image};
+use crate::atlas::TextureAtlas;
+use crate::batch::{Batch, Command, CommandQueue};
+use crate::backend::Backend;
+use crate::error::{Error, Result};
 use crate::geometry;
+use crate::math::Vec2;
+use crate::random::Rng;
+
+/// A rectangle, with its copies on the other sides of the bounds when it goes over their edges.
+///
+/// The copies follow the same rule as the wrapped shapes of the physics, see [`geometry::Rect::wrapped`].
+fn wrapped_rects(rect: Rect, bounds: Rect) -> Vec<Rect> {
+    let corner = |x: i32, y: i32| Vec2::new(x as f32, y as f32);
+    let area = geometry::Rect::from_2_points(bounds.left() as f32, bounds.top() as f32, bounds.right() as f32, bounds.bottom() as f32);
+    geometry::wrap_offsets(corner(rect.left(), rect.top()), corner(rect.right(), rect.bottom()), &area)
+        .map(|offset| Rect::new(rect.x() + offset.x as i32, rect.y() + offset.y as i32, rect.width(), rect.height()))
+        .collect()
+}
+
+/// Layer of the black bars drawn by [`Camera::draw_letterbox`], over the game.
+pub const LETTERBOX_LAYER: i32 = i32::MAX - 1;
+
+/// Layer of the statistics drawn by the renderer, over everything else.
+pub const OVERLAY_LAYER: i32 = i32::MAX;
+
+/// What it took to draw a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    /// Draws asked by the game.
+    pub commands: usize,
+
+    /// Groups of draws sharing the same color or texture.
+    pub batches: usize,
+
+    /// Calls made to the backend to draw the batches.
+    pub draw_calls: usize,
+
+    /// Textures uploaded because new images were drawn.
+    pub texture_uploads: usize,
+
+    /// Time since the previous frame was presented.
+    pub frame_time: Duration,
+}
+
+/// Digits of 3x5 pixels, row by row from the top, so the statistics can be drawn without a font.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// Size of the pixels of the digits, in pixels of the window.
+const STATS_PIXEL: i32 = 2;
+
+fn draw_number(canvas: &mut dyn Renderer, x: i32, y: i32, value: usize, color: Color) {
+    for (i, digit) in value.to_string().bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..3 {
+                if bits >> (2 - col) & 1 == 1 {
+                    let px = x + (i as i32 * 4 + col) * STATS_PIXEL;
+                    let py = y + row as i32 * STATS_PIXEL;
+                    canvas.fill_rect(Rect::new(px, py, STATS_PIXEL as u32, STATS_PIXEL as u32), color);
+                }
+            }
+        }
+    }
+}
+
+/// Draw statistics of a frame in the top left corner, over everything else:
+/// the frame time in milliseconds in yellow, the draw calls in green and the batches in cyan.
+pub fn draw_stats(canvas: &mut dyn Renderer, stats: &FrameStats) {
+    let layer = canvas.layer();
+    canvas.set_layer(OVERLAY_LAYER);
+
+    let rows = [
+        (stats.frame_time.as_millis() as usize, Color::YELLOW),
+        (stats.draw_calls, Color::GREEN),
+        (stats.batches, Color::CYAN),
+    ];
+    let line_height = 7 * STATS_PIXEL;
+    canvas.fill_rect(Rect::new(0, 0, (24 * STATS_PIXEL) as u32, (rows.len() as i32 * line_height + STATS_PIXEL) as u32), Color::RGBA(0, 0, 0, 160));
+    for (i, (value, color)) in rows.iter().enumerate() {
+        let y = STATS_PIXEL * 2 + i as i32 * line_height;
+        canvas.fill_rect(Rect::new(STATS_PIXEL, y + STATS_PIXEL, (STATS_PIXEL * 3) as u32, (STATS_PIXEL * 3) as u32), *color);
+        draw_number(canvas, STATS_PIXEL * 6, y, *value, *color);
+    }
 
+    canvas.set_layer(layer);
+}
+
+/// Drawing primitives used by the sprites.
+///
+/// It is implemented by [`SdlRenderer`] to draw in a real window,
+/// and by [`NullRenderer`] to run the games without any display.
+///
+/// The draws are kept until the frame is presented, then sorted by layer,
+/// the consecutive draws of a layer sharing the same color or texture being grouped.
+/// Inside a layer, the draws are done in the order they were asked.
+pub trait Renderer {
+    /// Size of the drawing area, in pixels.
+    fn output_size(&self) -> (u32, u32);
+
+    /// Fill the whole drawing area with a color.
+    fn clear(&mut self, color: Color);
+
+    fn fill_rect(&mut self, rect: Rect, color: Color);
+
+    fn draw_line(&mut self, start: Point, end: Point, color: Color);
+
+    /// Copy a part of an image to the drawing area, rotated of `angle` degrees around its center.
+    fn copy_image(&mut self, image: &Image, src: Rect, dst: Rect, angle: f64);
+
+    /// Show what has been drawn since the last clear.
+    fn present(&mut self);
+
+    /// Go to or leave the fullscreen, staying in the current mode if the window cannot change.
+    fn set_fullscreen(&mut self, fullscreen: bool);
+
+    fn is_fullscreen(&self) -> bool;
+
+    /// Layer of the next draws, the higher layers being drawn over the lower ones. It is 0 after a clear.
+    fn set_layer(&mut self, layer: i32);
+
+    fn layer(&self) -> i32;
+
+    /// Statistics of the last presented frame.
+    fn stats(&self) -> FrameStats;
+
+    /// Show the statistics of the previous frame over each frame, see [`draw_stats`].
+    fn set_stats_overlay(&mut self, shown: bool);
+
+    fn has_stats_overlay(&self) -> bool;
+}
+
+/// Renderer drawing in a SDL window.
+///
+/// The images are copied in a texture atlas the first time they are drawn,
+/// and taken out of it once all the clones of the image are dropped.
+/// The rectangles of the same color are filled in a single call.
+pub struct SdlRenderer {
+    canvas: WindowCanvas,
+    texture_creator: TextureCreator<WindowContext>,
+    atlas: TextureAtlas,
+    queue: CommandQueue,
+    clear_color: Color,
+    stats: FrameStats,
+    stats_overlay: bool,
+    last_present: Instant,
+}
+
+impl SdlRenderer {
+    pub fn new(mut canvas: WindowCanvas) -> SdlRenderer {
+        canvas.set_blend_mode(BlendMode::Blend);
+        let texture_creator = canvas.texture_creator();
+        SdlRenderer {
+            canvas,
+            texture_creator,
+            atlas: TextureAtlas::new(),
+            queue: CommandQueue::new(),
+            clear_color: Color::BLACK,
+            stats: FrameStats::default(),
+            stats_overlay: false,
+            last_present: Instant::now(),
+        }
+    }
+
+    fn submit(&mut self, batch: &Batch) {
+        let mut rects = Vec::new();
+        for command in &batch.commands {
+            match command {
+                Command::FillRect(rect, color) => {
+                    self.canvas.set_draw_color(*color);
+                    rects.push(*rect);
+                }
+                Command::Line(start, end, color) => {
+                    self.canvas.set_draw_color(*color);
+                    self.canvas.draw_line(*start, *end).unwrap();
+                }
+                Command::Image { image, src, dst, angle } => {
+                    let (page, place) = self.atlas.place(image).unwrap();
+                    self.canvas.copy_ex(
+                        self.atlas.texture(page),
+                        Rect::new(place.x() + src.x(), place.y() + src.y(), src.width(), src.height()),
+                        *dst,
+                        *angle,
+                        Point::new((dst.width() / 2) as i32, (dst.height() / 2) as i32),
+                        false,
+                        false,
+                    ).unwrap();
+                }
+            }
+        }
+        if !rects.is_empty() {
+            self.canvas.fill_rects(&rects).unwrap();
+        }
+    }
+}
+
+impl Renderer for SdlRenderer {
+    fn output_size(&self) -> (u32, u32) {
+        self.canvas.output_size().unwrap()
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.clear_color = color;
+        self.queue.clear();
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.queue.push(Command::FillRect(rect, color));
+    }
+
+    fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+        self.queue.push(Command::Line(start, end, color));
+    }
+
+    fn copy_image(&mut self, image: &Image, src: Rect, dst: Rect, angle: f64) {
+        self.queue.push(Command::Image { image: image.clone(), src, dst, angle });
+    }
+
+    fn present(&mut self) {
+        let now = Instant::now();
+        let frame_time = now - self.last_present;
+        self.last_present = now;
+        if self.stats_overlay {
+            let stats = self.stats;
+            draw_stats(self, &stats);
+        }
+
+        // All the images are put in the atlas before any of them is located, as adding one can move the others.
+        for image in self.queue.images() {
+            self.atlas.locate(image);
+        }
+        let atlas = &self.atlas;
+        let batches = self.queue.take_batches(|image| atlas.place(image).unwrap().0);
+        let texture_uploads = self.atlas.upload(&self.texture_creator);
+
+        self.canvas.set_draw_color(self.clear_color);
+        self.canvas.clear();
+        let mut stats = FrameStats { batches: batches.len(), texture_uploads, frame_time, ..FrameStats::default() };
+        for batch in &batches {
+            stats.commands += batch.commands.len();
+            stats.draw_calls += batch.draw_calls();
+            self.submit(batch);
+        }
+        self.stats = stats;
+        self.canvas.present();
+        self.atlas.drop_unused();
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        let state = if fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+        if let Err(error) = self.canvas.window_mut().set_fullscreen(state) {
+            eprintln!("{}, keeping the window as it is", error);
+        }
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.canvas.window().fullscreen_state() != FullscreenType::Off
+    }
+
+    fn set_layer(&mut self, layer: i32) {
+        self.queue.set_layer(layer);
+    }
+
+    fn layer(&self) -> i32 {
+        self.queue.layer()
+    }
+
+    fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    fn set_stats_overlay(&mut self, shown: bool) {
+        self.stats_overlay = shown;
+    }
+
+    fn has_stats_overlay(&self) -> bool {
+        self.stats_overlay
+    }
+}
+
+/// A draw call recorded by the [`NullRenderer`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCall {
+    Clear(Color),
+    FillRect(Rect, Color),
+    Line(Point, Point, Color),
+    Image { src: Rect, dst: Rect, angle: f64 },
+}
+
+/// What has been drawn by a [`NullRenderer`].
+#[derive(Debug, Default)]
+pub struct DrawRecord {
+    /// Number of frames presented.
+    pub frame_count: u32,
+
+    /// Draw calls of the last presented frame, in the order a real renderer submits them.
+    pub calls: Vec<DrawCall>,
+}
+
+/// Renderer without any display, recording the draw calls in memory.
+///
+/// Only the last presented frame is kept, so a long game does not eat all the memory.
+/// The frame time of its statistics is always zero, so the games run the same way each time.
+pub struct NullRenderer {
+    width: u32,
+    height: u32,
+    fullscreen: bool,
+    queue: CommandQueue,
+    clear_color: Color,
+    stats: FrameStats,
+    stats_overlay: bool,
+    record: Rc<RefCell<DrawRecord>>,
+}
+
+impl NullRenderer {
+    pub fn new(width: u32, height: u32) -> NullRenderer {
+        NullRenderer {
+            width,
+            height,
+            fullscreen: false,
+            queue: CommandQueue::new(),
+            clear_color: Color::BLACK,
+            stats: FrameStats::default(),
+            stats_overlay: false,
+            record: Rc::new(RefCell::new(DrawRecord::default())),
+        }
+    }
+
+    /// Shared access to the recorded draw calls, still valid once the renderer is moved into a window.
+    pub fn record(&self) -> Rc<RefCell<DrawRecord>> {
+        Rc::clone(&self.record)
+    }
+}
+
+impl Renderer for NullRenderer {
+    fn output_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.clear_color = color;
+        self.queue.clear();
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) {
+        self.queue.push(Command::FillRect(rect, color));
+    }
+
+    fn draw_line(&mut self, start: Point, end: Point, color: Color) {
+        self.queue.push(Command::Line(start, end, color));
+    }
+
+    fn copy_image(&mut self, image: &Image, src: Rect, dst: Rect, angle: f64) {
+        self.queue.push(Command::Image { image: image.clone(), src, dst, angle });
+    }
+
+    fn present(&mut self) {
+        if self.stats_overlay {
+            let stats = self.stats;
+            draw_stats(self, &stats);
+        }
+
+        // Each image has a texture of its own.
+        let batches = self.queue.take_batches(|image| image.key());
+        let mut stats = FrameStats { batches: batches.len(), ..FrameStats::default() };
+        let mut calls = vec![DrawCall::Clear(self.clear_color)];
+        for batch in batches {
+            stats.commands += batch.commands.len();
+            stats.draw_calls += batch.draw_calls();
+            calls.extend(batch.commands.into_iter().map(|command| match command {
+                Command::FillRect(rect, color) => DrawCall::FillRect(rect, color),
+                Command::Line(start, end, color) => DrawCall::Line(start, end, color),
+                Command::Image { src, dst, angle, .. } => DrawCall::Image { src, dst, angle },
+            }));
+        }
+        self.stats = stats;
+
+        let mut record = self.record.borrow_mut();
+        record.frame_count += 1;
+        record.calls = calls;
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.fullscreen = fullscreen;
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    fn set_layer(&mut self, layer: i32) {
+        self.queue.set_layer(layer);
+    }
+
+    fn layer(&self) -> i32 {
+        self.queue.layer()
+    }
+
+    fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    fn set_stats_overlay(&mut self, shown: bool) {
+        self.stats_overlay = shown;
+    }
+
+    fn has_stats_overlay(&self) -> bool {
+        self.stats_overlay
+    }
+}
+
+/// The place where the game is drawn, and where the events come from.
+///
+/// A headless window has no event pump: the events must be given to the game directly.
 pub struct Window {
-    pub canvas: WindowCanvas,
-    pub event_pump: EventPump,
+    pub canvas: Box<dyn Renderer>,
+    pub event_pump: Option<EventPump>,
 }
 
 impl Window {
-    pub fn new(width: u32, height: u32) -> Window {
-        let sdl_context = sdl2::init().unwrap();
+    /// Open a SDL window, which can be resized.
+    pub fn new(width: u32, height: u32) -> Result<Window> {
+        let sdl_context = sdl2::init().map_err(Error::video)?;
 
-        let video_subsystem = sdl_context.video().unwrap();
+        let video_subsystem = sdl_context.video().map_err(Error::video)?;
         let window = video_subsystem
             .window("rust-games", width, height)
             .position_centered()
+            .resizable()
             .build()
-            .unwrap();
+            .map_err(Error::video)?;
 
-        let window = Window {
-            canvas: window.into_canvas().build().unwrap(),
-            event_pump: sdl_context.event_pump().unwrap(),
-        };
+        Ok(Window {
+            canvas: Box::new(SdlRenderer::new(window.into_canvas().build().map_err(Error::video)?)),
+            event_pump: Some(sdl_context.event_pump().map_err(Error::video)?),
+        })
+    }
+
+    /// Create a window drawing nowhere.
+    pub fn headless(width: u32, height: u32) -> Window {
+        Window {
+            canvas: Box::new(NullRenderer::new(width, height)),
+            event_pump: None,
+        }
+    }
 
-        window
+    /// Create the window matching the backend selected at startup.
+    pub fn open(backend: Backend, width: u32, height: u32) -> Result<Window> {
+        match backend {
+            Backend::Sdl => Window::new(width, height),
+            Backend::Headless => Ok(Window::headless(width, height)),
+        }
     }
 
     pub fn width(&self) -> u32 {
-        let dim = self.canvas.output_size().unwrap();
-        dim.0
+        self.canvas.output_size().0
     }
 
     pub fn height(&self) -> u32 {
-        let dim = self.canvas.output_size().unwrap();
-        dim.1
+        self.canvas.output_size().1
     }
 
     pub fn clear(&mut self) {
-        self.canvas.set_draw_color(Color::BLACK);
-        self.canvas.clear();
+        self.canvas.clear(Color::BLACK);
+    }
+
+    pub fn present(&mut self) {
+        self.canvas.present();
+    }
+
+    pub fn toggle_fullscreen(&mut self) {
+        let fullscreen = self.canvas.is_fullscreen();
+        self.canvas.set_fullscreen(!fullscreen);
+    }
+
+    pub fn toggle_stats_overlay(&mut self) {
+        let shown = self.canvas.has_stats_overlay();
+        self.canvas.set_stats_overlay(!shown);
     }
+
+    /// Handle the events of the window itself, before the game:
+    /// F11 toggles the fullscreen, and F3 the statistics of the frames.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::KeyDown { keycode: Some(Keycode::F11), repeat: false, .. } => self.toggle_fullscreen(),
+            Event::KeyDown { keycode: Some(Keycode::F3), repeat: false, .. } => self.toggle_stats_overlay(),
+            _ => {}
+        }
+    }
+}
+
+/// Maps the coordinates of the world to the pixels of the window.
+///
+/// The world is shown in a viewport keeping the aspect ratio of the size the game was designed for,
+/// centered in the window with black bars on the sides.
+/// Sizes in pixels, like the ones of the images, are given for the design size and scaled with the viewport.
+pub struct Camera {
+    world: geometry::Rect,
+    design_width: u32,
+    design_height: u32,
+    viewport: Rect,
+    zoom: f32,
+    pan: Vec2,
+    shake_amplitude: f32,
+    shake_duration: f32,
+    shake_time_left: f32,
+    shake_offset: Vec2,
+    rng: Rng,
 }
 
+impl Camera {
+    /// A camera showing the world in a viewport of the design size, until the window is resized.
+    pub fn new(world: geometry::Rect, design_width: u32, design_height: u32) -> Camera {
+        Camera {
+            world,
+            design_width,
+            design_height,
+            viewport: Rect::new(0, 0, design_width, design_height),
+            zoom: 1.,
+            pan: Vec2::ZERO,
+            shake_amplitude: 0.,
+            shake_duration: 0.,
+            shake_time_left: 0.,
+            shake_offset: Vec2::ZERO,
+            rng: Rng::new(0),
+        }
+    }
+
+    /// Fit the viewport in a drawing area of the given size, keeping the previous one if the area is empty.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let scale = (width as f32 / self.design_width as f32).min(height as f32 / self.design_height as f32);
+        let w = ((self.design_width as f32 * scale) as u32).clamp(1, width);
+        let h = ((self.design_height as f32 * scale) as u32).clamp(1, height);
+        self.viewport = Rect::new(((width - w) / 2) as i32, ((height - h) / 2) as i32, w, h);
+    }
 
-pub struct Sprite<'a> {
+    /// Fit the viewport in the window, which may have been resized since the last frame.
+    pub fn fit(&mut self, window: &Window) {
+        self.resize(window.width(), window.height());
+    }
+
+    /// Part of the window where the world is drawn.
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Zoom around the center of the view, 2 showing the world twice as big.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+    }
+
+    /// Center of the view in the world, without the shake.
+    pub fn center(&self) -> Vec2 {
+        self.world.center() + self.pan
+    }
+
+    pub fn look_at(&mut self, center: Vec2) {
+        self.pan = center - self.world.center();
+    }
+
+    /// Move the view in the world.
+    pub fn pan(&mut self, offset: Vec2) {
+        self.pan += offset;
+    }
+
+    /// Shake the view during some time, of up to the amplitude in the world, the shake fading out.
+    pub fn shake(&mut self, amplitude: f32, duration: f32) {
+        self.shake_amplitude = amplitude;
+        self.shake_duration = duration;
+        self.shake_time_left = duration;
+    }
+
+    /// Advance the shake of dt.
+    pub fn update(&mut self, dt: f32) {
+        self.shake_time_left = (self.shake_time_left - dt).max(0.);
+        self.shake_offset = if self.shake_time_left > 0. {
+            let amplitude = self.shake_amplitude * self.shake_time_left / self.shake_duration;
+            Vec2::new(self.rng.range(-amplitude, amplitude), self.rng.range(-amplitude, amplitude))
+        } else {
+            Vec2::ZERO
+        };
+    }
+
+    /// Pixels of the window by unit of the world, on each axis.
+    fn scale(&self) -> Vec2 {
+        Vec2::new(self.viewport.width() as f32 / self.world.w(), self.viewport.height() as f32 / self.world.h()) * self.zoom
+    }
+
+    fn viewport_center(&self) -> Vec2 {
+        Vec2::new(
+            self.viewport.x() as f32 + self.viewport.width() as f32 / 2.,
+            self.viewport.y() as f32 + self.viewport.height() as f32 / 2.,
+        )
+    }
+
+    pub fn to_screen(&self, position: Vec2) -> Point {
+        let scale = self.scale();
+        let offset = position - (self.center() + self.shake_offset);
+        let screen = self.viewport_center() + Vec2::new(offset.x * scale.x, offset.y * scale.y);
+        Point::new(screen.x.floor() as i32, screen.y.floor() as i32)
+    }
+
+    /// Position in the world of a pixel of the window, like the one under the mouse.
+    pub fn to_world(&self, point: Point) -> Vec2 {
+        let scale = self.scale();
+        let offset = Vec2::new(point.x() as f32, point.y() as f32) - self.viewport_center();
+        self.center() + self.shake_offset + Vec2::new(offset.x / scale.x, offset.y / scale.y)
+    }
+
+    pub fn to_screen_rect(&self, rect: &geometry::Rect) -> Rect {
+        let min = self.to_screen(rect.min());
+        let max = self.to_screen(rect.max());
+        Rect::new(min.x(), min.y(), (max.x() - min.x()).max(0) as u32, (max.y() - min.y()).max(0) as u32)
+    }
+
+    /// Pixels of the window by pixel of the design size, without the zoom.
+    pub fn pixel_scale(&self) -> f32 {
+        self.viewport.width() as f32 / self.design_width as f32
+    }
+
+    /// Length in the window of a length in pixels of the design size, for something of the world.
+    pub fn scale_pixels(&self, length: i32) -> i32 {
+        (length as f32 * self.pixel_scale() * self.zoom) as i32
+    }
+
+    /// Position in the window of a position in pixels of the design size, for the interface over the world.
+    ///
+    /// It doesn't follow the zoom, the pan nor the shake.
+    pub fn to_screen_pixel(&self, point: Point) -> Point {
+        let scale = self.pixel_scale();
+        Point::new(
+            self.viewport.x() + (point.x() as f32 * scale) as i32,
+            self.viewport.y() + (point.y() as f32 * scale) as i32,
+        )
+    }
+
+    /// Fill the parts of the drawing area out of the viewport, hiding what is drawn past the world.
+    ///
+    /// The bars are drawn on their own layer, over the game whatever the layers it uses.
+    pub fn draw_letterbox(&self, canvas: &mut dyn Renderer) {
+        let layer = canvas.layer();
+        canvas.set_layer(LETTERBOX_LAYER);
+        let (w, h) = canvas.output_size();
+        let v = self.viewport;
+        let bars = [
+            (0, 0, w, v.top().max(0) as u32),
+            (0, v.bottom(), w, (h as i32 - v.bottom()).max(0) as u32),
+            (0, v.top(), v.left().max(0) as u32, v.height()),
+            (v.right(), v.top(), (w as i32 - v.right()).max(0) as u32, v.height()),
+        ];
+        for (x, y, w, h) in bars.iter() {
+            if *w > 0 && *h > 0 {
+                canvas.fill_rect(Rect::new(*x, *y, *w, *h), Color::BLACK);
+            }
+        }
+        canvas.set_layer(layer);
+    }
+}
+
+
+pub struct Sprite {
     x_shift: i32,
     y_shift: i32,
-    pub surface: Surface<'a>,
+    width: u32,
+    height: u32,
+    pub image: Image,
+    /// Part of the image drawn, the whole image by default.
+    pub src_rect: Rect,
     pub dest_rect: Rect,
     pub angle: f64,
     is_visible: bool,
 }
 
-impl Sprite<'_> {
-    pub fn from_bmp(surf_path: &str) -> Sprite {
-        let surface = Surface::load_bmp(surf_path).unwrap();
-        let rect = surface.rect().clone();
-
-        Sprite {
-            x_shift: 0,
-            y_shift: 0,
-            surface,
-            dest_rect: rect,
-            angle: 0.,
-            is_visible: true,
-        }
+impl Sprite {
+    /// A sprite drawing the whole image at its own size.
+    pub fn from_image(image: Image) -> Sprite {
+        let rect = image.rect();
+        Sprite::simple_new(image, rect)
     }
 
-    pub fn simple_new(surf_path: &str, dest_rect: Rect) -> Sprite {
-        let surface = Surface::load_bmp(surf_path).unwrap();
-        Sprite {
-            x_shift: 0,
-            y_shift: 0,
-            surface,
-            dest_rect,
-            angle: 0.,
-            is_visible: true,
-        }
+    pub fn simple_new(image: Image, dest_rect: Rect) -> Sprite {
+        Sprite::new(0, 0, image, dest_rect)
     }
 
-    pub fn new(x_shift: i32, y_shift: i32, surf_path: &str, dest_rect: Rect, color: Color) -> Sprite {
-        let mut surface = Surface::load_bmp(surf_path).unwrap();
-        surface.set_color_mod(color);
+    /// A sprite of the size of the destination rectangle, in pixels of the design size of the camera.
+    pub fn new(x_shift: i32, y_shift: i32, image: Image, dest_rect: Rect) -> Sprite {
         Sprite {
             x_shift,
             y_shift,
-            surface,
+            width: dest_rect.width(),
+            height: dest_rect.height(),
+            src_rect: image.rect(),
+            image,
             dest_rect,
             angle: 0.,
             is_visible: true,
         }
     }
 
-    pub fn update(&mut self, logic_rect: geometry::Rect, angle: f64, scale_x: u32, scale_y: u32) {
-        self.dest_rect.y = (logic_rect.y0() * (scale_y as f32)) as i32;
-        self.dest_rect.x = (logic_rect.x0() * (scale_x as f32)) as i32;
-
-        self.dest_rect.x += self.x_shift;
-        self.dest_rect.y += self.y_shift;
+    pub fn update(&mut self, logic_rect: geometry::Rect, angle: f64, camera: &Camera) {
+        let position = camera.to_screen(logic_rect.min());
+        self.dest_rect.x = position.x() + camera.scale_pixels(self.x_shift);
+        self.dest_rect.y = position.y() + camera.scale_pixels(self.y_shift);
 
+        self.dest_rect.set_width(camera.scale_pixels(self.width as i32) as u32);
+        self.dest_rect.set_height(camera.scale_pixels(self.height as i32) as u32);
 
         self.angle = angle;
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
         if self.is_visible {
-            let texture_creator = canvas.texture_creator();
-            let texture = &texture_creator.create_texture_from_surface(&self.surface).unwrap();
+            canvas.copy_image(&self.image, self.src_rect, self.dest_rect, self.angle);
+        }
+    }
 
-            canvas.copy_ex(
-                texture,
-                Rect::new(0, 0, self.surface.width(), self.surface.height()),
-                self.dest_rect,
-                self.angle,
-                Point::new((self.dest_rect.width() / 2) as i32, (self.dest_rect.height() / 2) as i32),
-                false,
-                false,
-            ).unwrap();
+    /// Draw the sprite, and its ghosts on the other sides of the bounds when it goes over their edges.
+    pub fn draw_wrapped(&self, canvas: &mut dyn Renderer, bounds: Rect) {
+        if self.is_visible {
+            for dest_rect in wrapped_rects(self.dest_rect, bounds) {
+                canvas.copy_image(&self.image, self.src_rect, dest_rect, self.angle);
+            }
         }
     }
 
+    /// Draw a part of another image, like a frame of a [`crate::animation::SpriteSheet`].
+    pub fn set_frame(&mut self, image: &Image, src_rect: Rect) {
+        self.image = image.clone();
+        self.src_rect = src_rect;
+    }
+
     pub fn hide(&mut self) {
         self.is_visible = false;
     }
@@ -157,21 +791,24 @@ impl RectSprite {
         RectSprite::new(0, 0, color)
     }
 
-    pub fn update(&mut self, logic_rect: geometry::Rect, x_scale: u32, y_scale: u32) {
-        self.rect.x = (logic_rect.x0() * x_scale as f32) as i32;
-        self.rect.y = (logic_rect.y0() * y_scale as f32) as i32;
-
-        self.rect.x += self.x_shift;
-        self.rect.y += self.y_shift;
+    pub fn update(&mut self, logic_rect: geometry::Rect, camera: &Camera) {
+        self.rect = camera.to_screen_rect(&logic_rect);
+        self.rect.x += camera.scale_pixels(self.x_shift);
+        self.rect.y += camera.scale_pixels(self.y_shift);
+    }
 
-        self.rect.set_width((logic_rect.w() * x_scale as f32) as u32);
-        self.rect.set_height((logic_rect.h() * y_scale as f32) as u32);
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
+        if self.is_visible {
+            canvas.fill_rect(self.rect, self.color);
+        }
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
+    /// Draw the rectangle, and its ghosts on the other sides of the bounds when it goes over their edges.
+    pub fn draw_wrapped(&self, canvas: &mut dyn Renderer, bounds: Rect) {
         if self.is_visible {
-            canvas.set_draw_color(self.color);
-            canvas.fill_rect(self.rect).unwrap();
+            for rect in wrapped_rects(self.rect, bounds) {
+                canvas.fill_rect(rect, self.color);
+            }
         }
     }
 
@@ -198,35 +835,416 @@ impl Clone for RectSprite {
     }
 }
 
-pub struct RenderedString<'a> {
-    surface: Surface<'a>,
+/// A text centered on a position.
+///
+/// The text is rendered once, and rendered again only when it changes.
+pub struct RenderedString {
+    font: FontId,
+    color: Color,
+    text: String,
+    image: Image,
     xc: i32,
     yc: i32,
 }
 
-impl RenderedString<'_> {
-    pub fn new<'a>(str: &String, xc: i32, yc: i32, ttf_context: &Sdl2TtfContext, font_path: &str, font_size: u16) -> RenderedString<'a> {
-        let font = ttf_context.load_font(font_path, font_size).unwrap();
-        let surface = font.render(&*str).solid(Color::WHITE).unwrap();
-        RenderedString { surface, xc, yc }
+impl RenderedString {
+    pub fn new(text: &str, xc: i32, yc: i32, assets: &Assets, font: FontId) -> Result<RenderedString> {
+        RenderedString::new_colored(text, xc, yc, assets, font, Color::WHITE)
     }
 
-    pub fn new_colored<'a>(str: &String, xc: i32, yc: i32, ttf_context: &Sdl2TtfContext, font_path: &str, font_size: u16, color: Color) -> RenderedString<'a> {
-        let font = ttf_context.load_font(font_path, font_size).unwrap();
-        let surface = font.render(&*str).solid(color).unwrap();
-        RenderedString { surface, xc, yc }
+    pub fn new_colored(text: &str, xc: i32, yc: i32, assets: &Assets, font: FontId, color: Color) -> Result<RenderedString> {
+        let image = assets.render_text(font, text, color)?;
+        Ok(RenderedString { font, color, text: text.to_string(), image, xc, yc })
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
-        let texture_creator = canvas.texture_creator();
-        let texture = &texture_creator.create_texture_from_surface(&self.surface).unwrap();
-        let texture_query = texture.query();
-        let w = texture_query.width;
-        let h = texture_query.height;
-        canvas.copy(
-            texture,
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Change the text, rendering it only if it is not the current one.
+    pub fn set_text(&mut self, text: &str, assets: &Assets) -> Result<()> {
+        if text != self.text {
+            self.image = assets.render_text(self.font, text, self.color)?;
+            self.text = text.to_string();
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
+        let w = self.image.width();
+        let h = self.image.height();
+        canvas.copy_image(
+            &self.image,
             Rect::new(0, 0, w, h),
             Rect::new(self.xc - (w / 2) as i32, self.yc - (h / 2) as i32, w, h),
-        ).unwrap();
+            0.,
+        );
+    }
+
+    /// Draw the text scaled with the viewport of the camera, its position being in pixels of the design size.
+    pub fn draw_scaled(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        let w = self.image.width();
+        let h = self.image.height();
+        let scale = camera.pixel_scale();
+        let (scaled_w, scaled_h) = ((w as f32 * scale) as u32, (h as f32 * scale) as u32);
+        let center = camera.to_screen_pixel(Point::new(self.xc, self.yc));
+        canvas.copy_image(
+            &self.image,
+            Rect::new(0, 0, w, h),
+            Rect::new(center.x() - (scaled_w / 2) as i32, center.y() - (scaled_h / 2) as i32, scaled_w, scaled_h),
+            0.,
+        );
+    }
+}
+
+/// Handle on a node of a [`Scene`], valid until the node is removed.
+///
+/// Handles are generational like the [`crate::ecs::Entity`]: the handle of a removed node
+/// never gives the node added later in its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: usize,
+    generation: u32,
+}
+
+/// Layers of a scene, drawn from the background to the HUD.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    Background,
+    Playfield,
+    Hud,
+}
+
+/// What a node of a scene draws.
+pub enum Drawable {
+    /// Nothing, the node only moves or hides its children.
+    Group,
+    /// An image centered on the node, turned with it.
+    Sprite(Sprite),
+    /// A rectangle filling the node, which does not turn.
+    Rect(RectSprite),
+    /// A text at its own position in pixels of the design size, for the HUD.
+    Text(RenderedString),
+}
+
+/// Place of a node relative to its parent: the position of its center, and its angle in radians.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transform {
+    pub position: Vec2,
+    pub angle: f32,
+}
+
+impl Transform {
+    pub fn new(position: Vec2, angle: f32) -> Transform {
+        Transform { position, angle }
+    }
+
+    /// This transform, given relative to a parent, in the frame of the parent of the parent.
+    pub fn then(&self, parent: &Transform) -> Transform {
+        Transform {
+            position: parent.position + self.position.rotate(parent.angle),
+            angle: parent.angle + self.angle,
+        }
+    }
+}
+
+struct Node {
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    drawable: Drawable,
+    transform: Transform,
+    size: Vec2,
+    layer: Layer,
+    z: i32,
+    is_visible: bool,
+}
+
+/// Drawables composed in a tree, each child being placed relative to its parent and hidden with it.
+///
+/// The nodes are drawn by layer, then by z-index inside a layer, then in the order of the tree,
+/// all of them on the layer of the renderer when the scene is drawn, which keeps the order of the draws.
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    generations: Vec<u32>,
+    free: Vec<usize>,
+    roots: Vec<NodeId>,
+}
+
+impl Scene {
+    pub fn new() -> Scene {
+        Scene::default()
+    }
+
+    fn insert(&mut self, node: Node) -> NodeId {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                NodeId { index, generation: self.generations[index] }
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.generations.push(0);
+                NodeId { index: self.nodes.len() - 1, generation: 0 }
+            }
+        }
+    }
+
+    /// Is the node still in the scene?
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.generations.get(id.index) == Some(&id.generation) && self.nodes[id.index].is_some()
+    }
+
+    /// Panics if the node has been removed.
+    fn node(&self, id: NodeId) -> &Node {
+        assert!(self.contains(id), "removed node");
+        self.nodes[id.index].as_ref().unwrap()
+    }
+
+    /// Panics if the node has been removed.
+    fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        assert!(self.contains(id), "removed node");
+        self.nodes[id.index].as_mut().unwrap()
+    }
+
+    /// Add a node at the root of the scene.
+    pub fn add(&mut self, layer: Layer, drawable: Drawable) -> NodeId {
+        let id = self.insert(Node {
+            parent: None,
+            children: Vec::new(),
+            drawable,
+            transform: Transform::default(),
+            size: Vec2::ZERO,
+            layer,
+            z: 0,
+            is_visible: true,
+        });
+        self.roots.push(id);
+        id
+    }
+
+    /// Add a node attached to another one, in the same layer.
+    pub fn add_child(&mut self, parent: NodeId, drawable: Drawable) -> NodeId {
+        let layer = self.node(parent).layer;
+        let id = self.insert(Node {
+            parent: Some(parent),
+            children: Vec::new(),
+            drawable,
+            transform: Transform::default(),
+            size: Vec2::ZERO,
+            layer,
+            z: 0,
+            is_visible: true,
+        });
+        self.node_mut(parent).children.push(id);
+        id
+    }
+
+    /// Remove a node with all its children.
+    pub fn remove(&mut self, id: NodeId) {
+        match self.node(id).parent {
+            Some(parent) => self.node_mut(parent).children.retain(|child| *child != id),
+            None => self.roots.retain(|root| *root != id),
+        }
+        let mut removed = vec![id];
+        while let Some(id) = removed.pop() {
+            let node = self.nodes[id.index].take().expect("removed node");
+            removed.extend(node.children);
+            self.generations[id.index] = self.generations[id.index].wrapping_add(1);
+            self.free.push(id.index);
+        }
+    }
+
+    pub fn drawable(&self, id: NodeId) -> &Drawable {
+        &self.node(id).drawable
+    }
+
+    pub fn drawable_mut(&mut self, id: NodeId) -> &mut Drawable {
+        &mut self.node_mut(id).drawable
+    }
+
+    pub fn transform(&self, id: NodeId) -> Transform {
+        self.node(id).transform
+    }
+
+    pub fn set_transform(&mut self, id: NodeId, transform: Transform) {
+        self.node_mut(id).transform = transform;
+    }
+
+    pub fn set_position(&mut self, id: NodeId, position: Vec2) {
+        self.node_mut(id).transform.position = position;
+    }
+
+    pub fn set_angle(&mut self, id: NodeId, angle: f32) {
+        self.node_mut(id).transform.angle = angle;
+    }
+
+    /// Size of the node in the world, the one of the rectangle it fills.
+    pub fn set_size(&mut self, id: NodeId, size: Vec2) {
+        self.node_mut(id).size = size;
+    }
+
+    /// Place a node on a rectangle, relative to its parent.
+    pub fn set_rect(&mut self, id: NodeId, rect: &geometry::Rect) {
+        let node = self.node_mut(id);
+        node.transform.position = rect.center();
+        node.size = rect.size();
     }
-}
\ No newline at end of file
+
+    /// Move a node and its children to another layer.
+    pub fn set_layer(&mut self, id: NodeId, layer: Layer) {
+        let mut moved = vec![id];
+        while let Some(id) = moved.pop() {
+            let node = self.node_mut(id);
+            node.layer = layer;
+            moved.extend(node.children.iter().copied());
+        }
+    }
+
+    /// Order of the node inside its layer, the higher ones being drawn over the lower ones.
+    pub fn set_z(&mut self, id: NodeId, z: i32) {
+        self.node_mut(id).z = z;
+    }
+
+    pub fn show(&mut self, id: NodeId) {
+        self.node_mut(id).is_visible = true;
+    }
+
+    pub fn hide(&mut self, id: NodeId) {
+        self.node_mut(id).is_visible = false;
+    }
+
+    /// Is the node shown, with all its parents?
+    pub fn is_visible(&self, id: NodeId) -> bool {
+        let node = self.node(id);
+        node.is_visible && node.parent.is_none_or(|parent| self.is_visible(parent))
+    }
+
+    /// Place of the node in the world.
+    pub fn world_transform(&self, id: NodeId) -> Transform {
+        let node = self.node(id);
+        match node.parent {
+            Some(parent) => node.transform.then(&self.world_transform(parent)),
+            None => node.transform,
+        }
+    }
+
+    /// Nodes of the tree in depth-first order, with their place in the world, skipping the hidden ones.
+    fn visible_nodes(&self) -> Vec<(NodeId, Transform)> {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<(NodeId, Transform)> = self.roots.iter().rev().map(|root| (*root, Transform::default())).collect();
+        while let Some((id, parent)) = stack.pop() {
+            let node = self.node(id);
+            if !node.is_visible {
+                continue;
+            }
+            let transform = node.transform.then(&parent);
+            nodes.push((id, transform));
+            stack.extend(node.children.iter().rev().map(|child| (*child, transform)));
+        }
+        nodes
+    }
+
+    /// Update the sprites to the place of their nodes.
+    pub fn update(&mut self, camera: &Camera) {
+        let nodes = self.visible_nodes();
+        for (id, transform) in nodes {
+            let node = self.node_mut(id);
+            let rect = geometry::Rect::from_min_size(transform.position - node.size / 2., node.size);
+            match &mut node.drawable {
+                Drawable::Sprite(sprite) => sprite.update(rect, transform.angle.to_degrees() as f64, camera),
+                Drawable::Rect(rect_sprite) => rect_sprite.update(rect, camera),
+                Drawable::Group | Drawable::Text(_) => {}
+            }
+        }
+    }
+
+    /// Draw the visible nodes, by layer and z-index, on the current layer of the renderer.
+    pub fn draw(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        let mut nodes: Vec<&Node> = self.visible_nodes().into_iter().map(|(id, _)| self.node(id)).collect();
+        nodes.sort_by_key(|node| (node.layer, node.z));
+
+        for node in nodes {
+            match &node.drawable {
+                Drawable::Sprite(sprite) => sprite.draw(canvas),
+                Drawable::Rect(rect_sprite) => rect_sprite.draw(canvas),
+                Drawable::Text(text) => text.draw_scaled(canvas, camera),
+                Drawable::Group => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_letterboxes_and_zooms() {
+        let mut camera = Camera::new(geometry::Rect::new(0., 0., 1., 1.), 600, 600);
+        camera.resize(800, 600);
+        assert_eq!(camera.viewport(), Rect::new(100, 0, 600, 600));
+        assert_eq!(camera.to_screen(Vec2::new(0.5, 0.5)), Point::new(400, 300));
+        assert_eq!(camera.to_screen_rect(&geometry::Rect::new(0., 0., 0.5, 1.)), Rect::new(100, 0, 300, 600));
+        assert_eq!(camera.to_world(Point::new(700, 600)), Vec2::new(1., 1.));
+
+        // A minimized window keeps the last viewport.
+        camera.resize(0, 600);
+        assert_eq!(camera.viewport(), Rect::new(100, 0, 600, 600));
+
+        camera.set_zoom(2.);
+        camera.look_at(Vec2::new(0.25, 0.25));
+        assert_eq!(camera.to_screen(Vec2::new(0.25, 0.25)), Point::new(400, 300));
+        assert_eq!(camera.to_screen(Vec2::new(0.5, 0.25)), Point::new(700, 300));
+        assert_eq!(camera.scale_pixels(10), 20);
+        assert_eq!(camera.to_screen_pixel(Point::new(300, 300)), Point::new(400, 300));
+    }
+
+    #[test]
+    fn ghosts_are_drawn_on_the_other_sides_of_the_bounds() {
+        let rects = wrapped_rects(Rect::new(-5, 90, 10, 20), Rect::new(0, 0, 100, 100));
+        assert_eq!(rects, [Rect::new(-5, 90, 10, 20), Rect::new(95, 90, 10, 20), Rect::new(-5, -10, 10, 20), Rect::new(95, -10, 10, 20)]);
+    }
+
+    #[test]
+    fn scene_nodes_follow_their_parents_and_are_drawn_by_layer() {
+        let camera = Camera::new(geometry::Rect::new(0., 0., 1., 1.), 100, 100);
+        let mut scene = Scene::new();
+        let hud = scene.add(Layer::Hud, Drawable::Rect(RectSprite::default(Color::RED)));
+        let tank = scene.add(Layer::Playfield, Drawable::Rect(RectSprite::default(Color::WHITE)));
+        let turret = scene.add_child(tank, Drawable::Rect(RectSprite::default(Color::BLUE)));
+        scene.set_rect(hud, &geometry::Rect::new(0., 0., 1., 0.1));
+        scene.set_transform(tank, Transform::new(Vec2::new(0.5, 0.5), std::f32::consts::FRAC_PI_2));
+        scene.set_size(tank, Vec2::new(0.2, 0.2));
+        scene.set_rect(turret, &geometry::Rect::new(0.05, -0.05, 0.1, 0.1));
+        scene.set_z(tank, 1);
+        scene.set_z(turret, 2);
+
+        // The turret is a tenth in front of the tank, which faces down.
+        let position = scene.world_transform(turret).position;
+        assert!(position.distance(Vec2::new(0.5, 0.6)) < 1e-6);
+
+        let mut canvas = NullRenderer::new(100, 100);
+        let record = canvas.record();
+        scene.update(&camera);
+        scene.draw(&mut canvas, &camera);
+        canvas.present();
+        let colors: Vec<Color> = record.borrow().calls.iter().filter_map(|call| match call {
+            DrawCall::FillRect(_, color) => Some(*color),
+            _ => None,
+        }).collect();
+        assert_eq!(colors, vec![Color::WHITE, Color::BLUE, Color::RED]);
+
+        scene.hide(tank);
+        assert!(!scene.is_visible(turret));
+        scene.draw(&mut canvas, &camera);
+        canvas.present();
+        assert_eq!(record.borrow().calls.len(), 2);
+
+        // The handles of removed nodes don't give the nodes taking their slots.
+        scene.remove(tank);
+        let shell = scene.add(Layer::Playfield, Drawable::Group);
+        assert!(!scene.contains(tank) && !scene.contains(turret));
+        assert!(scene.contains(shell));
+    }
+}
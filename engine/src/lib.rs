@@ -1,9 +1,23 @@
+pub mod math;
 pub mod physics;
 pub mod geometry;
 pub mod random;
 pub mod collide;
 pub mod audio;
+pub mod batch;
+pub mod atlas;
 pub mod graphics;
+pub mod animation;
+pub mod tween;
+pub mod text;
+pub mod particles;
+pub mod game_loop;
+pub mod backend;
+pub mod replay;
+pub mod assets;
+pub mod error;
+pub mod ecs;
+pub mod spatial;
 
 pub mod engine {}
 
@@ -0,0 +1,200 @@
+//! Vectors of the plane, for positions, velocities and geometry.
+use std::f32::consts::PI;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A 2D vector, angles being in radians from the x axis, toward the y axis.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2 { x: 0., y: 0. };
+
+    pub const fn new(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+
+    /// Unit vector of the given angle.
+    pub fn from_angle(angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(cos, sin)
+    }
+
+    /// Vector of the given length and angle.
+    pub fn polar(length: f32, angle: f32) -> Vec2 {
+        Vec2::from_angle(angle) * length
+    }
+
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Z coordinate of the cross product, positive when other is on the positive angle side.
+    pub fn cross(self, other: Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    pub fn distance(self, other: Vec2) -> f32 {
+        (other - self).length()
+    }
+
+    /// Vector of length 1 in the same direction, or zero for the zero vector.
+    pub fn normalize(self) -> Vec2 {
+        let length = self.length();
+        if length == 0. {
+            return Vec2::ZERO;
+        }
+        self / length
+    }
+
+    /// Angle of the vector, between -π and π.
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+
+    /// Angle to turn from this vector to the other one, between -π and π.
+    pub fn angle_to(self, other: Vec2) -> f32 {
+        self.cross(other).atan2(self.dot(other))
+    }
+
+    pub fn rotate(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Perpendicular vector, turned by a quarter of turn.
+    pub fn perp(self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    /// Linear interpolation, giving self for t = 0 and other for t = 1.
+    pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        self + (other - self) * t
+    }
+
+    /// Mirror of the vector on a surface of the given unit normal.
+    pub fn reflect(self, normal: Vec2) -> Vec2 {
+        self - normal * (2. * self.dot(normal))
+    }
+}
+
+/// Same angle, between -π and π.
+pub fn normalize_angle(angle: f32) -> f32 {
+    let angle = (angle + PI).rem_euclid(2. * PI) - PI;
+    if angle == -PI { PI } else { angle }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, k: f32) -> Vec2 {
+        Vec2::new(self.x * k, self.y * k)
+    }
+}
+
+impl Mul<Vec2> for f32 {
+    type Output = Vec2;
+
+    fn mul(self, v: Vec2) -> Vec2 {
+        v * self
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, k: f32) -> Vec2 {
+        Vec2::new(self.x / k, self.y / k)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    fn mul_assign(&mut self, k: f32) {
+        *self = *self * k;
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    fn from((x, y): (f32, f32)) -> Vec2 {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for (f32, f32) {
+    fn from(v: Vec2) -> (f32, f32) {
+        (v.x, v.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-5
+    }
+
+    #[test]
+    fn operations() {
+        let a = Vec2::new(3., 4.);
+        assert_eq!(a.length(), 5.);
+        assert_eq!(a + Vec2::new(1., 1.), Vec2::new(4., 5.));
+        assert_eq!(2. * a - a, a);
+        assert_eq!(a.dot(a.perp()), 0.);
+        assert!(close(a.normalize(), Vec2::new(0.6, 0.8)));
+        assert!(close(Vec2::new(1., 0.).rotate(FRAC_PI_2), Vec2::new(0., 1.)));
+        assert!(close(Vec2::polar(2., a.angle()), a.normalize() * 2.));
+        assert_eq!(Vec2::ZERO.lerp(a, 0.5), Vec2::new(1.5, 2.));
+        assert_eq!(Vec2::new(1., -1.).reflect(Vec2::new(0., 1.)), Vec2::new(1., 1.));
+        assert!((Vec2::new(1., 0.).angle_to(Vec2::new(0., -1.)) + FRAC_PI_2).abs() < 1e-5);
+        assert!((normalize_angle(3. * PI) - PI).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,234 @@
+//! Particles for the effects of the games: explosions, exhausts, debris...
+//!
+//! A [`ParticleSystem`] owns a pool of particles allocated once, moved by the `dt` of the game.
+//! The particles are emitted in bursts with [`ParticleSystem::burst`], or continuously by an [`Emitter`].
+//! Their lifetime, speed and direction are drawn from the random generator of the system,
+//! so the same seed always gives the same particles.
+use std::f32::consts::TAU;
+
+use sdl2::pixels::Color;
+
+use crate::geometry;
+use crate::graphics::{Camera, Renderer};
+use crate::math::Vec2;
+use crate::random::Rng;
+use crate::tween::Lerp;
+
+/// How the particles of an effect are emitted, move and look.
+///
+/// The ranges are drawn at random for each particle, and the pairs give the values at the birth and at the death.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleConfig {
+    /// Range of the lifetime, in seconds.
+    pub lifetime: (f32, f32),
+    /// Range of the speed, in world units per second.
+    pub speed: (f32, f32),
+    /// Mean direction, in radians.
+    pub direction: f32,
+    /// Angle of the cone around the direction the particles go in, in radians: 2π for all the directions.
+    pub spread: f32,
+    /// Acceleration of the particles.
+    pub gravity: Vec2,
+    /// Color at the birth and at the death, interpolated in between.
+    pub colors: (Color, Color),
+    /// Size of the side of the squares at the birth and at the death, in world units.
+    pub sizes: (f32, f32),
+}
+
+impl ParticleConfig {
+    /// Particles going in all the directions without gravity, of a single color and size.
+    pub fn new(lifetime: f32, speed: f32, color: Color, size: f32) -> ParticleConfig {
+        ParticleConfig {
+            lifetime: (lifetime, lifetime),
+            speed: (speed, speed),
+            direction: 0.,
+            spread: TAU,
+            gravity: Vec2::ZERO,
+            colors: (color, color),
+            sizes: (size, size),
+        }
+    }
+}
+
+/// A random value of a range, without drawing a number when the range is a single value.
+fn draw(rng: &mut Rng, (min, max): (f32, f32)) -> f32 {
+    if min < max { rng.range(min, max) } else { min }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    gravity: Vec2,
+    age: f32,
+    lifetime: f32,
+    colors: (Color, Color),
+    sizes: (f32, f32),
+}
+
+impl Particle {
+    /// Part of its life already lived, from 0 at its birth to 1 at its death.
+    fn progress(&self) -> f32 {
+        (self.age / self.lifetime).min(1.)
+    }
+}
+
+/// A pool of particles, allocated at once, the particles emitted when it is full being dropped.
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    capacity: usize,
+    rng: Rng,
+}
+
+impl ParticleSystem {
+    pub fn new(capacity: usize, rng: Rng) -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::with_capacity(capacity),
+            capacity,
+            rng,
+        }
+    }
+
+    /// Emit a particle at a position, unless the pool is full.
+    pub fn emit(&mut self, position: Vec2, config: &ParticleConfig) {
+        if self.particles.len() == self.capacity {
+            return;
+        }
+        let angle = config.direction + self.rng.range(-0.5, 0.5) * config.spread;
+        let speed = draw(&mut self.rng, config.speed);
+        let lifetime = draw(&mut self.rng, config.lifetime);
+        self.particles.push(Particle {
+            position,
+            velocity: Vec2::polar(speed, angle),
+            gravity: config.gravity,
+            age: 0.,
+            lifetime,
+            colors: config.colors,
+            sizes: config.sizes,
+        });
+    }
+
+    /// Emit many particles at once, like for an explosion.
+    pub fn burst(&mut self, position: Vec2, count: usize, config: &ParticleConfig) {
+        for _ in 0..count {
+            self.emit(position, config);
+        }
+    }
+
+    /// Move the particles of dt, dropping the ones at the end of their life.
+    pub fn update(&mut self, dt: f32) {
+        for particle in &mut self.particles {
+            particle.age += dt;
+            particle.velocity += particle.gravity * dt;
+            particle.position += particle.velocity * dt;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+    }
+
+    /// Number of living particles.
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// Draw each particle as a square of its current size and color.
+    pub fn draw(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        for particle in &self.particles {
+            let t = particle.progress();
+            let size = particle.sizes.0.lerp(particle.sizes.1, t);
+            let rect = geometry::Rect::from_min_size(particle.position - Vec2::new(size, size) / 2., Vec2::new(size, size));
+            canvas.fill_rect(camera.to_screen_rect(&rect), particle.colors.0.lerp(particle.colors.1, t));
+        }
+    }
+}
+
+/// Emits particles continuously at a rate per second while it is on, like the exhaust of a ship.
+pub struct Emitter {
+    pub config: ParticleConfig,
+    rate: f32,
+    pending: f32,
+    is_emitting: bool,
+}
+
+impl Emitter {
+    /// An emitter which is off until started.
+    pub fn new(config: ParticleConfig, rate: f32) -> Emitter {
+        Emitter {
+            config,
+            rate,
+            pending: 0.,
+            is_emitting: false,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.is_emitting = true;
+    }
+
+    pub fn stop(&mut self) {
+        self.is_emitting = false;
+        self.pending = 0.;
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.is_emitting
+    }
+
+    /// Emit the particles due during dt at a position, if the emitter is on.
+    pub fn update(&mut self, dt: f32, position: Vec2, system: &mut ParticleSystem) {
+        if !self.is_emitting {
+            return;
+        }
+        self.pending += self.rate * dt;
+        while self.pending >= 1. {
+            system.emit(position, &self.config);
+            self.pending -= 1.;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_fall_and_die_at_the_end_of_their_life() {
+        let config = ParticleConfig { gravity: Vec2::new(0., 1.), ..ParticleConfig::new(1., 0., Color::WHITE, 0.01) };
+        let mut system = ParticleSystem::new(10, Rng::new(1));
+        system.burst(Vec2::ZERO, 20, &config);
+        assert_eq!(system.len(), 10);
+
+        system.update(0.5);
+        assert_eq!(system.len(), 10);
+        assert!(system.particles.iter().all(|particle| particle.velocity == Vec2::new(0., 0.5)));
+
+        system.update(0.6);
+        assert!(system.is_empty());
+    }
+
+    #[test]
+    fn emitters_are_deterministic() {
+        let config = ParticleConfig { speed: (0.1, 0.5), lifetime: (2., 3.), ..ParticleConfig::new(1., 0., Color::RED, 0.01) };
+        let run = || {
+            let mut system = ParticleSystem::new(100, Rng::new(7));
+            let mut emitter = Emitter::new(config.clone(), 10.);
+            emitter.start();
+            for _ in 0..5 {
+                emitter.update(0.25, Vec2::new(0.5, 0.5), &mut system);
+                system.update(0.25);
+            }
+            system.particles.iter().map(|particle| particle.position).collect::<Vec<Vec2>>()
+        };
+
+        let positions = run();
+        assert_eq!(positions.len(), 12);
+        assert_eq!(positions, run());
+    }
+}
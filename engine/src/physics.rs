@@ -1,74 +1,158 @@
+use crate::collide::{first_hit, sweep_circle, sweep_rect, Hit};
 use crate::geometry::{AsCircle, AsRect, Circle, Rect};
+use crate::math::Vec2;
 
-pub struct Position {
-    x: f32,
-    y: f32,
+/// Position of a solid, in the unit of its limit.
+pub type Position = Vec2;
+
+/// Velocity of a solid, by second.
+pub type Velocity = Vec2;
+
+/// Dynamics of a solid: its velocity changes with accelerations, drag and impulses.
+///
+/// The body doesn't own the velocity, so it works with any solid:
+/// [`Body::integrate`] is called on its velocity before the solid moves.
+pub struct Body {
+    /// Mass of the body, infinite for an obstacle which can't be pushed.
+    pub mass: f32,
+
+    /// Fraction of the velocity lost each second.
+    pub drag: f32,
+
+    /// Highest speed, in any direction.
+    pub max_speed: Option<f32>,
+
+    /// Part of the speed kept after a collision, from 0 (no bounce) to 1 (elastic bounce).
+    pub restitution: f32,
+
+    /// Orientation, in radians, turning at the angular velocity.
+    pub angle: f32,
+    pub angular_velocity: f32,
+
+    acc: Vec2,
 }
 
-impl Position {
-    pub fn new(x: f32, y: f32) -> Position {
-        Position {
-            x,
-            y,
+impl Body {
+    pub fn new(mass: f32) -> Body {
+        Body {
+            mass,
+            drag: 0.,
+            max_speed: None,
+            restitution: 1.,
+            angle: 0.,
+            angular_velocity: 0.,
+            acc: Vec2::ZERO,
         }
     }
 
-    pub fn x(&self) -> f32 {
-        self.x
+    /// A body which can't be pushed, like a wall.
+    pub fn fixed() -> Body {
+        Body::new(f32::INFINITY)
     }
-    pub fn y(&self) -> f32 {
-        self.y
+
+    fn inverse_mass(&self) -> f32 {
+        if self.mass.is_finite() { 1. / self.mass } else { 0. }
     }
 
+    /// Accelerate during the next integration.
+    pub fn accelerate(&mut self, acc: Vec2) {
+        self.acc += acc;
+    }
 
-    pub fn set_x(&mut self, x: f32) {
-        self.x = x;
+    /// Apply a force during the next integration.
+    pub fn apply_force(&mut self, force: Vec2) {
+        self.accelerate(force * self.inverse_mass());
     }
-    pub fn set_y(&mut self, y: f32) {
-        self.y = y;
+
+    /// Change the velocity at once.
+    pub fn apply_impulse(&self, vel: &mut Velocity, impulse: Vec2) {
+        *vel += impulse * self.inverse_mass();
     }
-}
 
-pub struct Velocity {
-    vx: f32,
-    vy: f32,
-}
+    /// Update the velocity and the angle during dt, then forget the accelerations.
+    pub fn integrate(&mut self, vel: &mut Velocity, dt: f32) {
+        *vel += self.acc * dt;
+        self.acc = Vec2::ZERO;
 
-impl Velocity {
-    pub fn new(vx: f32, vy: f32) -> Velocity {
-        Velocity {
-            vx,
-            vy,
-        }
-    }
-    pub fn default() -> Velocity {
-        Velocity {
-            vx: 0.,
-            vy: 0.,
-        }
-    }
-    pub fn copy(&self) -> Velocity {
-        Velocity {
-            vx: self.vx,
-            vy: self.vy,
+        *vel *= (1. - self.drag * dt).max(0.);
+
+        if let Some(max_speed) = self.max_speed {
+            let speed = vel.length();
+            if speed > max_speed {
+                *vel *= max_speed / speed;
+            }
         }
-    }
 
-    pub fn vx(&self) -> f32 {
-        self.vx
+        self.angle += self.angular_velocity * dt;
     }
-    pub fn vy(&self) -> f32 {
-        self.vy
+
+    /// Bounce on a fixed obstacle, the normal going from the obstacle toward the body.
+    pub fn bounce(&self, vel: &mut Velocity, normal: Vec2) {
+        let mut obstacle_vel = Velocity::ZERO;
+        resolve_collision(self, vel, &Body::fixed(), &mut obstacle_vel, normal);
     }
+}
 
-    pub fn mag(&self) -> f32 {
-        ((self.vx * self.vx) + (self.vy * self.vy)).sqrt()
+/// Change the velocities of two colliding bodies, the normal going from b toward a.
+///
+/// The impulse depends on the masses and on the lowest restitution of both bodies.
+/// Bodies already getting apart are left as they are.
+pub fn resolve_collision(a: &Body, a_vel: &mut Velocity, b: &Body, b_vel: &mut Velocity, normal: Vec2) {
+    let normal_speed = (*a_vel - *b_vel).dot(normal);
+    let inverse_masses = a.inverse_mass() + b.inverse_mass();
+    if normal_speed >= 0. || inverse_masses == 0. {
+        return;
     }
-    pub fn angle(&self) -> f32 { self.vy.atan2(self.vx) }
 
-    pub fn set_vx(&mut self, vx: f32) { self.vx = vx; }
-    pub fn set_vy(&mut self, vy: f32) {
-        self.vy = vy;
+    let restitution = a.restitution.min(b.restitution);
+    let j = -(1. + restitution) * normal_speed / inverse_masses;
+    a.apply_impulse(a_vel, normal * j);
+    b.apply_impulse(b_vel, -normal * j);
+}
+
+/// What a solid does when it reaches its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Stop on the limit.
+    Clamp,
+    /// Come back on the other side, as in asteroids.
+    Wrap,
+    /// Stop on the limit, the velocity being reflected.
+    Bounce,
+    /// Go on, the solid being out once it is past the limit, so the game can despawn it.
+    Despawn,
+}
+
+impl Boundary {
+    /// Apply the boundary on one axis, for a solid going from p to p + size, the limit from l0 to l1.
+    ///
+    /// Returns whether the solid is out.
+    fn apply(self, p: &mut f32, v: &mut f32, size: f32, l0: f32, l1: f32) -> bool {
+        match self {
+            Boundary::Clamp => {
+                if *p < l0 {
+                    *p = l0;
+                } else if *p + size > l1 {
+                    *p = l1 - size;
+                }
+            }
+            Boundary::Bounce => {
+                if *p < l0 {
+                    *p = l0;
+                    *v = v.abs();
+                } else if *p + size > l1 {
+                    *p = l1 - size;
+                    *v = -v.abs();
+                }
+            }
+            Boundary::Wrap => {
+                *p = l0 + (*p - l0).rem_euclid(l1 - l0);
+            }
+            Boundary::Despawn => {
+                return *p + size < l0 || *p > l1;
+            }
+        }
+        false
     }
 }
 
@@ -78,6 +162,8 @@ pub struct RectSolid {
     w: f32,
     h: f32,
     limit: Rect,
+    boundary: Boundary,
+    is_out: bool,
 }
 
 impl RectSolid {
@@ -89,52 +175,88 @@ impl RectSolid {
             w,
             h,
             limit,
+            boundary: Boundary::Clamp,
+            is_out: false,
         }
     }
 
     pub fn fixed(x: f32, y: f32, w: f32, h: f32) -> RectSolid
     {
-        let pos = Position::new(x, y);
-        let vel = Velocity::default();
-
         RectSolid {
-            pos,
-            vel,
+            pos: Position::new(x, y),
+            vel: Velocity::ZERO,
             w,
             h,
             limit: Rect::default(),
+            boundary: Boundary::Clamp,
+            is_out: false,
         }
     }
 
+    /// Change what the solid does on its limit, clamping by default.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Is the solid past its limit, with the [`Boundary::Despawn`] policy?
+    pub fn is_out(&self) -> bool {
+        self.is_out
+    }
+
     pub fn update(&mut self, dt: f32) {
-        self.pos.set_x(self.pos.x + (self.vel.vx * dt));
-        self.pos.set_y(self.pos.y + (self.vel.vy * dt));
+        self.pos += self.vel * dt;
+
+        let out_y = self.boundary.apply(&mut self.pos.y, &mut self.vel.y, self.h, self.limit.y0(), self.limit.y1());
+        let out_x = self.boundary.apply(&mut self.pos.x, &mut self.vel.x, self.w, self.limit.x0(), self.limit.x1());
+        self.is_out = out_x || out_y;
+    }
 
-        if self.pos.y() < self.limit.y0() {
-            self.pos.set_y(self.limit.y0());
-        } else if self.pos.y() + self.h > self.limit.y1() {
-            self.pos.set_y(self.limit.y1() - self.h);
+    /// The rectangle of the solid, with its copies on the other sides when it wraps over the limit.
+    pub fn wrapped_rects(&self) -> Vec<Rect> {
+        match self.boundary {
+            Boundary::Wrap => self.as_rect().wrapped(&self.limit),
+            _ => vec![self.as_rect()],
         }
+    }
+
+    /// Rectangle covered by the solid while moving during dt, to find what it may hit.
+    pub fn swept_rect(&self, dt: f32) -> Rect {
+        let end = self.pos + self.vel * dt;
+        Rect::from_2_points(self.pos.x.min(end.x), self.pos.y.min(end.y), self.pos.x.max(end.x) + self.w, self.pos.y.max(end.y) + self.h)
+    }
+
+    /// First contact with a fixed rectangle when moving during dt.
+    pub fn sweep(&self, obstacle: &Rect, dt: f32) -> Option<Hit> {
+        sweep_rect(&self.as_rect(), self.vel, obstacle, dt)
+    }
 
-        if self.pos.x() < self.limit.x0() {
-            self.pos.set_x(self.limit.x0());
-        } else if self.pos.x() + self.w > self.limit.x1() {
-            self.pos.set_x(self.limit.x1() - self.w);
+    /// Move during dt, but stop at the first contact with the obstacles.
+    ///
+    /// The hit is returned so the velocity can be changed, and the solid moved for the time left.
+    pub fn update_until_hit(&mut self, dt: f32, obstacles: &[Rect]) -> Option<Hit> {
+        let hit = first_hit(obstacles.iter().filter_map(|obstacle| self.sweep(obstacle, dt)));
+        match &hit {
+            Some(hit) => self.update(hit.time),
+            None => self.update(dt),
         }
+        hit
     }
 }
 
 impl AsRect for RectSolid {
     fn as_rect(&self) -> Rect {
-        Rect::new(self.pos.x, self.pos.y, self.w, self.h)
+        Rect::from_min_size(self.pos, Vec2::new(self.w, self.h))
     }
 }
 
+/// A moving circle, its limit applying to its center.
 pub struct CircleSolid {
     pub pos: Position,
     pub vel: Velocity,
     pub r: f32,
     limit: Rect,
+    boundary: Boundary,
+    is_out: bool,
 }
 
 impl CircleSolid {
@@ -145,28 +267,129 @@ impl CircleSolid {
             vel,
             r,
             limit,
+            boundary: Boundary::Clamp,
+            is_out: false,
         }
     }
+
+    /// Change what the solid does on its limit, clamping by default.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Is the solid past its limit, with the [`Boundary::Despawn`] policy?
+    pub fn is_out(&self) -> bool {
+        self.is_out
+    }
+
     pub fn update(&mut self, dt: f32) {
-        self.pos.set_x(self.pos.x + (self.vel.vx * dt));
-        self.pos.set_y(self.pos.y + (self.vel.vy * dt));
+        self.pos += self.vel * dt;
+
+        let out_y = self.boundary.apply(&mut self.pos.y, &mut self.vel.y, 0., self.limit.y0(), self.limit.y1());
+        let out_x = self.boundary.apply(&mut self.pos.x, &mut self.vel.x, 0., self.limit.x0(), self.limit.x1());
+        self.is_out = out_x || out_y;
+    }
 
-        if self.pos.y() < self.limit.y0() {
-            self.pos.set_y(self.limit.y0());
-        } else if self.pos.y() > self.limit.y1() {
-            self.pos.set_y(self.limit.y1());
+    /// The circle of the solid, with its copies on the other sides when it wraps over the limit.
+    pub fn wrapped_circles(&self) -> Vec<Circle> {
+        match self.boundary {
+            Boundary::Wrap => self.as_circle().wrapped(&self.limit),
+            _ => vec![self.as_circle()],
         }
+    }
 
-        if self.pos.x() < self.limit.x0() {
-            self.pos.set_x(self.limit.x0());
-        } else if self.pos.x() > self.limit.x1() {
-            self.pos.set_x(self.limit.x1());
+    /// First contact with a fixed circle when moving during dt.
+    pub fn sweep(&self, obstacle: &Circle, dt: f32) -> Option<Hit> {
+        sweep_circle(&self.as_circle(), self.vel, obstacle, dt)
+    }
+
+    /// Move during dt, but stop at the first contact with the obstacles.
+    ///
+    /// The hit is returned so the velocity can be changed, and the solid moved for the time left.
+    pub fn update_until_hit(&mut self, dt: f32, obstacles: &[Circle]) -> Option<Hit> {
+        let hit = first_hit(obstacles.iter().filter_map(|obstacle| self.sweep(obstacle, dt)));
+        match &hit {
+            Some(hit) => self.update(hit.time),
+            None => self.update(dt),
         }
+        hit
     }
 }
 
 impl AsCircle for CircleSolid {
     fn as_circle(&self) -> Circle {
-        Circle::new(self.pos.x, self.pos.y, self.r)
+        Circle::from_center(self.pos, self.r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solid_stops_at_the_first_obstacle() {
+        let mut ball = RectSolid::new(Position::new(0., 0.), Velocity::new(10., 0.), 0.01, 0.01, Rect::new(-1., -1., 3., 3.));
+        let obstacles = [Rect::new(0.6, -0.5, 0.01, 1.), Rect::new(0.3, -0.5, 0.01, 1.)];
+
+        let hit = ball.update_until_hit(0.1, &obstacles).unwrap();
+        assert!((ball.pos.x - 0.29).abs() < 1e-5);
+
+        ball.vel = ball.vel.reflect(hit.normal);
+        assert_eq!(ball.vel.x, -10.);
+        assert!(ball.update_until_hit(0.1 - hit.time, &obstacles).is_none());
+    }
+
+    #[test]
+    fn boundary_policies() {
+        let limit = || Rect::new(0., 0., 1., 1.);
+        let solid = |boundary| {
+            let mut solid = CircleSolid::new(Position::new(0.9, 0.5), Velocity::new(1., 0.), 0.05, limit());
+            solid.set_boundary(boundary);
+            solid.update(0.12);
+            solid
+        };
+
+        let clamped = solid(Boundary::Clamp);
+        assert_eq!((clamped.pos.x, clamped.vel.x), (1., 1.));
+
+        let bounced = solid(Boundary::Bounce);
+        assert_eq!((bounced.pos.x, bounced.vel.x), (1., -1.));
+
+        let wrapped = solid(Boundary::Wrap);
+        assert!((wrapped.pos.x - 0.02).abs() < 1e-5);
+        assert_eq!(wrapped.wrapped_circles().len(), 2);
+
+        let despawned = solid(Boundary::Despawn);
+        assert!(despawned.is_out());
+        assert!(!solid(Boundary::Clamp).is_out());
+    }
+
+    #[test]
+    fn max_speed_is_a_magnitude() {
+        let mut body = Body::new(2.);
+        body.max_speed = Some(1.);
+        let mut vel = Velocity::ZERO;
+
+        body.apply_force(Vec2::new(10., 10.));
+        body.integrate(&mut vel, 1.);
+        assert!((vel.length() - 1.).abs() < 1e-5);
+        assert!((vel.x - vel.y).abs() < 1e-5);
+    }
+
+    #[test]
+    fn impulses_keep_the_momentum() {
+        let heavy = Body::new(3.);
+        let mut light = Body::new(1.);
+        light.restitution = 0.5;
+        let mut heavy_vel = Velocity::new(1., 0.);
+        let mut light_vel = Velocity::new(-1., 0.);
+
+        resolve_collision(&heavy, &mut heavy_vel, &light, &mut light_vel, Vec2::new(-1., 0.));
+        assert!((3. * heavy_vel.x + light_vel.x - 2.).abs() < 1e-5);
+        assert!((light_vel.x - heavy_vel.x - 1.).abs() < 1e-5);
+
+        let mut vel = Velocity::new(1., -2.);
+        light.bounce(&mut vel, Vec2::new(0., 1.));
+        assert_eq!(vel, Vec2::new(1., 1.));
     }
 }
@@ -1,15 +1,110 @@
-use rand::prelude::*;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Generate a random unsigned int between 0 and max.
+/// Seedable pseudo random number generator.
 ///
-/// The generation is very simple, it just takes the current time and take the module from it.
-/// It is not a good random generator but will be ok for simple application;
-pub fn rand(min: i32, max: i32) -> i32 {
-    let mut rng = rand::thread_rng();
-    rng.gen_range(min..max)
+/// The generation is a SplitMix64, very simple and fast, but good enough for games.
+/// The same seed always gives the same sequence, so a game can be replayed bit for bit.
+/// Each subsystem of a game should own its own stream, created with [`fork`].
+///
+/// [`fork`]: Rng::fork
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator from a seed.
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Create a generator seeded with the current time, for when the game doesn't need to be replayed.
+    pub fn from_time() -> Rng {
+        Rng::new(time_seed())
+    }
+
+    /// Next random 64 bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random int between min (included) and max (excluded).
+    pub fn rand(&mut self, min: i32, max: i32) -> i32 {
+        assert!(min < max, "empty range {}..{}", min, max);
+        let range = (max as i64 - min as i64) as u64;
+        (min as i64 + (self.next_u64() % range) as i64) as i32
+    }
+
+    /// Random float between 0 (included) and 1 (excluded).
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Random float between min (included) and max (excluded).
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.unit()
+    }
+
+    /// Random boolean, like a coin toss.
+    pub fn flip(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Create a new independent stream, seeded from this one.
+    ///
+    /// Drawing numbers from the fork doesn't change the sequence of the parent,
+    /// so adding random effects to a subsystem doesn't change the others.
+    pub fn fork(&mut self) -> Rng {
+        Rng::new(self.next_u64())
+    }
+}
+
+/// A seed taken from the current time.
+pub fn time_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    now.as_secs() ^ ((now.subsec_nanos() as u64) << 32)
 }
 
-/// Random boolean, like a coin toss.
-pub fn flip() -> bool {
-    random()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn forks_are_independent_of_parent_usage() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut fork_a = a.fork();
+        let mut fork_b = b.fork();
+
+        // Drawing from a fork doesn't change the parent sequence.
+        fork_a.next_u64();
+        assert_eq!(a.next_u64(), b.next_u64());
+
+        fork_b.next_u64();
+        assert_eq!(fork_a.next_u64(), fork_b.next_u64());
+    }
+
+    #[test]
+    fn rand_stays_in_range() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            let value = rng.rand(-35, 35);
+            assert!((-35..35).contains(&value));
+            let value = rng.range(0.25, 0.5);
+            assert!((0.25..0.5).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Recording and replay of play sessions.
+//!
+//! A session is the seed of the game, then the sequence of frames given to the game loop:
+//! the real duration of each frame and the events polled during it.
+//! Replaying the frames on a game created with the same seed gives exactly the same game.
+//!
+//! # File format
+//!
+//! A recording is a text file, one entry per line:
+//!
+//! ```text
+//! replay 1
+//! seed 1234
+//! frame 0.016666668
+//! key_down 0 Up
+//! frame 0.017
+//! key_up 0 Up
+//! quit
+//! ```
+//!
+//! - `replay <version>` is always the first line, the current version is 1.
+//! - `seed <u64>` is the seed the game has been created with.
+//! - `frame <dt>` starts a new frame lasting `dt` seconds.
+//! - `key_down <repeat> <key>` and `key_up <repeat> <key>` are keyboard events of the current frame.
+//!   `repeat` is 1 for a key repeat, 0 otherwise, and `key` is the SDL name of the key.
+//! - `quit` is a quit event of the current frame.
+//!
+//! Empty lines and lines starting with `#` are ignored. Other events are not recorded,
+//! as none of the games use them.
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::{fmt, fs, io};
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+
+use crate::backend::key_event;
+use crate::error;
+
+/// Version of the file format written by the recorder.
+pub const VERSION: u32 = 1;
+
+/// One frame of a recorded session.
+pub struct Frame {
+    /// Real duration of the frame, in seconds.
+    pub dt: f32,
+    pub events: Vec<Event>,
+}
+
+/// Write a play session, frame by frame.
+pub struct Recorder {
+    writer: Box<dyn Write>,
+}
+
+impl Recorder {
+    /// Start a recording with the seed of the game.
+    pub fn new(mut writer: Box<dyn Write>, seed: u64) -> io::Result<Recorder> {
+        writeln!(writer, "replay {}", VERSION)?;
+        writeln!(writer, "seed {}", seed)?;
+        Ok(Recorder { writer })
+    }
+
+    /// Start a recording in a new file.
+    pub fn create<P: AsRef<Path>>(path: P, seed: u64) -> io::Result<Recorder> {
+        let file = File::create(path)?;
+        Recorder::new(Box::new(BufWriter::new(file)), seed)
+    }
+
+    /// Start a new frame, the next events recorded belong to it.
+    pub fn frame(&mut self, dt: f32) -> io::Result<()> {
+        writeln!(self.writer, "frame {}", dt)
+    }
+
+    /// Record an event of the current frame.
+    pub fn event(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::Quit { .. } => writeln!(self.writer, "quit"),
+            Event::KeyDown { keycode: Some(keycode), repeat, .. } => {
+                writeln!(self.writer, "key_down {} {}", *repeat as u8, keycode.name())
+            }
+            Event::KeyUp { keycode: Some(keycode), repeat, .. } => {
+                writeln!(self.writer, "key_up {} {}", *repeat as u8, keycode.name())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Error found while reading or writing a recording.
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+
+    /// The line, starting at 1, can't be understood.
+    Syntax { line: usize, content: String },
+
+    /// A command line flag is not followed by the file it needs.
+    MissingFile(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(error) => write!(f, "can't read or write the replay: {}", error),
+            ReplayError::Syntax { line, content } => write!(f, "invalid replay line {}: {:?}", line, content),
+            ReplayError::MissingFile(flag) => write!(f, "{} requires a file", flag),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> ReplayError {
+        ReplayError::Io(error)
+    }
+}
+
+/// A recorded session, ready to be played back.
+pub struct Replay {
+    seed: u64,
+    frames: VecDeque<Frame>,
+}
+
+impl Replay {
+    /// Read a recording from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Replay, ReplayError> {
+        Replay::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Read a recording from its text.
+    pub fn parse(text: &str) -> Result<Replay, ReplayError> {
+        let mut seed = None;
+        let mut frames = VecDeque::new();
+        let mut has_header = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let syntax_error = || ReplayError::Syntax { line: index + 1, content: line.to_string() };
+            let (command, args) = match line.find(' ') {
+                Some(space) => (&line[..space], line[space + 1..].trim()),
+                None => (line, ""),
+            };
+
+            if !has_header {
+                if command != "replay" || args.parse::<u32>().ok() != Some(VERSION) {
+                    return Err(syntax_error());
+                }
+                has_header = true;
+                continue;
+            }
+
+            match command {
+                "seed" => seed = Some(args.parse().map_err(|_| syntax_error())?),
+                "frame" => frames.push_back(Frame { dt: args.parse().map_err(|_| syntax_error())?, events: Vec::new() }),
+                "quit" | "key_down" | "key_up" => {
+                    let event = match command {
+                        "quit" => Event::Quit { timestamp: 0 },
+                        _ => parse_key_event(command == "key_down", args).ok_or_else(syntax_error)?,
+                    };
+                    frames.back_mut().ok_or_else(syntax_error)?.events.push(event);
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+
+        match seed {
+            Some(seed) => Ok(Replay { seed, frames }),
+            None => Err(ReplayError::Syntax { line: 0, content: "missing seed".to_string() }),
+        }
+    }
+
+    /// Seed the recorded game has been created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Take the next frame to play, if any.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.frames.pop_front()
+    }
+}
+
+fn parse_key_event(down: bool, args: &str) -> Option<Event> {
+    let (repeat, name) = args.split_at(args.find(' ')?);
+    let repeat = match repeat {
+        "0" => false,
+        "1" => true,
+        _ => return None,
+    };
+    let keycode = Keycode::from_name(name.trim())?;
+    Some(key_event(down, keycode, repeat))
+}
+
+/// Where the frames of the game loop come from.
+pub enum Input {
+    /// Real time and events polled from the window.
+    Live,
+
+    /// Same as live, each frame being recorded.
+    Record(Recorder),
+
+    /// Frames read from a recording. The window events are ignored, except quitting.
+    Replay(Replay),
+}
+
+impl Input {
+    /// Select the input from the command line, `--record <file>` or `--replay <file>`.
+    ///
+    /// When recording, the session is recorded with the given seed.
+    /// It returns the input and the seed the game must be created with.
+    pub fn from_args(seed: u64) -> error::Result<(Input, u64)> {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |flag: &str| match args.iter().position(|arg| arg == flag) {
+            Some(index) => args.get(index + 1).map(Some).ok_or_else(|| ReplayError::MissingFile(flag.to_string())),
+            None => Ok(None),
+        };
+
+        if let Some(path) = value_of("--replay")? {
+            let replay = Replay::load(path)?;
+            let seed = replay.seed();
+            return Ok((Input::Replay(replay), seed));
+        }
+
+        if let Some(path) = value_of("--record")? {
+            let recorder = Recorder::create(path, seed).map_err(ReplayError::Io)?;
+            return Ok((Input::Record(recorder), seed));
+        }
+
+        Ok((Input::Live, seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::backend::{key_down, key_up};
+
+    use super::*;
+
+    /// A writer keeping what is written, readable after the recorder took it.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn recorded_session_is_replayed() {
+        let buffer = SharedBuffer::default();
+        let mut recorder = Recorder::new(Box::new(buffer.clone()), 1234).unwrap();
+        recorder.frame(0.016_666_668).unwrap();
+        recorder.event(&key_down(Keycode::Up)).unwrap();
+        recorder.frame(0.1).unwrap();
+        recorder.event(&key_up(Keycode::Up)).unwrap();
+        recorder.event(&Event::Quit { timestamp: 0 }).unwrap();
+
+        let text = String::from_utf8(buffer.0.borrow().clone()).unwrap();
+        let mut replay = Replay::parse(&text).unwrap();
+        assert_eq!(replay.seed(), 1234);
+
+        let frame = replay.next_frame().unwrap();
+        assert_eq!(frame.dt, 0.016_666_668);
+        assert!(matches!(frame.events[..], [Event::KeyDown { keycode: Some(Keycode::Up), repeat: false, .. }]));
+
+        let frame = replay.next_frame().unwrap();
+        assert_eq!(frame.dt, 0.1);
+        assert!(matches!(frame.events[..], [Event::KeyUp { .. }, Event::Quit { .. }]));
+
+        assert!(replay.next_frame().is_none());
+    }
+
+    #[test]
+    fn invalid_line_is_reported() {
+        let error = Replay::parse("replay 1\nseed 1\nframe 0.1\njump\n").err().unwrap();
+        assert!(matches!(error, ReplayError::Syntax { line: 4, .. }));
+
+        let error = Replay::parse("seed 1\n").err().unwrap();
+        assert!(matches!(error, ReplayError::Syntax { line: 1, .. }));
+    }
+}
@@ -0,0 +1,206 @@
+//! Broad phase of the collisions: find the objects which may touch a rectangle, without testing all of them.
+//!
+//! [`Grid`] indexes moving or few objects by the cells they cover,
+//! [`TileGrid`] is a map of fixed tiles, like the walls of combat.
+//! The candidates they give still have to be tested with [`crate::collide`].
+use std::ops::Range;
+
+use crate::geometry::{AsCircle, AsRect, Rect};
+
+/// Cells of a grid covered by a rectangle, the rectangles out of the grid being in the border cells.
+///
+/// A rectangle on the border of a cell is also in the next one, rounding errors included,
+/// as touching rectangles collide.
+fn cell_range(x: f32, w: f32, x0: f32, cell_w: f32, count: usize) -> Range<usize> {
+    let margin = cell_w * 1e-3;
+    let cell = |x: f32| (((x - x0) / cell_w).floor().max(0.) as usize).min(count - 1);
+    cell(x - margin)..cell(x + w + margin) + 1
+}
+
+fn bounding_rect<T: AsCircle>(object: &T) -> Rect {
+    let circle = object.as_circle();
+    Rect::new(circle.x() - circle.r(), circle.y() - circle.r(), 2. * circle.r(), 2. * circle.r())
+}
+
+/// Uniform grid indexing objects by key.
+///
+/// An object is in all the cells its rectangle covers, so a query only tests the objects of the cells around.
+/// Moving objects are indexed again each frame, after a [`Grid::clear`].
+pub struct Grid<K> {
+    x0: f32,
+    y0: f32,
+    cell_w: f32,
+    cell_h: f32,
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<K>>,
+}
+
+impl<K: Copy + PartialEq> Grid<K> {
+    /// Create an empty grid splitting the bounds in cols × rows cells.
+    pub fn new(bounds: Rect, cols: usize, rows: usize) -> Grid<K> {
+        assert!(cols > 0 && rows > 0, "a grid needs at least one cell");
+        Grid {
+            x0: bounds.x0(),
+            y0: bounds.y0(),
+            cell_w: bounds.w() / cols as f32,
+            cell_h: bounds.h() / rows as f32,
+            cols,
+            rows,
+            cells: (0..cols * rows).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    /// Remove all the objects.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+    }
+
+    /// Index an object by its rectangle.
+    pub fn insert<T: AsRect>(&mut self, key: K, object: &T) {
+        for index in self.cells_of(&object.as_rect()) {
+            self.cells[index].push(key);
+        }
+    }
+
+    /// Index an object by the rectangle around its circle.
+    pub fn insert_circle<T: AsCircle>(&mut self, key: K, object: &T) {
+        self.insert(key, &bounding_rect(object));
+    }
+
+    /// Remove an object, the rectangle being the one it was inserted with.
+    pub fn remove<T: AsRect>(&mut self, key: K, object: &T) {
+        for index in self.cells_of(&object.as_rect()) {
+            self.cells[index].retain(|other| *other != key);
+        }
+    }
+
+    /// Keys of the objects which may touch the rectangle, each given once.
+    pub fn query<T: AsRect>(&self, object: &T) -> Vec<K> {
+        let mut keys = Vec::new();
+        for index in self.cells_of(&object.as_rect()) {
+            for key in &self.cells[index] {
+                if !keys.contains(key) {
+                    keys.push(*key);
+                }
+            }
+        }
+        keys
+    }
+
+    /// Keys of the objects which may touch the circle, each given once.
+    pub fn query_circle<T: AsCircle>(&self, object: &T) -> Vec<K> {
+        self.query(&bounding_rect(object))
+    }
+
+    fn cells_of(&self, rect: &Rect) -> Vec<usize> {
+        let cols = cell_range(rect.x0(), rect.w(), self.x0, self.cell_w, self.cols);
+        let rows = cell_range(rect.y0(), rect.h(), self.y0, self.cell_h, self.rows);
+        rows.flat_map(|row| cols.clone().map(move |col| row * self.cols + col)).collect()
+    }
+}
+
+/// Map of tiles of the same size, each being solid or empty.
+#[derive(Clone)]
+pub struct TileGrid {
+    x0: f32,
+    y0: f32,
+    tile_w: f32,
+    tile_h: f32,
+    cols: usize,
+    rows: usize,
+    tiles: Vec<bool>,
+}
+
+impl TileGrid {
+    /// Create a grid of empty tiles, splitting the bounds in cols × rows tiles.
+    pub fn new(bounds: Rect, cols: usize, rows: usize) -> TileGrid {
+        assert!(cols > 0 && rows > 0, "a tile grid needs at least one tile");
+        TileGrid {
+            x0: bounds.x0(),
+            y0: bounds.y0(),
+            tile_w: bounds.w() / cols as f32,
+            tile_h: bounds.h() / rows as f32,
+            cols,
+            rows,
+            tiles: vec![false; cols * rows],
+        }
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn set(&mut self, col: usize, row: usize, solid: bool) {
+        self.tiles[row * self.cols + col] = solid;
+    }
+
+    pub fn is_solid(&self, col: usize, row: usize) -> bool {
+        self.tiles[row * self.cols + col]
+    }
+
+    /// Rectangle of a tile, solid or not.
+    pub fn tile_rect(&self, col: usize, row: usize) -> Rect {
+        Rect::new(self.x0 + col as f32 * self.tile_w, self.y0 + row as f32 * self.tile_h, self.tile_w, self.tile_h)
+    }
+
+    /// Rectangles of the solid tiles under an object.
+    pub fn solid_tiles<T: AsRect>(&self, object: &T) -> Vec<Rect> {
+        let rect = object.as_rect();
+        let cols = cell_range(rect.x0(), rect.w(), self.x0, self.tile_w, self.cols);
+        let rows = cell_range(rect.y0(), rect.h(), self.y0, self.tile_h, self.rows);
+
+        let mut tiles = Vec::new();
+        for row in rows {
+            for col in cols.clone() {
+                if self.is_solid(col, row) {
+                    tiles.push(self.tile_rect(col, row));
+                }
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geometry::Circle;
+
+    use super::*;
+
+    #[test]
+    fn grid_only_gives_the_objects_around() {
+        let mut grid = Grid::new(Rect::new(0., 0., 1., 1.), 10, 10);
+        grid.insert(1, &Rect::new(0.05, 0.05, 0.2, 0.01));
+        grid.insert_circle(2, &Circle::new(0.8, 0.8, 0.05));
+        grid.insert(3, &Rect::new(2., 2., 0.1, 0.1));
+
+        assert_eq!(grid.query(&Rect::new(0.21, 0., 0.01, 0.1)), vec![1]);
+        assert_eq!(grid.query_circle(&Circle::new(0.7, 0.7, 0.01)), vec![2]);
+        assert!(grid.query(&Rect::new(0.5, 0.5, 0.01, 0.01)).is_empty());
+
+        // Out of the bounds, the objects are in the border cells.
+        assert_eq!(grid.query(&Rect::new(1.5, 1.5, 0.1, 0.1)), vec![3]);
+
+        grid.remove(1, &Rect::new(0.05, 0.05, 0.2, 0.01));
+        assert!(grid.query(&Rect::new(0., 0., 0.3, 0.1)).is_empty());
+    }
+
+    #[test]
+    fn tile_grid_gives_the_solid_tiles_under_a_rect() {
+        let mut tiles = TileGrid::new(Rect::new(0., 0., 1., 1.), 4, 4);
+        tiles.set(1, 1, true);
+        tiles.set(3, 3, true);
+
+        let under = tiles.solid_tiles(&Rect::new(0.2, 0.2, 0.1, 0.1));
+        assert_eq!(under.len(), 1);
+        assert_eq!((under[0].x0(), under[0].y0()), (0.25, 0.25));
+        assert!(tiles.solid_tiles(&Rect::new(0.6, 0.1, 0.1, 0.1)).is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Texts drawn with a TTF font or a bitmap font, aligned and wrapped in a box, with a shadow or an outline.
+//!
+//! The glyphs of a TTF font are rendered once by [`Assets::glyph`], and the ones of a [`BitmapFont`] are cut in images.
+//! A [`Text`] assembles its glyphs in a single image, again only when its text changes,
+//! so a score drawn at each frame is not rendered at each frame.
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::rc::Rc;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::{Point, Rect};
+use sdl2::render::BlendMode;
+use sdl2::surface::Surface;
+
+use crate::assets::{Assets, FontId, Image};
+use crate::error::{Error, Result};
+use crate::graphics::{Camera, Renderer};
+
+/// Glyphs cut in images, like digits drawn by hand.
+///
+/// The characters without a glyph, like the spaces, are left blank.
+#[derive(Clone, Default)]
+pub struct BitmapFont {
+    glyphs: HashMap<char, (Image, Rect)>,
+    height: u32,
+    spacing: u32,
+}
+
+impl BitmapFont {
+    pub fn new() -> BitmapFont {
+        BitmapFont::default()
+    }
+
+    /// A font whose glyphs are whole images, one per character, in order.
+    pub fn from_images(chars: &str, images: Vec<Image>) -> BitmapFont {
+        assert_eq!(chars.chars().count(), images.len(), "a bitmap font needs an image per character");
+        let mut font = BitmapFont::new();
+        for (ch, image) in chars.chars().zip(images) {
+            let rect = image.rect();
+            font.add_glyph(ch, image, rect);
+        }
+        font
+    }
+
+    /// A font cut in an image in cells of the same size, row by row, one per character.
+    pub fn from_grid(image: Image, chars: &str, cell_width: u32, cell_height: u32) -> BitmapFont {
+        let columns = image.width() / cell_width;
+        let mut font = BitmapFont::new();
+        for (i, ch) in chars.chars().enumerate() {
+            let (col, row) = (i as u32 % columns, i as u32 / columns);
+            let rect = Rect::new((col * cell_width) as i32, (row * cell_height) as i32, cell_width, cell_height);
+            font.add_glyph(ch, image.clone(), rect);
+        }
+        font
+    }
+
+    /// Draw a character with a part of an image.
+    pub fn add_glyph(&mut self, ch: char, image: Image, rect: Rect) {
+        self.height = self.height.max(rect.height());
+        self.glyphs.insert(ch, (image, rect));
+    }
+
+    /// Space between the glyphs, in pixels.
+    pub fn with_spacing(mut self, spacing: u32) -> BitmapFont {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Height of the lines, the one of the highest glyph.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+/// Font of a text.
+#[derive(Clone)]
+pub enum Font {
+    Ttf(FontId),
+    Bitmap(Rc<BitmapFont>),
+}
+
+impl Font {
+    /// The glyph of a character, if the font has one.
+    fn glyph(&self, ch: char, assets: &Assets) -> Result<Option<(Image, Rect)>> {
+        match self {
+            Font::Ttf(font) => {
+                let image = assets.glyph(*font, ch)?;
+                let rect = image.rect();
+                Ok(Some((image, rect)))
+            }
+            Font::Bitmap(font) => Ok(font.glyphs.get(&ch).cloned()),
+        }
+    }
+
+    fn spacing(&self) -> u32 {
+        match self {
+            Font::Ttf(_) => 0,
+            Font::Bitmap(font) => font.spacing,
+        }
+    }
+}
+
+/// Where the lines of a text are in its box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// How a text is drawn.
+#[derive(Clone)]
+pub struct TextStyle {
+    font: Font,
+    color: Color,
+    align: Align,
+    max_width: Option<u32>,
+    line_spacing: u32,
+    shadow: Option<(Point, Color)>,
+    outline: Option<(u32, Color)>,
+}
+
+impl TextStyle {
+    /// A text in white, aligned on the left, whose lines are never wrapped.
+    pub fn new(font: Font) -> TextStyle {
+        TextStyle {
+            font,
+            color: Color::WHITE,
+            align: Align::Left,
+            max_width: None,
+            line_spacing: 0,
+            shadow: None,
+            outline: None,
+        }
+    }
+
+    /// Multiply the colors of the glyphs by a color, which gives the color of the white glyphs.
+    pub fn color(mut self, color: Color) -> TextStyle {
+        self.color = color;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> TextStyle {
+        self.align = align;
+        self
+    }
+
+    /// Cut the lines between their words, so they are not wider than a width in pixels.
+    pub fn wrap(mut self, max_width: u32) -> TextStyle {
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Space added between the lines, in pixels.
+    pub fn line_spacing(mut self, spacing: u32) -> TextStyle {
+        self.line_spacing = spacing;
+        self
+    }
+
+    /// Draw the text a second time under itself, shifted and in another color.
+    pub fn shadow(mut self, offset: Point, color: Color) -> TextStyle {
+        self.shadow = Some((offset, color));
+        self
+    }
+
+    /// Surround the glyphs with a border of a width in pixels.
+    pub fn outline(mut self, width: u32, color: Color) -> TextStyle {
+        self.outline = Some((width, color));
+        self
+    }
+
+    /// Space needed around the glyphs by the shadow and the outline.
+    fn margin(&self) -> i32 {
+        let outline = self.outline.map_or(0, |(width, _)| width as i32);
+        let shadow = self.shadow.map_or(0, |(offset, _)| offset.x().abs().max(offset.y().abs()));
+        outline + shadow
+    }
+
+    /// Offsets and colors of the copies of the glyphs drawn for the text, from the bottom to the top.
+    fn passes(&self) -> Vec<(Point, Color)> {
+        let mut passes = Vec::new();
+        if let Some((offset, color)) = self.shadow {
+            passes.push((offset, color));
+        }
+        if let Some((width, color)) = self.outline {
+            let width = width as i32;
+            for dy in -width..=width {
+                for dx in -width..=width {
+                    if (dx, dy) != (0, 0) {
+                        passes.push((Point::new(dx, dy), color));
+                    }
+                }
+            }
+        }
+        passes.push((Point::new(0, 0), self.color));
+        passes
+    }
+}
+
+/// Lines of a text, cut at its line breaks, and between its words to fit in the max width.
+///
+/// A word wider than the max width gets a line of its own.
+fn wrap_lines(text: &str, max_width: Option<u32>, width_of: impl Fn(&str) -> u32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let max_width = match max_width {
+            Some(max_width) => max_width,
+            None => {
+                lines.push(paragraph.to_string());
+                continue;
+            }
+        };
+
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let longer = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if !line.is_empty() && width_of(&longer) > max_width {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = longer;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Assemble the glyphs of a text in an image, with the margin needed by the effects around its box.
+fn render(text: &str, style: &TextStyle, assets: &Assets) -> Result<Image> {
+    let error = |reason: String| Error::Render { text: text.to_string(), reason };
+
+    // The glyphs are converted once, to be copied with the colors of each pass.
+    let mut glyphs: HashMap<char, Option<(Surface<'static>, Rect)>> = HashMap::new();
+    for ch in text.chars().filter(|ch| *ch != '\n') {
+        if let Entry::Vacant(entry) = glyphs.entry(ch) {
+            let glyph = match style.font.glyph(ch, assets)? {
+                Some((image, rect)) => {
+                    let mut surface = image.surface().convert_format(PixelFormatEnum::ARGB8888).map_err(error)?;
+                    surface.set_blend_mode(BlendMode::Blend).map_err(error)?;
+                    Some((surface, rect))
+                }
+                None => None,
+            };
+            entry.insert(glyph);
+        }
+    }
+
+    let line_height = match &style.font {
+        Font::Ttf(_) => glyphs.values().flatten().map(|(_, rect)| rect.height()).max().unwrap_or(0),
+        Font::Bitmap(font) => font.height,
+    };
+    let spacing = style.font.spacing();
+    let advances: HashMap<char, u32> = glyphs
+        .iter()
+        .map(|(ch, glyph)| (*ch, glyph.as_ref().map_or(line_height / 2, |(_, rect)| rect.width())))
+        .collect();
+    let advance = |ch: char| advances.get(&ch).copied().unwrap_or(0);
+    let width_of = |line: &str| line.chars().map(|ch| advance(ch) + spacing).sum::<u32>().saturating_sub(spacing);
+
+    let lines = wrap_lines(text, style.max_width, width_of);
+    let box_width = style.max_width.unwrap_or_else(|| lines.iter().map(|line| width_of(line)).max().unwrap_or(0));
+    let box_height = lines.len() as u32 * (line_height + style.line_spacing) - style.line_spacing;
+
+    let margin = style.margin();
+    let mut surface = Surface::new(
+        (box_width + 2 * margin as u32).max(1),
+        (box_height + 2 * margin as u32).max(1),
+        PixelFormatEnum::ARGB8888,
+    ).map_err(error)?;
+
+    for (offset, color) in style.passes() {
+        for (i, line) in lines.iter().enumerate() {
+            let free_space = box_width.saturating_sub(width_of(line)) as i32;
+            let mut x = margin + offset.x() + match style.align {
+                Align::Left => 0,
+                Align::Center => free_space / 2,
+                Align::Right => free_space,
+            };
+            let y = margin + offset.y() + (i as u32 * (line_height + style.line_spacing)) as i32;
+            for ch in line.chars() {
+                if let Some(Some((glyph, rect))) = glyphs.get_mut(&ch) {
+                    glyph.set_color_mod(color);
+                    glyph.blit(*rect, &mut surface, Rect::new(x, y, rect.width(), rect.height())).map_err(error)?;
+                }
+                x += (advance(ch) + spacing) as i32;
+            }
+        }
+    }
+    Ok(Image::new(surface))
+}
+
+/// A text in a box whose top is at a position in pixels,
+/// the position being on the left side, the center or the right side of the box depending on the alignment.
+///
+/// The text is assembled in an image once, and again only when it changes.
+pub struct Text {
+    style: TextStyle,
+    text: String,
+    image: Image,
+    position: Point,
+}
+
+impl Text {
+    pub fn new(text: &str, position: Point, style: TextStyle, assets: &Assets) -> Result<Text> {
+        let image = render(text, &style, assets)?;
+        Ok(Text { style, text: text.to_string(), image, position })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Change the text, assembling it only if it is not the current one.
+    pub fn set_text(&mut self, text: &str, assets: &Assets) -> Result<()> {
+        if text != self.text {
+            self.image = render(text, &self.style, assets)?;
+            self.text = text.to_string();
+        }
+        Ok(())
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: Point) {
+        self.position = position;
+    }
+
+    /// Size of the box of the text, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        let margin = 2 * self.style.margin() as u32;
+        (self.image.width() - margin, self.image.height() - margin)
+    }
+
+    /// Top left corner of the image, with the margin of the effects.
+    fn origin(&self) -> Point {
+        let width = self.size().0 as i32;
+        let x = match self.style.align {
+            Align::Left => self.position.x(),
+            Align::Center => self.position.x() - width / 2,
+            Align::Right => self.position.x() - width,
+        };
+        let margin = self.style.margin();
+        Point::new(x - margin, self.position.y() - margin)
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
+        let origin = self.origin();
+        let rect = self.image.rect();
+        canvas.copy_image(&self.image, rect, Rect::new(origin.x(), origin.y(), rect.width(), rect.height()), 0.);
+    }
+
+    /// Draw the text scaled with the viewport of the camera, its position being in pixels of the design size.
+    pub fn draw_scaled(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        let origin = camera.to_screen_pixel(self.origin());
+        let rect = self.image.rect();
+        let scale = camera.pixel_scale();
+        let (width, height) = ((rect.width() as f32 * scale) as u32, (rect.height() as f32 * scale) as u32);
+        canvas.copy_image(&self.image, rect, Rect::new(origin.x(), origin.y(), width, height), 0.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_wrapped_between_words() {
+        let width_of = |line: &str| line.len() as u32;
+        assert_eq!(wrap_lines("a long sentence\nto wrap", Some(6), width_of), vec!["a long", "sentence", "to", "wrap"]);
+        assert_eq!(wrap_lines("a long sentence\nto wrap", Some(9), width_of), vec!["a long", "sentence", "to wrap"]);
+        assert_eq!(wrap_lines("a long sentence", None, width_of), vec!["a long sentence"]);
+    }
+}
@@ -0,0 +1,465 @@
+//! Interpolation of values over time, for the motion of the entities and the effects of the interface.
+//!
+//! An [`Ease`] shapes the progress of a [`Tween`], which goes from a value to another in a duration,
+//! after an optional delay, once, in loop or back and forth.
+//! Tweens are chained in a [`Sequence`] or played together in a [`Parallel`] group,
+//! all of them being advanced by the `dt` of the game with [`Animate::update`].
+use std::f32::consts::PI;
+
+use sdl2::pixels::Color;
+
+use crate::math::Vec2;
+
+/// Easing curves, mapping the progress of a tween from 0 to 1 to the part of the way done.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    /// Goes a bit beyond the end before coming back to it.
+    BackOut,
+    /// Oscillates around the end before settling on it.
+    ElasticOut,
+    /// Bounces on the end like a falling ball.
+    BounceOut,
+}
+
+impl Ease {
+    /// Part of the way done at a progress t, 0 at the start and 1 at the end.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1. - (1. - t) * (1. - t),
+            Ease::QuadInOut => if t < 0.5 { 2. * t * t } else { 1. - (-2. * t + 2.).powi(2) / 2. },
+            Ease::CubicIn => t * t * t,
+            Ease::CubicOut => 1. - (1. - t).powi(3),
+            Ease::CubicInOut => if t < 0.5 { 4. * t * t * t } else { 1. - (-2. * t + 2.).powi(3) / 2. },
+            Ease::SineIn => 1. - (t * PI / 2.).cos(),
+            Ease::SineOut => (t * PI / 2.).sin(),
+            Ease::SineInOut => -((t * PI).cos() - 1.) / 2.,
+            Ease::BackOut => {
+                let c = 1.70158;
+                1. + (c + 1.) * (t - 1.).powi(3) + c * (t - 1.).powi(2)
+            }
+            Ease::ElasticOut => {
+                if t == 0. || t == 1. {
+                    t
+                } else {
+                    2f32.powf(-10. * t) * ((t * 10. - 0.75) * 2. * PI / 3.).sin() + 1.
+                }
+            }
+            Ease::BounceOut => {
+                let (n, d) = (7.5625, 2.75);
+                if t < 1. / d {
+                    n * t * t
+                } else if t < 2. / d {
+                    let t = t - 1.5 / d;
+                    n * t * t + 0.75
+                } else if t < 2.5 / d {
+                    let t = t - 2.25 / d;
+                    n * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d;
+                    n * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+/// Values which can be interpolated: t is 0 at the start and 1 at the end, and can go beyond for some eases.
+pub trait Lerp: Copy {
+    fn lerp(self, end: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, end: f32, t: f32) -> f32 {
+        self + (end - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(self, end: Vec2, t: f32) -> Vec2 {
+        self + (end - self) * t
+    }
+}
+
+impl Lerp for Color {
+    fn lerp(self, end: Color, t: f32) -> Color {
+        let channel = |start: u8, end: u8| (start as f32).lerp(end as f32, t).round().clamp(0., 255.) as u8;
+        Color::RGBA(channel(self.r, end.r), channel(self.g, end.g), channel(self.b, end.b), channel(self.a, end.a))
+    }
+}
+
+/// How many times a tween or a sequence is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Times(u32),
+    Forever,
+}
+
+impl Repeat {
+    fn is_last(self, cycle: u32) -> bool {
+        match self {
+            Repeat::Times(n) => cycle + 1 >= n,
+            Repeat::Forever => false,
+        }
+    }
+}
+
+type Callback = Box<dyn FnMut()>;
+
+/// Something changing over time, like a tween or a group of tweens.
+pub trait Animate {
+    type Value;
+
+    /// Advance of dt, returning the part of dt left over once finished.
+    fn update(&mut self, dt: f32) -> f32;
+
+    /// Current value.
+    fn value(&self) -> Self::Value;
+
+    fn is_finished(&self) -> bool;
+
+    /// Play again from the start.
+    fn restart(&mut self);
+}
+
+impl<A: Animate + ?Sized> Animate for Box<A> {
+    type Value = A::Value;
+
+    fn update(&mut self, dt: f32) -> f32 {
+        (**self).update(dt)
+    }
+
+    fn value(&self) -> A::Value {
+        (**self).value()
+    }
+
+    fn is_finished(&self) -> bool {
+        (**self).is_finished()
+    }
+
+    fn restart(&mut self) {
+        (**self).restart()
+    }
+}
+
+/// A value going from a start to an end in a duration, shaped by an ease.
+///
+/// A yoyo tween goes back to its start on every other cycle.
+pub struct Tween<T> {
+    start: T,
+    end: T,
+    duration: f32,
+    ease: Ease,
+    delay: f32,
+    repeat: Repeat,
+    yoyo: bool,
+    waited: f32,
+    time: f32,
+    cycle: u32,
+    is_finished: bool,
+    on_complete: Option<Callback>,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// A tween played once, without delay.
+    pub fn new(start: T, end: T, duration: f32, ease: Ease) -> Tween<T> {
+        assert!(duration > 0., "a tween must last some time");
+        Tween {
+            start,
+            end,
+            duration,
+            ease,
+            delay: 0.,
+            repeat: Repeat::Times(1),
+            yoyo: false,
+            waited: 0.,
+            time: 0.,
+            cycle: 0,
+            is_finished: false,
+            on_complete: None,
+        }
+    }
+
+    /// A value kept for a duration, like a pause in a sequence.
+    pub fn hold(value: T, duration: f32) -> Tween<T> {
+        Tween::new(value, value, duration, Ease::Linear)
+    }
+
+    /// Wait some time before starting, the value being the start one meanwhile.
+    pub fn delay(mut self, delay: f32) -> Tween<T> {
+        self.delay = delay;
+        self
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Tween<T> {
+        assert!(repeat != Repeat::Times(0), "a tween must be played at least once");
+        self.repeat = repeat;
+        self
+    }
+
+    /// Go back to the start on every other cycle.
+    pub fn yoyo(mut self) -> Tween<T> {
+        self.yoyo = true;
+        self
+    }
+
+    /// Call a function once the tween is finished, which never happens when it is repeated forever.
+    pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Tween<T> {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Progress of the current cycle, from 0 to 1, after the ease.
+    pub fn progress(&self) -> f32 {
+        let t = self.time / self.duration;
+        let t = if self.yoyo && self.cycle % 2 == 1 { 1. - t } else { t };
+        self.ease.apply(t)
+    }
+}
+
+impl<T: Lerp> Animate for Tween<T> {
+    type Value = T;
+
+    fn update(&mut self, dt: f32) -> f32 {
+        if self.is_finished {
+            return dt;
+        }
+
+        let waiting = (self.delay - self.waited).min(dt).max(0.);
+        self.waited += waiting;
+        self.time += dt - waiting;
+        while self.time >= self.duration {
+            if self.repeat.is_last(self.cycle) {
+                let left = self.time - self.duration;
+                self.time = self.duration;
+                self.is_finished = true;
+                if let Some(callback) = &mut self.on_complete {
+                    callback();
+                }
+                return left;
+            }
+            self.time -= self.duration;
+            self.cycle += 1;
+        }
+        0.
+    }
+
+    fn value(&self) -> T {
+        self.start.lerp(self.end, self.progress())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    fn restart(&mut self) {
+        self.waited = 0.;
+        self.time = 0.;
+        self.cycle = 0;
+        self.is_finished = false;
+    }
+}
+
+/// Steps played one after the other, the time left by a step going to the next one.
+pub struct Sequence<A> {
+    steps: Vec<A>,
+    current: usize,
+    repeat: Repeat,
+    cycle: u32,
+    is_finished: bool,
+    on_complete: Option<Callback>,
+}
+
+impl<A: Animate> Sequence<A> {
+    /// A sequence played once.
+    ///
+    /// Panics if there is no step.
+    pub fn new(steps: Vec<A>) -> Sequence<A> {
+        assert!(!steps.is_empty(), "a sequence must have a step");
+        Sequence {
+            steps,
+            current: 0,
+            repeat: Repeat::Times(1),
+            cycle: 0,
+            is_finished: false,
+            on_complete: None,
+        }
+    }
+
+    pub fn repeat(mut self, repeat: Repeat) -> Sequence<A> {
+        assert!(repeat != Repeat::Times(0), "a sequence must be played at least once");
+        self.repeat = repeat;
+        self
+    }
+
+    /// Call a function once the last step of the last cycle is finished.
+    pub fn on_complete<F: FnMut() + 'static>(mut self, callback: F) -> Sequence<A> {
+        self.on_complete = Some(Box::new(callback));
+        self
+    }
+
+    /// Index of the step played.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+}
+
+impl<A: Animate> Animate for Sequence<A> {
+    type Value = A::Value;
+
+    fn update(&mut self, mut dt: f32) -> f32 {
+        while !self.is_finished {
+            dt = self.steps[self.current].update(dt);
+            if !self.steps[self.current].is_finished() {
+                return 0.;
+            }
+            if self.current + 1 < self.steps.len() {
+                self.current += 1;
+            } else if self.repeat.is_last(self.cycle) {
+                self.is_finished = true;
+                if let Some(callback) = &mut self.on_complete {
+                    callback();
+                }
+            } else {
+                self.cycle += 1;
+                self.current = 0;
+                self.steps.iter_mut().for_each(Animate::restart);
+            }
+        }
+        dt
+    }
+
+    fn value(&self) -> A::Value {
+        self.steps[self.current].value()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    fn restart(&mut self) {
+        self.current = 0;
+        self.cycle = 0;
+        self.is_finished = false;
+        self.steps.iter_mut().for_each(Animate::restart);
+    }
+}
+
+/// Two animations played together, finished once both of them are, like a fade along with a slide.
+///
+/// Groups of more animations are made by nesting groups.
+pub struct Parallel<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Animate, B: Animate> Parallel<A, B> {
+    pub fn new(first: A, second: B) -> Parallel<A, B> {
+        Parallel { first, second }
+    }
+}
+
+impl<A: Animate, B: Animate> Animate for Parallel<A, B> {
+    type Value = (A::Value, B::Value);
+
+    fn update(&mut self, dt: f32) -> f32 {
+        let left = self.first.update(dt).min(self.second.update(dt));
+        if self.is_finished() { left } else { 0. }
+    }
+
+    fn value(&self) -> (A::Value, B::Value) {
+        (self.first.value(), self.second.value())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.first.is_finished() && self.second.is_finished()
+    }
+
+    fn restart(&mut self) {
+        self.first.restart();
+        self.second.restart();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn eases_go_from_0_to_1() {
+        let eases = [
+            Ease::Linear, Ease::QuadIn, Ease::QuadOut, Ease::QuadInOut, Ease::CubicIn, Ease::CubicOut, Ease::CubicInOut,
+            Ease::SineIn, Ease::SineOut, Ease::SineInOut, Ease::BackOut, Ease::ElasticOut, Ease::BounceOut,
+        ];
+        for ease in eases.iter() {
+            assert!(ease.apply(0.).abs() < 1e-5, "{:?}", ease);
+            assert!((ease.apply(1.) - 1.).abs() < 1e-5, "{:?}", ease);
+        }
+        assert!(Ease::QuadIn.apply(0.5) < 0.5);
+        assert!(Ease::QuadOut.apply(0.5) > 0.5);
+        assert!(Ease::BackOut.apply(0.8) > 1.);
+    }
+
+    #[test]
+    fn tweens_wait_their_delay_and_go_back_and_forth() {
+        let completed = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&completed);
+        let mut tween = Tween::new(0., 10., 1., Ease::Linear)
+            .delay(0.5)
+            .yoyo()
+            .repeat(Repeat::Times(2))
+            .on_complete(move || counter.set(counter.get() + 1));
+
+        tween.update(0.5);
+        assert_eq!(tween.value(), 0.);
+        tween.update(0.25);
+        assert_eq!(tween.value(), 2.5);
+        tween.update(1.);
+        assert_eq!(tween.value(), 7.5);
+        assert!(!tween.is_finished());
+
+        assert_eq!(tween.update(1.), 0.25);
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), 0.);
+        assert_eq!(completed.get(), 1);
+
+        let mut color = Tween::new(Color::WHITE, Color::RGBA(255, 255, 255, 0), 2., Ease::Linear);
+        color.update(1.);
+        assert_eq!(color.value(), Color::RGBA(255, 255, 255, 128));
+    }
+
+    #[test]
+    fn sequences_pass_the_time_left_to_their_next_step() {
+        let slide = Sequence::new(vec![
+            Tween::new(Vec2::ZERO, Vec2::new(1., 0.), 1., Ease::Linear),
+            Tween::hold(Vec2::new(1., 0.), 1.),
+            Tween::new(Vec2::new(1., 0.), Vec2::new(1., 1.), 1., Ease::Linear),
+        ]);
+        let fade = Tween::new(1., 0., 2., Ease::Linear);
+        let mut group = Parallel::new(slide, fade);
+
+        group.update(2.5);
+        assert_eq!(group.value(), (Vec2::new(1., 0.5), 0.));
+        assert!(!group.is_finished());
+        assert_eq!(group.update(1.), 0.5);
+        assert!(group.is_finished());
+
+        group.restart();
+        assert_eq!(group.value(), (Vec2::ZERO, 1.));
+    }
+}
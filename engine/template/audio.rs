@@ -1,22 +1,26 @@
-use sdl2::mixer::{Chunk, Channel};
-use engine::audio::init_audio;
+use engine::audio::{Mixer, SoundManager};
+use engine::error::Error;
+use engine::math::Vec2;
+use engine::res;
+
 use crate::logic::Logic;
 
-pub const CHANNEL_COUNT : i32 = 6;
+pub const CHANNEL_COUNT: i32 = 6;
+pub const SOUND_BANK_PATH: &str = res!("sounds.bank");
 
+/// The sounds of the game, played by name from its bank.
 pub struct Audio {
-
+    sounds: SoundManager,
 }
 
 impl Audio {
-    pub fn new() -> Audio {
-        init_audio(CHANNEL_COUNT);
-
-        Audio {
-        }
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.load_bank(SOUND_BANK_PATH)?;
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+        Ok(Audio { sounds })
     }
 
-    pub fn update(&mut self, logic : &Logic){
+    pub fn update(&mut self, logic: &Logic) {
     }
-
 }
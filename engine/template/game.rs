@@ -0,0 +1,57 @@
+use sdl2::event::Event;
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::graphics::Window;
+
+use crate::audio::Audio;
+use crate::collide::check_collision;
+use crate::event::handle_event;
+use crate::graphics::Graphics;
+use crate::logic::Logic;
+
+/// The game, gathering the logic, the audio and the graphics for the game loop.
+pub struct Template<'a> {
+    logic: Logic,
+    audio: Audio,
+    graphics: Graphics,
+    assets: Assets<'a>,
+}
+
+impl Template<'_> {
+    pub fn new(audio: Audio, canvas_width: u32, canvas_height: u32, ttf_context: &Sdl2TtfContext) -> Result<Template<'_>, Error> {
+        let mut assets = Assets::new(ttf_context);
+        Ok(Template {
+            logic: Logic::new(),
+            audio,
+            graphics: Graphics::new(canvas_width, canvas_height, &mut assets)?,
+            assets,
+        })
+    }
+}
+
+impl Game for Template<'_> {
+    fn handle_event(&mut self, event: Event) {
+        handle_event(event, &mut self.logic, &self.audio);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.logic.update(dt);
+        self.audio.update(&self.logic);
+    }
+
+    fn collide(&mut self, dt: f32) {
+        check_collision(&mut self.logic, dt, &self.audio);
+    }
+
+    fn render(&mut self, window: &mut Window, _alpha: f32) {
+        self.graphics.update(&self.logic, window, &self.assets);
+        self.graphics.draw(window);
+    }
+
+    fn is_over(&self) -> bool {
+        self.logic.is_over()
+    }
+}
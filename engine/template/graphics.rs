@@ -1,11 +1,11 @@
 use sdl2::pixels::Color;
 use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
-use sdl2::ttf::Sdl2TtfContext;
 
+use engine::assets::Assets;
+use engine::error::Error;
 use engine::geometry;
 use engine::geometry::AsRect;
-use engine::graphics::{RectSprite, RenderedString, Sprite, Window};
+use engine::graphics::{RectSprite, Renderer, RenderedString, Sprite, Window};
 
 use crate::logic::Logic;
 use crate::logic;
@@ -17,13 +17,13 @@ pub struct Graphics<> {
 
 impl Graphics {
     /// Init the dynamic elements required to draw the game
-    pub fn new(canvas_width: u32, canvas_height: u32, ttf_context: &Sdl2TtfContext) -> Graphics {
-        Graphics {
-        }
+    pub fn new(canvas_width: u32, canvas_height: u32, assets: &mut Assets) -> Result<Graphics, Error> {
+        Ok(Graphics {
+        })
     }
 
     /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window, ttf_context: &Sdl2TtfContext) {
+    pub fn update(&mut self, logic: &Logic, window: &Window, assets: &Assets) {
     }
 
     /// Draw the game.
@@ -33,7 +33,7 @@ impl Graphics {
     pub fn draw(&self, window: &mut Window) {
         window.clear();
 
-        let canvas = &mut window.canvas;
+        let canvas = window.canvas.as_mut();
 
 
         canvas.present();
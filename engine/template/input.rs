@@ -1,6 +0,0 @@
-use crate::logic::Logic;
-use sdl2::keyboard::Scancode;
-
-pub fn handle_input(event_pump: &sdl2::EventPump, logic: &mut Logic){
-
-}
\ No newline at end of file
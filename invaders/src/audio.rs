@@ -1,19 +1,82 @@
-use sdl2::mixer::{Channel, Chunk};
+use engine::audio::music::Sequencer;
+use engine::audio::{Mixer, SoundManager};
+use engine::error::Error;
+use engine::res;
 
-use engine::audio::init_audio;
-
-use crate::logic::Logic;
+use crate::logic::{ALIEN_COUNT, Logic};
 
 pub const CHANNEL_COUNT: i32 = 6;
+pub const SOUND_BANK_PATH: &str = res!("sounds.bank");
+
+pub const MARCH_STEPS: [&str; 6] = ["march_1", "march_2", "march_3", "march_4", "march_5", "march_6"];
+/// Tempo of the march at the start of the game, in beats per minute.
+pub const MARCH_BPM: f32 = 60.;
+/// Tempo of the march when a single alien is left.
+pub const MARCH_MAX_BPM: f32 = 240.;
 
-pub struct Audio {}
+/// The tempo of the march, going from the starting one to the maximum one as the aliens are destroyed.
+pub fn march_bpm(alien_count: usize) -> f32 {
+    let destroyed = ALIEN_COUNT.saturating_sub(alien_count.max(1)) as f32;
+    MARCH_BPM + (MARCH_MAX_BPM - MARCH_BPM) * destroyed / (ALIEN_COUNT - 1) as f32
+}
+
+/// The invaders march to a tempo going faster as they are destroyed.
+pub struct Audio {
+    sounds: SoundManager,
+    march: Sequencer,
+}
 
 impl Audio {
-    pub fn new() -> Audio {
-        init_audio(CHANNEL_COUNT);
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.load_bank(SOUND_BANK_PATH)?;
+        let mut march = Sequencer::new(&MARCH_STEPS, MARCH_BPM);
+        march.start();
+        Ok(Audio { sounds, march })
+    }
+
+    pub fn play_alien_destroyed(&self) {
+        self.sounds.play("alien_destroyed");
+    }
 
-        Audio {}
+    pub fn update(&mut self, dt: f32, logic: &Logic) {
+        let bpm = march_bpm(logic.alien_count());
+        if bpm != self.march.bpm() {
+            self.march.set_bpm(bpm);
+        }
+        self.march.update(dt, &self.sounds);
+        self.sounds.update_music(dt);
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::audio::NullMixer;
+
+    use super::*;
 
-    pub fn update(&mut self, _logic: &Logic) {}
+    #[test]
+    fn march_speeds_up_as_the_aliens_are_destroyed() {
+        assert_eq!(march_bpm(ALIEN_COUNT), MARCH_BPM);
+        assert_eq!(march_bpm(1), MARCH_MAX_BPM);
+
+        let mixer = NullMixer::new();
+        let record = mixer.record();
+        let mut audio = Audio::new(Box::new(mixer)).unwrap();
+        let mut logic = Logic::new();
+        let steps_during_10_seconds = |audio: &mut Audio, logic: &Logic| {
+            let before = record.borrow().played.len();
+            for _ in 0..100 {
+                audio.update(0.1, logic);
+            }
+            record.borrow().played.len() - before
+        };
+        // The first step is played at once.
+        assert_eq!(steps_during_10_seconds(&mut audio, &logic), 11);
+
+        for index in 0..ALIEN_COUNT - 1 {
+            logic.destroy_alien(index);
+        }
+        assert_eq!(steps_during_10_seconds(&mut audio, &logic), 40);
+    }
 }
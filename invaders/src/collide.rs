@@ -4,4 +4,19 @@ use engine::geometry::AsRect;
 use crate::audio::Audio;
 use crate::logic::Logic;
 
-pub fn check_collision(_logic: &mut Logic, _dt: f32, _audio: &Audio) {}
\ No newline at end of file
+/// The missile explodes on the top of the board, or on the first alien it hits, destroying it.
+pub fn check_collision(logic: &mut Logic, _dt: f32, audio: &Audio) {
+    if logic.spaceship.missile.is_destroyed() {
+        return;
+    }
+
+    let missile = logic.spaceship.missile.as_rect();
+    let hit = logic.aliens.iter().position(|alien| !alien.is_destroyed() && collide(&missile, &alien.as_rect()).is_some());
+    if let Some(index) = hit {
+        audio.play_alien_destroyed();
+        logic.destroy_alien(index);
+        logic.spaceship.missile.destroy();
+    } else if missile.y0() <= 0. {
+        logic.spaceship.missile.destroy();
+    }
+}
@@ -1,67 +1,243 @@
+use std::rc::Rc;
+
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
-use sdl2::ttf::Sdl2TtfContext;
+use sdl2::rect::{Point, Rect};
 
+use engine::animation::{Animation, Clip, Playback, SpriteSheet};
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::interpolate;
 use engine::geometry;
 use engine::geometry::AsRect;
-use engine::graphics::{RectSprite, RenderedString, Sprite, Window};
+use engine::graphics::{Camera, RectSprite, Renderer, Sprite, Window};
+use engine::math::Vec2;
+use engine::res;
+use engine::text::{BitmapFont, Font, Text, TextStyle};
 
-use crate::logic::{Logic, SPACESHIP_HEIGHT, SPACESHIP_WIDTH};
+use crate::logic::{ALIEN_HEIGHT, ALIEN_WIDTH, Logic, SPACESHIP_HEIGHT, SPACESHIP_WIDTH};
 use crate::logic;
 
-pub const SPACESHIP_SPRITE_PATH: &str = "res/sprite/spaceship.bmp";
+pub const SPACESHIP_SPRITE_PATH: &str = res!("sprite/spaceship.bmp");
 pub const MISSILE_COLOR: Color = Color::GREEN;
+/// Farthest the missile is drawn from its previous position, it has been fired again when it moves more in a step.
+pub const MISSILE_MAX_STEP: f32 = 0.1;
+pub const MISSILE_EXPLOSION_SPRITE_PATH: &str = res!("sprite/missile_explosion.bmp");
+pub const MISSILE_EXPLOSION_DURATION: f32 = 0.2;
+
+/// The two images the aliens of each row of the formation switch between, from the top.
+pub const ALIEN_ROW_SPRITE_PATHS: [[&str; 2]; 5] = [
+    [res!("sprite/alien1_frame1.bmp"), res!("sprite/alien1_frame2.bmp")],
+    [res!("sprite/alien2_frame1.bmp"), res!("sprite/alien2_frame2.bmp")],
+    [res!("sprite/alien2_frame1.bmp"), res!("sprite/alien2_frame2.bmp")],
+    [res!("sprite/alien3_frame1.bmp"), res!("sprite/alien3_frame2.bmp")],
+    [res!("sprite/alien3_frame1.bmp"), res!("sprite/alien3_frame2.bmp")],
+];
+/// Time each image of the aliens is shown.
+pub const ALIEN_FRAME_DURATION: f32 = 0.5;
+
+pub const SCORE_LABEL_SPRITE_PATH: &str = res!("sprite/score.bmp");
+pub const DIGIT_SPRITE_DIRECTORY: &str = res!("sprite");
+/// Top left corner of the score label, in pixels.
+pub const SCORE_POSITION: (i32, i32) = (10, 10);
+pub const SCORE_LABEL_GAP: i32 = 10;
+pub const SCORE_DIGIT_COUNT: usize = 4;
+pub const SCORE_DIGIT_SPACING: u32 = 2;
 
-pub struct Spaceship<'a> {
-    spaceship: Sprite<'a>,
+/// The spaceship and its missile, which explodes where it is destroyed.
+///
+/// The missile is drawn between its positions before and after the last step.
+pub struct Spaceship {
+    spaceship: Sprite,
     missile: RectSprite,
+    previous_missile: Vec2,
+    is_missile_flying: bool,
+    explosion: Sprite,
+    /// Time left before the explosion of the missile is hidden.
+    explosion_time_left: f32,
 }
 
-impl Spaceship<'_> {
-    pub fn new(cw: u32, ch: u32) -> Spaceship<'static> {
+impl Spaceship {
+    pub fn new(cw: u32, ch: u32, assets: &mut Assets) -> Result<Spaceship, Error> {
         let sprite_rect = Rect::new(0, 0, (SPACESHIP_WIDTH * cw as f32) as u32, (SPACESHIP_HEIGHT * ch as f32) as u32);
-        let sprite = Sprite::simple_new(SPACESHIP_SPRITE_PATH, sprite_rect);
-        Spaceship {
+        let sprite = Sprite::simple_new(assets.image(SPACESHIP_SPRITE_PATH)?, sprite_rect);
+
+        let explosion_image = assets.image(MISSILE_EXPLOSION_SPRITE_PATH)?;
+        let mut explosion = Sprite::new(-(explosion_image.width() as i32 / 2), 0, explosion_image.clone(), explosion_image.rect());
+        explosion.hide();
+
+        Ok(Spaceship {
             spaceship: sprite,
             missile: RectSprite::default(MISSILE_COLOR),
-        }
+            previous_missile: Vec2::ZERO,
+            is_missile_flying: false,
+            explosion,
+            explosion_time_left: 0.,
+        })
     }
 
-    pub fn update(&mut self, logic_spaceship: &logic::Spaceship, cw: u32, ch: u32) {
-        self.spaceship.update(logic_spaceship.as_rect(), 0., cw, ch);
-        if logic_spaceship.missile.is_destroyed() {
+    /// Keep the position of the missile before a step of the logic.
+    pub fn save_position(&mut self, logic_spaceship: &logic::Spaceship) {
+        self.previous_missile = logic_spaceship.missile.as_rect().min();
+    }
+
+    pub fn update(&mut self, logic_spaceship: &logic::Spaceship, camera: &Camera, alpha: f32) {
+        self.spaceship.update(logic_spaceship.as_rect(), 0., camera);
+
+        let missile = &logic_spaceship.missile;
+        if missile.is_destroyed() {
             self.missile.hide();
+            if self.is_missile_flying {
+                self.explosion_time_left = MISSILE_EXPLOSION_DURATION;
+                self.explosion.show();
+            }
         } else {
             self.missile.show();
-            self.missile.update(logic_spaceship.missile.as_rect(), cw, ch);
+            let missile_rect = missile.as_rect();
+            let position = interpolate(self.previous_missile, missile_rect.min(), alpha, MISSILE_MAX_STEP);
+            self.missile.update(geometry::Rect::from_min_size(position, missile_rect.size()), camera);
+        }
+        self.is_missile_flying = !missile.is_destroyed();
+
+        if self.explosion_time_left <= 0. {
+            self.explosion.hide();
         }
+        let missile_rect = missile.as_rect();
+        self.explosion.update(geometry::Rect::new(missile_rect.xc(), missile_rect.y0(), 0., 0.), 0., camera);
     }
 
-    pub fn draw(&self, canvas: &mut WindowCanvas) {
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
         self.spaceship.draw(canvas);
         self.missile.draw(canvas);
+        self.explosion.draw(canvas);
     }
 }
 
+/// The aliens of the formation, hidden once destroyed.
+///
+/// The aliens of a row switch between their two images together.
+pub struct Aliens {
+    sprites: Vec<Sprite>,
+    rows: Vec<(SpriteSheet, Animation)>,
+}
+
+impl Aliens {
+    pub fn new(logic: &Logic, cw: u32, ch: u32, assets: &mut Assets) -> Result<Aliens, Error> {
+        let rows = ALIEN_ROW_SPRITE_PATHS.iter()
+            .map(|paths| {
+                let images = paths.iter().map(|path| assets.image(path)).collect::<Result<Vec<_>, Error>>()?;
+                let mut animation = Animation::new();
+                animation.add("march", Clip::uniform(&[0, 1], ALIEN_FRAME_DURATION, Playback::Loop));
+                animation.play("march");
+                Ok((SpriteSheet::from_images(images), animation))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let sprite_rect = Rect::new(0, 0, (ALIEN_WIDTH * cw as f32) as u32, (ALIEN_HEIGHT * ch as f32) as u32);
+        let sprites = logic.aliens.iter()
+            .map(|alien| {
+                let (sheet, _) = &rows[alien.row];
+                Sprite::simple_new(sheet.frame(0).0.clone(), sprite_rect)
+            })
+            .collect();
+        Ok(Aliens { sprites, rows })
+    }
 
-pub struct Graphics<'a> {
-    spaceship: Spaceship<'a>,
+    pub fn update(&mut self, logic: &Logic, camera: &Camera) {
+        for (sprite, alien) in self.sprites.iter_mut().zip(&logic.aliens) {
+            if alien.is_destroyed() {
+                sprite.hide();
+            }
+            let (sheet, animation) = &self.rows[alien.row];
+            animation.apply(sheet, sprite);
+            sprite.update(alien.as_rect(), 0., camera);
+        }
+    }
+
+    pub fn animate(&mut self, dt: f32) {
+        for (_, animation) in &mut self.rows {
+            animation.update(dt);
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Renderer) {
+        for sprite in &self.sprites {
+            sprite.draw(canvas);
+        }
+    }
+}
+
+/// The score at the top of the board: its label, then its digits drawn with the images of the digits.
+pub struct Hud {
+    label: Sprite,
+    label_position: Vec2,
+    score: Text,
 }
 
-impl Graphics<'_> {
+impl Hud {
+    pub fn new(cw: u32, ch: u32, assets: &mut Assets) -> Result<Hud, Error> {
+        let label_image = assets.image(SCORE_LABEL_SPRITE_PATH)?;
+        let (x, y) = SCORE_POSITION;
+        let label_position = Vec2::new(x as f32 / cw as f32, y as f32 / ch as f32);
+        let score_position = Point::new(x + label_image.width() as i32 + SCORE_LABEL_GAP, y);
+
+        let digits = (0..10)
+            .map(|digit| assets.image(&format!("{}/{}.bmp", DIGIT_SPRITE_DIRECTORY, digit)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let font = BitmapFont::from_images("0123456789", digits).with_spacing(SCORE_DIGIT_SPACING);
+        let style = TextStyle::new(Font::Bitmap(Rc::new(font)));
+
+        Ok(Hud {
+            label: Sprite::from_image(label_image),
+            label_position,
+            score: Text::new(&Hud::score_text(0), score_position, style, assets)?,
+        })
+    }
+
+    fn score_text(score: u32) -> String {
+        format!("{:0width$}", score, width = SCORE_DIGIT_COUNT)
+    }
+
+    pub fn update(&mut self, score: u32, camera: &Camera, assets: &Assets) {
+        self.label.update(geometry::Rect::from_min_size(self.label_position, Vec2::ZERO), 0., camera);
+        self.score.set_text(&Hud::score_text(score), assets).expect("can't draw the score");
+    }
+
+    pub fn draw(&self, canvas: &mut dyn Renderer, camera: &Camera) {
+        self.label.draw(canvas);
+        self.score.draw_scaled(canvas, camera);
+    }
+}
+
+/// The board is shown by a camera keeping the shape of the window it was designed for.
+pub struct Graphics {
+    camera: Camera,
+    spaceship: Spaceship,
+    aliens: Aliens,
+    hud: Hud,
+}
+
+impl Graphics {
     /// Init the dynamic elements required to draw the game
-    pub fn new(cw: u32, ch: u32, _ttf_context: &Sdl2TtfContext) -> Graphics {
-        Graphics {
-            spaceship: Spaceship::new(cw, ch),
-        }
+    pub fn new(logic: &Logic, cw: u32, ch: u32, assets: &mut Assets) -> Result<Graphics, Error> {
+        Ok(Graphics {
+            camera: Camera::new(geometry::Rect::new(0., 0., 1., 1.), cw, ch),
+            spaceship: Spaceship::new(cw, ch, assets)?,
+            aliens: Aliens::new(logic, cw, ch, assets)?,
+            hud: Hud::new(cw, ch, assets)?,
+        })
+    }
+
+    /// Keep the positions of the fast elements before a step of the logic.
+    pub fn save_positions(&mut self, logic: &Logic) {
+        self.spaceship.save_position(&logic.spaceship);
     }
 
-    /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window, _ttf_context: &Sdl2TtfContext) {
-        let w = window.width();
-        let h = window.height();
-        self.spaceship.update(&logic.spaceship, w, h)
+    /// Update the dynamic elements accordingly to the state of the game, alpha being the fraction of a step not simulated yet.
+    pub fn update(&mut self, logic: &Logic, window: &Window, assets: &Assets, alpha: f32) {
+        self.camera.fit(window);
+        self.spaceship.update(&logic.spaceship, &self.camera, alpha);
+        self.aliens.update(logic, &self.camera);
+        self.hud.update(logic.score, &self.camera, assets);
     }
 
     /// Draw the game.
@@ -70,8 +246,17 @@ impl Graphics<'_> {
     /// It draws each dynamic element and show the canvas
     pub fn draw(&self, window: &mut Window) {
         window.clear();
-        let canvas = &mut window.canvas;
+        let canvas = window.canvas.as_mut();
         self.spaceship.draw(canvas);
+        self.aliens.draw(canvas);
+        self.hud.draw(canvas, &self.camera);
+        self.camera.draw_letterbox(canvas);
         canvas.present();
     }
+
+    /// Advance the animations of the sprites, and the explosion of the missile.
+    pub fn animate(&mut self, dt: f32) {
+        self.spaceship.explosion_time_left -= dt;
+        self.aliens.animate(dt);
+    }
 }
\ No newline at end of file
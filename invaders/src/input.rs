@@ -1,5 +0,0 @@
-use sdl2::keyboard::Scancode;
-
-use crate::logic::Logic;
-
-pub fn handle_input(_event_pump: &sdl2::EventPump, _logic: &mut Logic) {}
\ No newline at end of file
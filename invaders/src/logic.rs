@@ -1,6 +1,7 @@
 use sdl2::surface::Surface;
 
 use engine::geometry::{AsRect, Rect};
+use engine::math::Vec2;
 use engine::physics::{Position, RectSolid, Velocity};
 
 pub const SPACESHIP_WIDTH: f32 = 0.065;
@@ -14,6 +15,19 @@ pub const MISSILE_SPEED: f32 = -1.;
 pub const MISSILE_RECT_WIDTH: f32 = 0.005;
 pub const MISSILE_RECT_HEIGHT: f32 = 0.01;
 
+pub const ALIEN_WIDTH: f32 = 0.05;
+pub const ALIEN_HEIGHT: f32 = 0.035;
+pub const ALIEN_COLUMN_COUNT: usize = 11;
+/// Points earned by shooting an alien, for each row of the formation from the top.
+pub const ALIEN_ROW_POINTS: [u32; 5] = [30, 20, 20, 10, 10];
+pub const ALIEN_COUNT: usize = ALIEN_COLUMN_COUNT * ALIEN_ROW_POINTS.len();
+/// Top left corner of the formation.
+pub const ALIENS_X0: f32 = 0.1;
+pub const ALIENS_Y0: f32 = 0.15;
+/// Distance between the top left corners of two neighbour aliens.
+pub const ALIEN_STEP_X: f32 = 0.075;
+pub const ALIEN_STEP_Y: f32 = 0.065;
+
 pub struct Missile {
     solid: RectSolid,
     is_destroyed: bool,
@@ -69,7 +83,7 @@ pub struct Spaceship {
 impl Spaceship {
     pub fn new() -> Spaceship {
         let position = Position::new(SPACESHIP_STARTING_POSITION_X0, SPACESHIP_STARTING_POSITION_Y0);
-        let velocity = Velocity::new(0., 0.);
+        let velocity = Velocity::ZERO;
         let limit = Rect::new(0., 0., 1., 1.);
 
         Spaceship {
@@ -91,13 +105,11 @@ impl Spaceship {
 
 
     pub fn accelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vx(pv.vx() + SPACESHIP_SPEED);
+        self.solid.vel.x += SPACESHIP_SPEED;
     }
 
     pub fn decelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vx(pv.vx() - SPACESHIP_SPEED);
+        self.solid.vel.x -= SPACESHIP_SPEED;
     }
 
     pub fn update(&mut self, dt: f32) {
@@ -112,21 +124,78 @@ impl AsRect for Spaceship {
     }
 }
 
+/// An alien of the formation, worth more points the higher its row.
+pub struct Alien {
+    position: Vec2,
+    /// Row of the alien, from 0 at the top of the formation.
+    pub row: usize,
+    is_destroyed: bool,
+}
+
+impl Alien {
+    pub fn new(row: usize, column: usize) -> Alien {
+        let x = ALIENS_X0 + column as f32 * ALIEN_STEP_X;
+        let y = ALIENS_Y0 + row as f32 * ALIEN_STEP_Y;
+        Alien {
+            position: Vec2::new(x, y),
+            row,
+            is_destroyed: false,
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        ALIEN_ROW_POINTS[self.row]
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.is_destroyed
+    }
+}
+
+impl AsRect for Alien {
+    fn as_rect(&self) -> Rect {
+        Rect::from_min_size(self.position, Vec2::new(ALIEN_WIDTH, ALIEN_HEIGHT))
+    }
+}
+
 /// Logic is a structure that contains all entities from the game.
 pub struct Logic {
     pub spaceship: Spaceship,
+    /// The aliens, row by row from the top, the destroyed ones included.
+    pub aliens: Vec<Alien>,
+    /// Points scored by shooting the aliens.
+    pub score: u32,
     is_over: bool,
 }
 
 impl Logic {
     /// Create a new game logic with default values for game settings
     pub fn new() -> Logic {
+        let aliens = (0..ALIEN_ROW_POINTS.len())
+            .flat_map(|row| (0..ALIEN_COLUMN_COUNT).map(move |column| Alien::new(row, column)))
+            .collect();
         Logic {
             spaceship: Spaceship::new(),
+            aliens,
+            score: 0,
             is_over: false,
         }
     }
 
+    /// Number of aliens still alive.
+    pub fn alien_count(&self) -> usize {
+        self.aliens.iter().filter(|alien| !alien.is_destroyed).count()
+    }
+
+    /// Destroy an alien, scoring its points.
+    pub fn destroy_alien(&mut self, index: usize) {
+        let alien = &mut self.aliens[index];
+        if !alien.is_destroyed {
+            alien.is_destroyed = true;
+            self.score += alien.points();
+        }
+    }
+
     /// Update each entity of a delta of time and check if the game is over.
     pub fn update(&mut self, dt: f32) {
         self.spaceship.update(dt);
@@ -141,4 +210,22 @@ impl Logic {
     pub fn is_over(&self) -> bool {
         self.is_over
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn destroyed_aliens_score_the_points_of_their_row() {
+        let mut logic = Logic::new();
+        let count = logic.aliens.len();
+
+        // The top left alien, the bottom right one, and the top left one again.
+        logic.destroy_alien(0);
+        logic.destroy_alien(count - 1);
+        logic.destroy_alien(0);
+        assert_eq!(logic.score, 30 + 10);
+        assert_eq!(logic.aliens.iter().filter(|alien| alien.is_destroyed()).count(), 2);
+    }
+}
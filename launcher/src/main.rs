@@ -0,0 +1,22 @@
+use engine::backend::Backend;
+use engine::error::Error;
+use engine::game_loop::run;
+use engine::graphics::Window;
+use engine::replay::Input;
+
+use crate::menu::Menu;
+
+mod menu;
+
+pub const WINDOW_WIDTH: u32 = 600;
+pub const WINDOW_HEIGHT: u32 = 600;
+
+fn main() -> Result<(), Error> {
+    let backend = Backend::from_args();
+    let (input, _seed) = Input::from_args(0)?;
+    let ttf_context = sdl2::ttf::init()?;
+    let mut window = Window::open(backend, WINDOW_WIDTH, WINDOW_HEIGHT)?;
+
+    let mut menu = Menu::new(WINDOW_WIDTH, WINDOW_HEIGHT, &ttf_context)?;
+    run(&mut menu, &mut window, input)
+}
@@ -0,0 +1,180 @@
+use std::env;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::geometry;
+use engine::graphics::{Camera, RenderedString, Window};
+
+/// Games of the workspace, by name of their executable.
+pub const GAMES: [&str; 5] = ["pong", "breakout", "combat", "asteroids", "invaders"];
+
+/// The font of the games, shared rather than copied in the launcher.
+pub const FONT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../pong/res/atari.ttf");
+pub const TITLE_FONT_SIZE: u16 = 48;
+pub const ENTRY_FONT_SIZE: u16 = 32;
+pub const STATUS_FONT_SIZE: u16 = 12;
+
+pub const TITLE_POSITION_Y: i32 = 100;
+pub const FIRST_ENTRY_POSITION_Y: i32 = 220;
+pub const ENTRY_SPACING: i32 = 60;
+pub const STATUS_POSITION_Y: i32 = 560;
+
+pub const SELECTION_COLOR: Color = Color { r: 62, g: 117, b: 207, a: 255 };
+pub const SELECTION_WIDTH: u32 = 300;
+pub const SELECTION_HEIGHT: u32 = 50;
+
+/// Path of the executable of a game, built next to the launcher.
+pub fn game_executable(name: &str) -> io::Result<PathBuf> {
+    let launcher = env::current_exe()?;
+    Ok(launcher.with_file_name(format!("{}{}", name, env::consts::EXE_SUFFIX)))
+}
+
+/// Menu listing the games: up and down select a game, enter starts it and escape quits.
+///
+/// The selected game runs in its own process, the menu is back once it is over.
+pub struct Menu<'a> {
+    selected: usize,
+    is_over: bool,
+    assets: Assets<'a>,
+    camera: Camera,
+    title: RenderedString,
+    entries: Vec<RenderedString>,
+    status: RenderedString,
+}
+
+impl Menu<'_> {
+    pub fn new(window_width: u32, window_height: u32, ttf_context: &Sdl2TtfContext) -> Result<Menu<'_>, Error> {
+        let mut assets = Assets::new(ttf_context);
+        let xc = window_width as i32 / 2;
+
+        let title_font = assets.font(FONT_PATH, TITLE_FONT_SIZE)?;
+        let title = RenderedString::new("rust-games", xc, TITLE_POSITION_Y, &assets, title_font)?;
+
+        let entry_font = assets.font(FONT_PATH, ENTRY_FONT_SIZE)?;
+        let mut entries = Vec::new();
+        for (i, game) in GAMES.iter().enumerate() {
+            let yc = FIRST_ENTRY_POSITION_Y + i as i32 * ENTRY_SPACING;
+            entries.push(RenderedString::new(game, xc, yc, &assets, entry_font)?);
+        }
+
+        let status_font = assets.font(FONT_PATH, STATUS_FONT_SIZE)?;
+        let status = RenderedString::new(" ", xc, STATUS_POSITION_Y, &assets, status_font)?;
+
+        Ok(Menu {
+            selected: 0,
+            is_over: false,
+            assets,
+            camera: Camera::new(geometry::Rect::new(0., 0., 1., 1.), window_width, window_height),
+            title,
+            entries,
+            status,
+        })
+    }
+
+    /// Name of the selected game.
+    pub fn selected(&self) -> &str {
+        GAMES[self.selected]
+    }
+
+    fn select_previous(&mut self) {
+        self.selected = (self.selected + GAMES.len() - 1) % GAMES.len();
+    }
+
+    fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % GAMES.len();
+    }
+
+    /// Start the selected game and wait until it is over.
+    fn launch(&mut self) {
+        let game = self.selected();
+        let status = match game_executable(game).and_then(|path| Command::new(path).status()) {
+            Ok(status) if status.success() => String::from(" "),
+            Ok(status) => format!("{} stopped: {}", game, status),
+            Err(error) => format!("can't start {}: {}, build it with cargo build --workspace", game, error),
+        };
+        self.status.set_text(&status, &self.assets).expect("can't render the status");
+    }
+}
+
+impl Game for Menu<'_> {
+    fn handle_event(&mut self, event: Event) {
+        match event {
+            Event::Quit { .. } |
+            Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
+                self.is_over = true;
+            }
+            Event::KeyDown { keycode: Some(Keycode::Up), .. } => {
+                self.select_previous();
+            }
+            Event::KeyDown { keycode: Some(Keycode::Down), .. } => {
+                self.select_next();
+            }
+            Event::KeyDown { keycode: Some(Keycode::Return), repeat: false, .. } => {
+                self.launch();
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _dt: f32) {}
+
+    fn collide(&mut self, _dt: f32) {}
+
+    fn render(&mut self, window: &mut Window, _alpha: f32) {
+        window.clear();
+        self.camera.fit(window);
+
+        let xc = self.camera.viewport().center().x();
+        let yc = self.camera.to_screen_pixel(Point::new(0, FIRST_ENTRY_POSITION_Y + self.selected as i32 * ENTRY_SPACING)).y();
+        let scale = self.camera.pixel_scale();
+        let selection = Rect::from_center((xc, yc), (SELECTION_WIDTH as f32 * scale) as u32, (SELECTION_HEIGHT as f32 * scale) as u32);
+
+        let canvas = window.canvas.as_mut();
+        canvas.fill_rect(selection, SELECTION_COLOR);
+        self.title.draw_scaled(canvas, &self.camera);
+        for entry in &self.entries {
+            entry.draw_scaled(canvas, &self.camera);
+        }
+        self.status.draw_scaled(canvas, &self.camera);
+
+        canvas.present();
+    }
+
+    fn is_over(&self) -> bool {
+        self.is_over
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use engine::backend::key_down;
+
+    use super::*;
+
+    #[test]
+    fn selection_wraps_around() {
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let mut menu = Menu::new(600, 600, &ttf_context).unwrap();
+        assert_eq!(menu.selected(), "pong");
+
+        menu.handle_event(key_down(Keycode::Up));
+        assert_eq!(menu.selected(), "invaders");
+
+        menu.handle_event(key_down(Keycode::Down));
+        menu.handle_event(key_down(Keycode::Down));
+        assert_eq!(menu.selected(), "breakout");
+
+        menu.handle_event(key_down(Keycode::Escape));
+        assert!(menu.is_over());
+    }
+}
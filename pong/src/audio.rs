@@ -1,27 +1,31 @@
-use sdl2::mixer::Chunk;
+use engine::error::Error;
+use engine::audio::{Mixer, SoundManager};
+use engine::math::Vec2;
+use engine::res;
 
+pub const CHANNEL_COUNT: i32 = 4;
+pub const SOUND_BANK_PATH: &str = res!("sounds.bank");
+
+/// The bounces are heard on the side of the board they happen on.
 pub struct Audio {
-    wall_bounce: Chunk,
-    racket_bounce: Chunk,
-    lose: Chunk,
+    sounds: SoundManager,
 }
 
 impl Audio {
-    pub fn new() -> Audio {
-        Audio {
-            wall_bounce: sdl2::mixer::Chunk::from_file("res/wall.wav").unwrap(),
-            racket_bounce: sdl2::mixer::Chunk::from_file("res/racket.wav").unwrap(),
-            lose: sdl2::mixer::Chunk::from_file("res/lose.wav").unwrap(),
-        }
+    pub fn new(mixer: Box<dyn Mixer>) -> Result<Audio, Error> {
+        let mut sounds = SoundManager::new(mixer, CHANNEL_COUNT);
+        sounds.load_bank(SOUND_BANK_PATH)?;
+        sounds.set_listener(Vec2::new(0.5, 0.5), 0.5);
+        Ok(Audio { sounds })
     }
 
-    pub fn play_wall_bounce(&self) {
-        sdl2::mixer::Channel::all().play(&self.wall_bounce, 0).unwrap();
+    pub fn play_wall_bounce(&self, position: Vec2) {
+        self.sounds.play_at("wall", position);
     }
-    pub fn play_racket_bounce(&self) {
-        sdl2::mixer::Channel::all().play(&self.racket_bounce, 0).unwrap();
+    pub fn play_racket_bounce(&self, position: Vec2) {
+        self.sounds.play_at("racket", position);
     }
     pub fn play_lose(&self) {
-        sdl2::mixer::Channel::all().play(&self.lose, 0).unwrap();
+        self.sounds.play("lose");
     }
 }
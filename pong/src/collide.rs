@@ -20,8 +20,8 @@ impl Collide<'_> {
         let bottom_wall_rect = Rect::from_2_points(0., 0., 1., 0.);
         match collide(&ball_rect, &bottom_wall_rect) {
             Some(_rect) => {
-                logic.ball.reflect(0.);
-                self.audio.play_wall_bounce();
+                logic.ball.reflect(0., 1.);
+                self.audio.play_wall_bounce(ball_rect.center());
             }
             None => (),
         };
@@ -29,8 +29,8 @@ impl Collide<'_> {
         let top_wall_rect = Rect::from_2_points(0., 1., 1., 1.);
         match collide(&ball_rect, &top_wall_rect) {
             Some(_rect) => {
-                logic.ball.reflect(1. - BALL_DIM);
-                self.audio.play_wall_bounce();
+                logic.ball.reflect(1. - BALL_DIM, -1.);
+                self.audio.play_wall_bounce(ball_rect.center());
             }
             None => (),
         };
@@ -49,7 +49,7 @@ impl Collide<'_> {
                 }
                 let angle = logic.left_racket.get_bounce_angle(rect.xc(), rect.yc());
                 logic.ball.bounce(angle, rect.w());
-                self.audio.play_racket_bounce();
+                self.audio.play_racket_bounce(left_racket.center());
             }
             None => ()
         }
@@ -61,7 +61,7 @@ impl Collide<'_> {
                 }
                 let angle = logic.right_racket.get_bounce_angle(rect.xc(), rect.yc());
                 logic.ball.bounce(angle, -rect.w());
-                self.audio.play_racket_bounce();
+                self.audio.play_racket_bounce(right_racket.center());
             }
             None => ()
         }
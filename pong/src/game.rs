@@ -0,0 +1,125 @@
+use sdl2::event::Event;
+use sdl2::ttf::Sdl2TtfContext;
+
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::Game;
+use engine::graphics::Window;
+use engine::random::Rng;
+
+use crate::audio::Audio;
+use crate::collide::Collide;
+use crate::event::handle_event;
+use crate::graphics::Graphics;
+use crate::logic::Logic;
+
+/// The pong game, gathering the logic, the collisions and the graphics for the game loop.
+pub struct Pong<'a> {
+    logic: Logic<'a>,
+    collide: Collide<'a>,
+    graphics: Graphics,
+    assets: Assets<'a>,
+}
+
+impl Pong<'_> {
+    /// Create a new match, the seed giving all its random events.
+    pub fn new<'a>(audio: &'a Audio, ttf_context: &'a Sdl2TtfContext, seed: u64) -> Result<Pong<'a>, Error> {
+        let mut rng = Rng::new(seed);
+        let mut assets = Assets::new(ttf_context);
+        Ok(Pong {
+            logic: Logic::new(audio, rng.fork()),
+            collide: Collide::new(audio),
+            graphics: Graphics::new(&mut assets)?,
+            assets,
+        })
+    }
+}
+
+impl Game for Pong<'_> {
+    fn handle_event(&mut self, event: Event) {
+        handle_event(event, &mut self.logic);
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.graphics.save_positions(&self.logic);
+        self.logic.update(dt);
+    }
+
+    fn collide(&mut self, _dt: f32) {
+        self.collide.collide_ball_and_wall(&mut self.logic);
+        self.collide.collide_ball_and_racket(&mut self.logic);
+    }
+
+    fn render(&mut self, window: &mut Window, alpha: f32) {
+        self.graphics.update(&self.logic, window, &self.assets, alpha);
+        self.graphics.draw(window);
+    }
+
+    fn is_over(&self) -> bool {
+        self.logic.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl2::keyboard::Keycode;
+
+    use engine::audio::NullMixer;
+    use engine::game_loop::simulate;
+    use engine::geometry::AsRect;
+    use engine::graphics::NullRenderer;
+    use engine::backend::key_down;
+
+    use crate::logic::SCORE_MAX;
+
+    use super::*;
+
+    #[test]
+    fn full_match_runs_headless() {
+        let mixer = NullMixer::new();
+        let sounds = mixer.record();
+        let audio = Audio::new(Box::new(mixer)).unwrap();
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let renderer = NullRenderer::new(600, 600);
+        let frames = renderer.record();
+        let mut window = Window { canvas: Box::new(renderer), event_pump: None };
+
+        let mut pong = Pong::new(&audio, &ttf_context, 1).unwrap();
+
+        // The left player leaves the center, so the ball can't bounce forever between the rackets.
+        pong.handle_event(key_down(Keycode::Up));
+        simulate(&mut pong, &mut window, 1_000_000);
+
+        assert!(pong.is_over());
+        let score = &pong.logic.score;
+        assert_eq!(score.left().max(score.right()), SCORE_MAX);
+
+        let lose_count = sounds.borrow().played.iter().filter(|path| path.ends_with("lose.wav")).count();
+        assert_eq!(lose_count, (score.left() + score.right()) as usize);
+        assert!(frames.borrow().frame_count > 0);
+    }
+
+    #[test]
+    fn same_seed_same_match() {
+        let audio = Audio::new(Box::new(NullMixer::new())).unwrap();
+        let ttf_context = sdl2::ttf::init().unwrap();
+        let mut window = Window::headless(600, 600);
+
+        let mut first = Pong::new(&audio, &ttf_context, 42).unwrap();
+        let mut second = Pong::new(&audio, &ttf_context, 42).unwrap();
+        first.handle_event(key_down(Keycode::Up));
+        second.handle_event(key_down(Keycode::Up));
+
+        for _ in 0..100 {
+            simulate(&mut first, &mut window, 60);
+            simulate(&mut second, &mut window, 60);
+
+            let first_ball = first.logic.ball.as_rect();
+            let second_ball = second.logic.ball.as_rect();
+            assert_eq!(first_ball.x0(), second_ball.x0());
+            assert_eq!(first_ball.y0(), second_ball.y0());
+            assert_eq!(first.logic.score.left(), second.logic.score.left());
+            assert_eq!(first.logic.score.right(), second.logic.score.right());
+        }
+    }
+}
@@ -1,15 +1,18 @@
 use sdl2::pixels::Color;
-use sdl2::rect::Rect;
-use sdl2::render::WindowCanvas;
-use sdl2::ttf::Sdl2TtfContext;
 
-use engine::geometry::AsRect;
-use engine::graphics::{RectSprite, RenderedString, Window};
+use engine::assets::Assets;
+use engine::error::Error;
+use engine::game_loop::interpolate;
+use engine::geometry::{AsRect, Rect};
+use engine::graphics::{Camera, Drawable, Layer, NodeId, RectSprite, RenderedString, Scene, Window};
+use engine::math::Vec2;
+use engine::res;
 
 use crate::logic::Logic;
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
 
 pub const MID_LINE_N: u32 = 30;
-pub const MID_LINE_WIDTH: u32 = 5;
+pub const MID_LINE_WIDTH: f32 = 0.008;
 pub const MID_LINE_RELATIVE_LENGTH: f32 = 0.6;
 
 pub const SCORE_POINT_SIZE: u16 = 48;
@@ -18,120 +21,107 @@ pub const LEFT_SCORE_POSITION_X: i32 = 150;
 pub const RIGHT_SCORE_POSITION_X: i32 = 450;
 
 
-pub const FONT_PATH: &str = "res/atari.ttf";
+pub const FONT_PATH: &str = res!("atari.ttf");
+
+/// Farthest the ball is drawn from its previous position, it has been thrown again when it moves more in a step.
+pub const BALL_MAX_STEP: f32 = 0.1;
 
 /// Struct containing all basic dynamic elements required to draw the game.
 ///
-/// The graphics part contains the 2 rackets, the ball, and the score.
+/// The scene has the mid line in the background, the 2 rackets and the ball on the playfield, and the score in the HUD.
+/// The board is shown by a camera keeping it square whatever the size of the window.
+/// The ball is drawn between its positions before and after the last step.
 pub struct Graphics {
-    left_racket: RectSprite,
-    right_racket: RectSprite,
-    ball: RectSprite,
-    score: Score,
+    camera: Camera,
+    scene: Scene,
+    left_racket: NodeId,
+    right_racket: NodeId,
+    ball: NodeId,
+    previous_ball: Vec2,
+    left_score: NodeId,
+    right_score: NodeId,
 }
 
 impl Graphics {
     /// Init the dynamic elements required to draw the game
-    pub fn new() -> Graphics {
-        Graphics {
-            left_racket: RectSprite::default(Color::WHITE),
-            right_racket: RectSprite::default(Color::WHITE),
-            ball: RectSprite::default(Color::WHITE),
-            score: Score::new(),
-        }
+    pub fn new(assets: &mut Assets) -> Result<Graphics, Error> {
+        let mut scene = Scene::new();
+        Graphics::add_mid_line(&mut scene);
+
+        let left_racket = scene.add(Layer::Playfield, Drawable::Rect(RectSprite::default(Color::WHITE)));
+        let right_racket = scene.add(Layer::Playfield, Drawable::Rect(RectSprite::default(Color::WHITE)));
+        let ball = scene.add(Layer::Playfield, Drawable::Rect(RectSprite::default(Color::WHITE)));
+
+        let font = assets.font(FONT_PATH, SCORE_POINT_SIZE)?;
+        let left_score = RenderedString::new("0", LEFT_SCORE_POSITION_X, SCORE_POSITION_Y, assets, font)?;
+        let right_score = RenderedString::new("0", RIGHT_SCORE_POSITION_X, SCORE_POSITION_Y, assets, font)?;
+        let left_score = scene.add(Layer::Hud, Drawable::Text(left_score));
+        let right_score = scene.add(Layer::Hud, Drawable::Text(right_score));
+
+        Ok(Graphics {
+            camera: Camera::new(Rect::new(0., 0., 1., 1.), WINDOW_WIDTH, WINDOW_HEIGHT),
+            scene,
+            left_racket,
+            right_racket,
+            ball,
+            previous_ball: Vec2::ZERO,
+            left_score,
+            right_score,
+        })
     }
 
-    /// Update the dynamic elements accordingly to the state of the game.
-    pub fn update(&mut self, logic: &Logic, window: &Window) {
-        let w = window.width();
-        let h = window.height();
-
-        self.left_racket.update(logic.left_racket.as_rect(), w, h);
-        self.right_racket.update(logic.right_racket.as_rect(), w, h);
-        self.ball.update(logic.ball.as_rect(), w, h);
-        self.score.update(logic);
-    }
-
-    /// Draw the game.
-    ///
-    /// Start by clearing the all board.
-    /// Then, it draws the static element : the mid line for instance.
-    /// Finally, it draws each dynamic element and show the canvas
-    pub fn draw(&self, window: &mut Window, ttf_context: &Sdl2TtfContext) {
-        window.clear();
-
-        self.draw_mid_line(window);
-
-        let canvas = &mut window.canvas;
-        canvas.fill_rect(self.left_racket.rect).unwrap();
-        canvas.fill_rect(self.right_racket.rect).unwrap();
-        canvas.fill_rect(self.ball.rect).unwrap();
-
-        self.score.draw(canvas, ttf_context);
-
-        canvas.present();
+    /// Keep the position of the ball before a step of the logic.
+    pub fn save_positions(&mut self, logic: &Logic) {
+        self.previous_ball = logic.ball.as_rect().min();
     }
 
-
-    /// Private function to draw the static mid line.
-    fn draw_mid_line(&self, window: &mut Window) {
+    /// Add the static mid line, dashed from the top to the bottom of the board.
+    fn add_mid_line(scene: &mut Scene) {
         let n_lines = MID_LINE_N;
-        let len_line = window.height() / n_lines;
+        let len_line = 1. / n_lines as f32;
         let line_width = MID_LINE_WIDTH;
-        let line_height = (MID_LINE_RELATIVE_LENGTH * len_line as f32) as u32;
-        let line_x = (window.width() - line_width) / 2;
+        let line_height = MID_LINE_RELATIVE_LENGTH * len_line;
+        let line_x = (1. - line_width) / 2.;
 
-        let canvas = &mut window.canvas;
-        canvas.set_draw_color(Color::WHITE);
+        let mid_line = scene.add(Layer::Background, Drawable::Group);
         for i in 0..n_lines {
-            let rect = Rect::new(line_x as i32, (i * len_line) as i32, line_width, line_height);
-            canvas.fill_rect(rect).unwrap();
+            let dash = scene.add_child(mid_line, Drawable::Rect(RectSprite::default(Color::WHITE)));
+            scene.set_rect(dash, &Rect::new(line_x, i as f32 * len_line, line_width, line_height));
         }
     }
-}
 
-/// Used to draw the current score, using digit sprites.
-struct Score {
-    left: u8,
-    right: u8,
-}
+    /// Update the dynamic elements accordingly to the state of the game, alpha being the fraction of a step not simulated yet.
+    pub fn update(&mut self, logic: &Logic, window: &Window, assets: &Assets, alpha: f32) {
+        self.camera.fit(window);
 
-impl Score {
-    /// Create an empty score
-    pub fn new() -> Score {
-        let left: u8 = 0;
-        let right: u8 = 0;
-        Score {
-            left,
-            right,
-        }
+        self.scene.set_rect(self.left_racket, &logic.left_racket.as_rect());
+        self.scene.set_rect(self.right_racket, &logic.right_racket.as_rect());
+        let ball = logic.ball.as_rect();
+        let ball_position = interpolate(self.previous_ball, ball.min(), alpha, BALL_MAX_STEP);
+        self.scene.set_rect(self.ball, &Rect::from_min_size(ball_position, ball.size()));
+        self.update_score(self.left_score, logic.score.left(), assets);
+        self.update_score(self.right_score, logic.score.right(), assets);
+
+        self.scene.update(&self.camera);
     }
 
-    /// Get the current score from the game state
-    pub fn update(&mut self, logic: &Logic) {
-        self.left = logic.score.left();
-        self.right = logic.score.right();
+    /// Show a score, the digits being rendered again only when they change.
+    fn update_score(&mut self, node: NodeId, score: u8, assets: &Assets) {
+        if let Drawable::Text(text) = self.scene.drawable_mut(node) {
+            text.set_text(&score.to_string(), assets).expect("can't render the score");
+        }
     }
 
-    /// Draw the score on the scree, first selecting the right sprites from the digit, then showing it.
-    pub fn draw(&self, canvas: &mut WindowCanvas, ttf_context: &Sdl2TtfContext) {
-        let left_str = RenderedString::new(
-            &self.left.to_string(),
-            LEFT_SCORE_POSITION_X,
-            SCORE_POSITION_Y, ttf_context,
-            FONT_PATH,
-            SCORE_POINT_SIZE,
-        );
-
-        let right_str = RenderedString::new(
-            &self.right.to_string(),
-            RIGHT_SCORE_POSITION_X,
-            SCORE_POSITION_Y, ttf_context,
-            FONT_PATH,
-            SCORE_POINT_SIZE,
-        );
-
-        left_str.draw(canvas);
-        right_str.draw(canvas);
+    /// Draw the game.
+    ///
+    /// Start by clearing the all board, then draw the scene and show the canvas.
+    pub fn draw(&self, window: &mut Window) {
+        window.clear();
+
+        let canvas = window.canvas.as_mut();
+        self.scene.draw(canvas, &self.camera);
+
+        self.camera.draw_letterbox(canvas);
+        canvas.present();
     }
-}
\ No newline at end of file
+}
@@ -2,8 +2,9 @@
 use std::f32::consts::PI;
 
 use engine::geometry::{AsRect, Rect};
-use engine::physics::{Position, RectSolid, Velocity};
-use engine::random::{flip, rand};
+use engine::math::Vec2;
+use engine::physics::{Body, Position, RectSolid, Velocity};
+use engine::random::Rng;
 
 use crate::audio::Audio;
 
@@ -91,7 +92,7 @@ impl Racket {
     /// It can't get out from the screen.
     fn new(x: f32, y: f32) -> Racket {
         let pos = Position::new(x, y);
-        let vel = Velocity::new(0., 0.);
+        let vel = Velocity::ZERO;
         let limit = Rect::new(0., 0., 1., 1.);
         Racket {
             solid: RectSolid::new(pos, vel, RACKET_WIDTH, RACKET_HEIGHT, limit)
@@ -105,14 +106,12 @@ impl Racket {
 
     /// To make the racket start moving.
     pub fn accelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vy(pv.vy() + RACKET_SPEED);
+        self.solid.vel.y += RACKET_SPEED;
     }
 
     /// To make the racket stop moving.
     pub fn decelerate(&mut self) {
-        let pv = self.solid.vel.copy();
-        self.solid.vel.set_vy(pv.vy() - RACKET_SPEED);
+        self.solid.vel.y -= RACKET_SPEED;
     }
 
     /// Compute the bounce angle of the ball on the racket
@@ -138,56 +137,65 @@ impl AsRect for Racket {
 
 /// The ball which move across the board, between rackets.
 ///
-/// A ball is represented as a solid.
+/// A ball is represented as a solid, with an elastic body.
 /// It is reflected on rackets.
 ///
 pub struct Ball {
-    solid: RectSolid
+    solid: RectSolid,
+    body: Body,
+    /// Time of the last update left after the ball was stopped by a racket, to move once it has bounced.
+    time_left: f32,
 }
 
 impl Ball {
     /// Create a new ball with a random direction
-    fn new(x: f32, y: f32) -> Ball {
-        let mut random_angle: i32 = rand(-35, 35);
-        if flip() {
+    fn new(x: f32, y: f32, rng: &mut Rng) -> Ball {
+        let mut random_angle: i32 = rng.rand(-35, 35);
+        if rng.flip() {
             random_angle += 180;
         }
         let random_angle = random_angle as f32 * std::f32::consts::PI / 180.;
 
         let pos = Position::new(x, y);
-        let vel = Velocity::new(random_angle.cos() * BALL_SPEED, random_angle.sin() * BALL_SPEED);
+        let vel = Velocity::polar(BALL_SPEED, random_angle);
         let limit = Rect::new(-0.1, 0., 1.2, 1.1);
 
         Ball {
-            solid: RectSolid::new(pos, vel, BALL_DIM, BALL_DIM, limit)
+            solid: RectSolid::new(pos, vel, BALL_DIM, BALL_DIM, limit),
+            body: Body::new(1.),
+            time_left: 0.,
         }
     }
 
-    /// Ball update is just the solid physics updating.
-    fn update(&mut self, dt: f32) {
-        self.solid.update(dt);
+    /// Move the ball, stopping it against the rackets so a fast ball can't go through them.
+    ///
+    /// The ball moves for the rest of the time once it has bounced.
+    fn update(&mut self, dt: f32, rackets: &[Rect]) {
+        self.body.integrate(&mut self.solid.vel, dt);
+        self.time_left = match self.solid.update_until_hit(dt, rackets) {
+            Some(hit) => dt - hit.time,
+            None => 0.,
+        };
     }
 
-    /// Reflect ball from the wall.
-    pub fn reflect(&mut self, y0: f32) {
-        self.solid.pos.set_y(y0);
-
-        let vy = self.solid.vel.vy();
-        self.solid.vel.set_vy(-vy);
+    /// Reflect ball from the wall, the normal going from the wall toward the board.
+    pub fn reflect(&mut self, y0: f32, ny: f32) {
+        self.solid.pos.y = y0;
+        self.body.bounce(&mut self.solid.vel, Vec2::new(0., ny));
     }
 
     /// Bounce at a given angle.
     pub fn bounce(&mut self, angle: f32, x_shift: f32) {
 
         // Shift the ball outside the collision
-        let x = self.solid.pos.x();
-        self.solid.pos.set_x(x + x_shift);
+        self.solid.pos.x += x_shift;
 
         // Set the new speed
-        let vx = self.solid.vel.mag() * angle.cos();
-        let vy = self.solid.vel.mag() * angle.sin();
-        self.solid.vel.set_vx(vx);
-        self.solid.vel.set_vy(vy);
+        self.solid.vel = Velocity::polar(self.solid.vel.length(), angle);
+
+        // Move for the time left after the contact
+        self.solid.update(self.time_left);
+        self.time_left = 0.;
     }
 }
 
@@ -211,20 +219,24 @@ pub struct Logic<'a> {
     pub score: Score,
 
     audio: &'a Audio,
+    rng: Rng,
 
     is_over: bool,
 }
 
 impl Logic<'_> {
-    /// Create a new game logic with default values for game settings
-    pub fn new(audio: &Audio) -> Logic {
+    /// Create a new game logic with default values for game settings.
+    ///
+    /// The random generator gives the direction of each new ball.
+    pub fn new(audio: &Audio, mut rng: Rng) -> Logic<'_> {
         Logic {
             left_racket: Racket::new(RACKET_SHIFT_X, 0.5 - (RACKET_HEIGHT / 2.)),
             right_racket: Racket::new(1. - RACKET_WIDTH - RACKET_SHIFT_X, 0.5 - (RACKET_HEIGHT / 2.)),
-            ball: Ball::new(0.5 - (BALL_DIM / 2.), 0.5 - (BALL_DIM / 2.)),
+            ball: Ball::new(0.5 - (BALL_DIM / 2.), 0.5 - (BALL_DIM / 2.), &mut rng),
             score: Score::new(),
 
             audio,
+            rng,
             is_over: false,
         }
     }
@@ -233,7 +245,7 @@ impl Logic<'_> {
     pub fn update(&mut self, dt: f32) {
         self.left_racket.update(dt);
         self.right_racket.update(dt);
-        self.ball.update(dt);
+        self.ball.update(dt, &[self.left_racket.as_rect(), self.right_racket.as_rect()]);
         self.update_score();
     }
 
@@ -253,7 +265,7 @@ impl Logic<'_> {
             if self.score.is_game_over() {
                 self.is_over = true
             }
-            self.ball = Ball::new(0.5 - (BALL_DIM / 2.), 0.5 - (BALL_DIM / 2.));
+            self.ball = Ball::new(0.5 - (BALL_DIM / 2.), 0.5 - (BALL_DIM / 2.), &mut self.rng);
         }
     }
 
@@ -267,3 +279,21 @@ impl Logic<'_> {
         self.is_over
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bouncing_ball_moves_for_the_time_left() {
+        let mut ball = Ball::new(0.5, 0.5, &mut Rng::new(0));
+        ball.solid.vel = Velocity::new(1., 0.);
+        let racket = Rect::new(0.5 + BALL_DIM + 0.05, 0., RACKET_WIDTH, 1.);
+
+        // The ball touches the racket after half of the update, and goes back during the other half.
+        ball.update(0.1, &[racket]);
+        assert!((ball.as_rect().x1() - (0.5 + BALL_DIM + 0.05)).abs() < 1e-5);
+        ball.bounce(PI, 0.);
+        assert!((ball.as_rect().x0() - 0.5).abs() < 1e-5);
+    }
+}
@@ -1,53 +1,30 @@
-use std::time::SystemTime;
-
-use audio::Audio;
-use collide::Collide;
-use engine::audio::init_audio;
+use audio::{Audio, CHANNEL_COUNT};
+use engine::error::Error;
+use engine::audio::open_mixer;
+use engine::backend::Backend;
+use engine::game_loop::run;
 use engine::graphics::Window;
-use event::handle_event;
-use logic::Logic;
-
-use crate::graphics::Graphics;
+use engine::random::time_seed;
+use engine::replay::Input;
+use game::Pong;
 
 mod graphics;
 mod event;
 mod collide;
 mod audio;
 mod logic;
+mod game;
 
-fn main() {
-    init_audio(4);
-    let audio = Audio::new();
-    let mut logic = Logic::new(&audio);
-    let mut collide = Collide::new(&audio);
-
-    let mut window = Window::new(600, 600);
-    let ttf_context = sdl2::ttf::init().unwrap();
-
-    let mut graphics = Graphics::new();
-
+pub const WINDOW_WIDTH: u32 = 600;
+pub const WINDOW_HEIGHT: u32 = 600;
 
-    let mut previous = SystemTime::now();
-    'game_loop: loop {
-        let next = SystemTime::now();
-        let dt = next.duration_since(previous).unwrap().as_secs_f32();
-        previous = next;
+fn main() -> Result<(), Error> {
+    let backend = Backend::from_args();
+    let (input, seed) = Input::from_args(time_seed())?;
+    let audio = Audio::new(open_mixer(backend, CHANNEL_COUNT))?;
+    let ttf_context = sdl2::ttf::init()?;
+    let mut window = Window::open(backend, WINDOW_WIDTH, WINDOW_HEIGHT)?;
 
-        let event_pump = &mut window.event_pump;
-        for event in event_pump.poll_iter() {
-            handle_event(event, &mut logic);
-        }
-
-        logic.update(dt);
-        if logic.is_over() {
-            break 'game_loop;
-        }
-
-        collide.collide_ball_and_wall(&mut logic);
-        collide.collide_ball_and_racket(&mut logic);
-
-        graphics.update(&logic, &window);
-        graphics.draw(&mut window, &ttf_context);
-    }
+    let mut pong = Pong::new(&audio, &ttf_context, seed)?;
+    run(&mut pong, &mut window, input)
 }
-